@@ -0,0 +1,57 @@
+const adapter = await navigator.gpu.requestAdapter();
+
+const device = await adapter.requestDevice();
+
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(message);
+  }
+}
+
+const validModule = device.createShaderModule({
+  code: `
+@compute
+@workgroup_size(1)
+fn main() {}
+`,
+});
+
+const validInfo = await validModule.getCompilationInfo();
+assert(
+  validInfo.messages.length === 0,
+  `expected no messages, got ${JSON.stringify(validInfo.messages)}`,
+);
+
+device.pushErrorScope("validation");
+const invalidModule = device.createShaderModule({
+  code: `
+@compute
+@workgroup_size(1)
+fn main() {
+    let x: u32 = undefined_value;
+}
+`,
+});
+const error = await device.popErrorScope();
+assert(error !== null, "expected a validation error");
+
+const invalidInfo = await invalidModule.getCompilationInfo();
+assert(
+  invalidInfo.messages.length === 1,
+  `expected one message, got ${JSON.stringify(invalidInfo.messages)}`,
+);
+
+const [message] = invalidInfo.messages;
+assert(message.type === "error", `unexpected type ${message.type}`);
+assert(
+  message.message.includes("undefined_value"),
+  `unexpected message ${message.message}`,
+);
+assert(message.lineNum === 5, `unexpected lineNum ${message.lineNum}`);
+assert(message.linePos === 18, `unexpected linePos ${message.linePos}`);
+assert(
+  message.length === "undefined_value".length,
+  `unexpected length ${message.length}`,
+);
+
+device.destroy();
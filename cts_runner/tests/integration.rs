@@ -25,3 +25,14 @@ fn hello_compute_example() {
         .unwrap();
     assert!(output.status.success())
 }
+
+#[test]
+fn compilation_info_example() {
+    let output = std::process::Command::new(cts_runner_exe_path())
+        .arg("examples/compilation-info.js")
+        .spawn()
+        .unwrap()
+        .wait_with_output()
+        .unwrap();
+    assert!(output.status.success())
+}
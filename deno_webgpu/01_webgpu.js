@@ -24,6 +24,7 @@ const {
   Error,
   MathMax,
   ObjectDefineProperty,
+  ObjectFreeze,
   ObjectPrototypeIsPrototypeOf,
   Promise,
   PromisePrototypeCatch,
@@ -2570,6 +2571,20 @@ class GPUShaderModule {
     webidl.illegalConstructor();
   }
 
+  /**
+   * @returns {Promise<GPUCompilationInfo>}
+   */
+  getCompilationInfo() {
+    webidl.assertBranded(this, GPUShaderModulePrototype);
+    const prefix =
+      "Failed to execute 'getCompilationInfo' on 'GPUShaderModule'";
+    const shaderModuleRid = assertResource(this, prefix, "this");
+    const messages = ops.op_webgpu_shader_module_get_compilation_info(
+      shaderModuleRid,
+    );
+    return PromiseResolve({ messages: ObjectFreeze(messages) });
+  }
+
   [SymbolFor("Deno.privateCustomInspect")](inspect, inspectOptions) {
     return inspect(
       createFilteredInspectProxy({
@@ -205,6 +205,7 @@ pub fn op_webgpu_create_bind_group_layout(
     let descriptor = wgpu_core::binding_model::BindGroupLayoutDescriptor {
         label: Some(label),
         entries: Cow::from(entries),
+        flags: wgpu_types::BindGroupLayoutFlags::empty(),
     };
 
     gfx_put!(device => instance.device_create_bind_group_layout(
@@ -11,6 +11,7 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::time::Duration;
+use wgpu_core::resource::BufferAccessError;
 use wgpu_core::resource::BufferAccessResult;
 
 use super::error::DomExceptionOperationError;
@@ -31,7 +32,9 @@ impl Resource for WebGpuBuffer {
     }
 }
 
-struct WebGpuBufferMapped(*mut u8, usize);
+/// The mapped range, or `None` for buffers mapped for reading, along with its
+/// size.
+struct WebGpuBufferMapped(Option<*mut u8>, usize);
 impl Resource for WebGpuBufferMapped {
     fn name(&self) -> Cow<str> {
         "webGPUBufferMapped".into()
@@ -156,19 +159,33 @@ pub fn op_webgpu_buffer_get_mapped_range(
     let buffer_resource = state.resource_table.get::<WebGpuBuffer>(buffer_rid)?;
     let buffer = buffer_resource.1;
 
-    let (slice_pointer, range_size) = gfx_select!(buffer => instance.buffer_get_mapped_range(
+    // Buffers mapped for reading only give out a read-only range.
+    let mapped = gfx_select!(buffer => instance.buffer_get_mapped_range(
       buffer,
       offset,
       size
-    ))
-    .map_err(|e| DomExceptionOperationError::new(&e.to_string()))?;
+    ));
+    let (slice_pointer, mut_pointer, range_size) = match mapped {
+        Ok((pointer, range_size)) => (pointer.cast_const(), Some(pointer), range_size),
+        Err(BufferAccessError::MappedForReadOnly) => {
+            let (pointer, range_size) =
+                gfx_select!(buffer => instance.buffer_get_mapped_const_range(
+                  buffer,
+                  offset,
+                  size
+                ))
+                .map_err(|e| DomExceptionOperationError::new(&e.to_string()))?;
+            (pointer, None, range_size)
+        }
+        Err(e) => return Err(DomExceptionOperationError::new(&e.to_string()).into()),
+    };
 
-    let slice = unsafe { std::slice::from_raw_parts_mut(slice_pointer, range_size as usize) };
+    let slice = unsafe { std::slice::from_raw_parts(slice_pointer, range_size as usize) };
     buf.copy_from_slice(slice);
 
     let rid = state
         .resource_table
-        .add(WebGpuBufferMapped(slice_pointer, range_size as usize));
+        .add(WebGpuBufferMapped(mut_pointer, range_size as usize));
 
     Ok(WebGpuResult::rid(rid))
 }
@@ -189,7 +206,13 @@ pub fn op_webgpu_buffer_unmap(
     let buffer = buffer_resource.1;
 
     if let Some(buf) = buf {
-        let slice = unsafe { std::slice::from_raw_parts_mut(mapped_resource.0, mapped_resource.1) };
+        let Some(pointer) = mapped_resource.0 else {
+            return Err(DomExceptionOperationError::new(
+                "Buffer is mapped for reading, and its mapped range can't be written to",
+            )
+            .into());
+        };
+        let slice = unsafe { std::slice::from_raw_parts_mut(pointer, mapped_resource.1) };
         slice.copy_from_slice(buf);
     }
 
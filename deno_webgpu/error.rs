@@ -13,6 +13,7 @@ use wgpu_core::command::ClearError;
 use wgpu_core::command::CommandEncoderError;
 use wgpu_core::command::ComputePassError;
 use wgpu_core::command::CopyError;
+use wgpu_core::command::CreateCommandEncoderError;
 use wgpu_core::command::CreateRenderBundleError;
 use wgpu_core::command::QueryError;
 use wgpu_core::command::RenderBundleError;
@@ -169,6 +170,17 @@ impl From<CommandEncoderError> for WebGpuError {
     }
 }
 
+impl From<CreateCommandEncoderError> for WebGpuError {
+    fn from(err: CreateCommandEncoderError) -> Self {
+        match err {
+            CreateCommandEncoderError::DeviceLost => WebGpuError::Lost,
+            CreateCommandEncoderError::OutOfMemory => WebGpuError::OutOfMemory,
+            CreateCommandEncoderError::Device(err) => err.into(),
+            err => WebGpuError::Validation(fmt_err(&err)),
+        }
+    }
+}
+
 impl From<QueryError> for WebGpuError {
     fn from(err: QueryError) -> Self {
         WebGpuError::Validation(fmt_err(&err))
@@ -257,6 +269,7 @@ impl From<QueueSubmitError> for WebGpuError {
     fn from(err: QueueSubmitError) -> Self {
         match err {
             QueueSubmitError::Queue(err) => err.into(),
+            QueueSubmitError::Write(err) => err.into(),
             err => WebGpuError::Validation(fmt_err(&err)),
         }
     }
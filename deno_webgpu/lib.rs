@@ -220,6 +220,7 @@ deno_core::extension!(
         queue::op_webgpu_write_texture,
         // shader
         shader::op_webgpu_create_shader_module,
+        shader::op_webgpu_shader_module_get_compilation_info,
     ],
     esm = ["01_webgpu.js"],
     options = { unstable: bool },
@@ -34,6 +34,14 @@ pub fn op_webgpu_queue_submit(
 
     let maybe_err = gfx_select!(queue => instance.queue_submit(queue, &ids)).err();
 
+    // A failed submission submits nothing, but still consumes the command
+    // buffers.
+    if maybe_err.is_some() {
+        for id in &ids {
+            gfx_select!(id => instance.command_buffer_drop(*id));
+        }
+    }
+
     for rid in command_buffers {
         let resource = state.resource_table.take::<WebGpuCommandBuffer>(rid)?;
         resource.close();
@@ -5,6 +5,7 @@ use deno_core::op2;
 use deno_core::OpState;
 use deno_core::Resource;
 use deno_core::ResourceId;
+use serde::Serialize;
 use std::borrow::Cow;
 use std::rc::Rc;
 
@@ -39,17 +40,69 @@ pub fn op_webgpu_create_shader_module(
         .get::<super::WebGpuDevice>(device_rid)?;
     let device = device_resource.1;
 
-    let source = wgpu_core::pipeline::ShaderModuleSource::Wgsl(code);
+    let source = wgpu_core::pipeline::ShaderModuleSource::Wgsl(Cow::Borrowed(&code));
 
     let descriptor = wgpu_core::pipeline::ShaderModuleDescriptor {
         label: Some(label),
         shader_bound_checks: wgpu_types::ShaderBoundChecks::default(),
     };
 
-    gfx_put!(device => instance.device_create_shader_module(
-    device,
-    &descriptor,
-    source,
-    ()
-  ) => state, WebGpuShaderModule)
+    let (val, maybe_err) = gfx_select!(device => instance.device_create_shader_module(
+        device,
+        &descriptor,
+        source,
+        ()
+    ));
+
+    let rid = state
+        .resource_table
+        .add(WebGpuShaderModule(instance.clone(), val));
+    Ok(WebGpuResult::rid_err(rid, maybe_err))
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuCompilationMessage {
+    message: String,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    line_num: u32,
+    line_pos: u32,
+    offset: u32,
+    length: u32,
+}
+
+impl From<wgpu_core::pipeline::ShaderCompilationMessage> for GpuCompilationMessage {
+    fn from(message: wgpu_core::pipeline::ShaderCompilationMessage) -> Self {
+        let location = message.location;
+        Self {
+            message: message.message,
+            kind: "error",
+            line_num: location.map_or(0, |l| l.line_number),
+            line_pos: location.map_or(0, |l| l.line_position),
+            offset: location.map_or(0, |l| l.offset),
+            length: location.map_or(0, |l| l.length),
+        }
+    }
+}
+
+/// The messages produced when compiling the shader module.
+///
+/// naga does not report warnings, so this is empty unless compilation
+/// failed.
+#[op2]
+#[serde]
+pub fn op_webgpu_shader_module_get_compilation_info(
+    state: &mut OpState,
+    #[smi] shader_module_rid: ResourceId,
+) -> Result<Vec<GpuCompilationMessage>, AnyError> {
+    let instance = state.borrow::<super::Instance>();
+    let shader_module_resource = state
+        .resource_table
+        .get::<WebGpuShaderModule>(shader_module_rid)?;
+    let shader_module = shader_module_resource.1;
+
+    let messages =
+        gfx_select!(shader_module => instance.shader_module_get_compilation_info(shader_module));
+    Ok(messages.into_iter().map(Into::into).collect())
 }
@@ -0,0 +1,1906 @@
+/*! Tests of `Global` entry points which `wgpu` doesn't expose.
+ *  Like the trace tests, they run on every backend available on the system.
+!*/
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
+
+use player::IdentityPassThroughFactory;
+use wgc::{
+    binding_model::{
+        BindGroupLayoutEntryError, CreateBindGroupError, CreateBindGroupLayoutError,
+        CreatePipelineLayoutError,
+    },
+    command::{CreateCommandEncoderError, TransferError},
+    device::{
+        queue::{QueueSubmitError, QueueWriteError},
+        DeviceLostClosure,
+    },
+    global::Global,
+    hal_api::HalApi,
+    id::{self, TypedId},
+    identity::{GlobalIdentityHandlerFactory, IdentityManagerFactory, Input},
+    pipeline::CreateComputePipelineError,
+    registry::{ResourceLogEvent, ResourceLogRecord},
+    resource::{BufferAccessError, BufferDropResult, DestroyError},
+};
+
+type TestGlobal = Global<IdentityManagerFactory>;
+type PassThroughGlobal = Global<IdentityPassThroughFactory>;
+
+const BACKENDS: &[wgt::Backend] = &[
+    wgt::Backend::Vulkan,
+    wgt::Backend::Metal,
+    wgt::Backend::Dx12,
+    wgt::Backend::Gl,
+];
+
+/// A test which is run once for every backend with an adapter.
+trait GlobalTest {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId);
+}
+
+/// Like [`GlobalTest`], for tests which pick their own ids.
+trait PassThroughTest {
+    fn run<A: HalApi>(&self, global: &PassThroughGlobal, adapter: id::AdapterId);
+}
+
+fn for_each_adapter<F: GlobalIdentityHandlerFactory>(
+    factory: impl Fn() -> F,
+    adapter_input: fn(wgt::Backend) -> Input<F, id::AdapterId>,
+    mut run: impl FnMut(&Global<F>, id::AdapterId),
+) {
+    for &backend in BACKENDS {
+        // A global per backend, so a test can't leave anything behind for
+        // the next one.
+        let global = Global::new(
+            "test",
+            factory(),
+            wgt::InstanceDescriptor {
+                backends: backend.into(),
+                flags: wgt::InstanceFlags::debugging(),
+                dx12_shader_compiler: wgt::Dx12Compiler::Fxc,
+                gles_minor_version: wgt::Gles3MinorVersion::default(),
+            },
+        );
+        let adapter = match global.request_adapter(
+            &wgc::instance::RequestAdapterOptions {
+                power_preference: wgt::PowerPreference::None,
+                force_fallback_adapter: false,
+                compatible_surface: None,
+            },
+            wgc::instance::AdapterInputs::Mask(backend.into(), adapter_input),
+        ) {
+            Ok(adapter) => adapter,
+            Err(_) => continue,
+        };
+
+        println!("\tBackend {:?}", backend);
+        run(&global, adapter);
+    }
+}
+
+fn run_on_all_backends<T: GlobalTest>(test: T) {
+    for_each_adapter(
+        || IdentityManagerFactory,
+        |_| (),
+        |global, adapter| wgc::gfx_select!(adapter => test.run(global, adapter)),
+    );
+}
+
+fn run_on_all_backends_pass_through<T: PassThroughTest>(test: T) {
+    for_each_adapter(
+        || IdentityPassThroughFactory,
+        |backend| id::AdapterId::zip(0, 1, backend),
+        |global, adapter| wgc::gfx_select!(adapter => test.run(global, adapter)),
+    );
+}
+
+/// Request a device with `features`, or `None` if the adapter lacks them.
+fn request_device_with_features<A: HalApi>(
+    global: &TestGlobal,
+    adapter: id::AdapterId,
+    features: wgt::Features,
+) -> Option<(id::DeviceId, id::QueueId)> {
+    if !global
+        .adapter_features::<A>(adapter)
+        .unwrap()
+        .contains(features)
+    {
+        println!("\t\tSkipping, missing features {:?}", features);
+        return None;
+    }
+    let (device, queue, error) = global.adapter_request_device::<A>(
+        adapter,
+        &wgt::DeviceDescriptor {
+            label: None,
+            required_features: features,
+            required_limits: wgt::Limits::downlevel_webgl2_defaults(),
+        },
+        None,
+        (),
+        (),
+    );
+    if let Some(e) = error {
+        panic!("{:?}", e);
+    }
+    Some((device, queue))
+}
+
+fn request_device<A: HalApi>(
+    global: &TestGlobal,
+    adapter: id::AdapterId,
+) -> (id::DeviceId, id::QueueId) {
+    request_device_with_features::<A>(global, adapter, wgt::Features::empty()).unwrap()
+}
+
+fn request_device_pass_through<A: HalApi>(
+    global: &PassThroughGlobal,
+    adapter: id::AdapterId,
+) -> (id::DeviceId, id::QueueId) {
+    let (device, queue, error) = global.adapter_request_device::<A>(
+        adapter,
+        &wgt::DeviceDescriptor {
+            label: None,
+            required_features: wgt::Features::empty(),
+            required_limits: wgt::Limits::downlevel_webgl2_defaults(),
+        },
+        None,
+        id::DeviceId::zip(0, 1, A::VARIANT),
+        id::QueueId::zip(0, 1, A::VARIANT),
+    );
+    if let Some(e) = error {
+        panic!("{:?}", e);
+    }
+    (device, queue)
+}
+
+fn create_buffer<A: HalApi>(
+    global: &TestGlobal,
+    device: id::DeviceId,
+    size: wgt::BufferAddress,
+    usage: wgt::BufferUsages,
+) -> id::BufferId {
+    let (buffer, error) = global.device_create_buffer::<A>(
+        device,
+        &wgt::BufferDescriptor {
+            label: None,
+            size,
+            usage,
+            mapped_at_creation: false,
+        },
+        (),
+    );
+    if let Some(e) = error {
+        panic!("{:?}", e);
+    }
+    buffer
+}
+
+fn create_texture<A: HalApi>(
+    global: &TestGlobal,
+    device: id::DeviceId,
+    desc: &wgc::resource::TextureDescriptor,
+) -> id::TextureId {
+    let (texture, error) = global.device_create_texture::<A>(device, desc, ());
+    if let Some(e) = error {
+        panic!("{:?}", e);
+    }
+    texture
+}
+
+/// Map `buffer`, which must have `MAP_READ` usage, and return its contents.
+fn read_buffer<A: HalApi>(
+    global: &TestGlobal,
+    device: id::DeviceId,
+    buffer: id::BufferId,
+    size: wgt::BufferAddress,
+) -> Vec<u8> {
+    global
+        .buffer_map_async::<A>(
+            buffer,
+            0..size,
+            wgc::resource::BufferMapOperation {
+                host: wgc::device::HostMap::Read,
+                callback: None,
+            },
+        )
+        .unwrap();
+    global
+        .device_poll::<A>(device, wgt::Maintain::Wait)
+        .unwrap();
+    let (ptr, len) = global
+        .buffer_get_mapped_const_range::<A>(buffer, 0, Some(size))
+        .unwrap();
+    let contents = unsafe { std::slice::from_raw_parts(ptr, len as usize) }.to_vec();
+    global.buffer_unmap::<A>(buffer).unwrap();
+    contents
+}
+
+/// Copy mip level `mip_level` of the `Rgba8Unorm` 2D `texture`, which must
+/// have `COPY_SRC` usage, to the CPU and return its tightly packed rows.
+fn read_texture<A: HalApi>(
+    global: &TestGlobal,
+    device: id::DeviceId,
+    queue: id::QueueId,
+    texture: id::TextureId,
+    mip_level: u32,
+    size: wgt::Extent3d,
+) -> Vec<u8> {
+    let row_size = size.width as usize * 4;
+    let padded_row_size = wgt::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let buffer_size = (padded_row_size * size.height) as wgt::BufferAddress;
+    let buffer = create_buffer::<A>(
+        global,
+        device,
+        buffer_size,
+        wgt::BufferUsages::MAP_READ | wgt::BufferUsages::COPY_DST,
+    );
+    // Garbage the copy has to overwrite, so a copy which does nothing can't
+    // pass for a cleared texture.
+    global
+        .queue_write_buffer::<A>(queue, buffer, 0, &vec![0xAA; buffer_size as usize])
+        .unwrap();
+
+    let (encoder, error) = global.device_create_command_encoder::<A>(
+        device,
+        &wgt::CommandEncoderDescriptor { label: None },
+        (),
+    );
+    assert!(error.is_none(), "{:?}", error);
+    global
+        .command_encoder_copy_texture_to_buffer::<A>(
+            encoder,
+            &wgc::command::ImageCopyTexture {
+                texture,
+                mip_level,
+                origin: wgt::Origin3d::ZERO,
+                aspect: wgt::TextureAspect::All,
+            },
+            &wgc::command::ImageCopyBuffer {
+                buffer,
+                layout: wgt::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: None,
+                },
+            },
+            &size,
+        )
+        .unwrap();
+    let (command_buffer, error) =
+        global.command_encoder_finish::<A>(encoder, &wgt::CommandBufferDescriptor { label: None });
+    assert!(error.is_none(), "{:?}", error);
+    global.queue_submit::<A>(queue, &[command_buffer]).unwrap();
+
+    let contents = read_buffer::<A>(global, device, buffer, buffer_size);
+    global.buffer_drop::<A>(buffer, false);
+    contents
+        .chunks(padded_row_size as usize)
+        .flat_map(|row| &row[..row_size])
+        .copied()
+        .collect()
+}
+
+struct ClearDeviceResources;
+
+impl GlobalTest for ClearDeviceResources {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (cleared, cleared_queue) = request_device::<A>(global, adapter);
+        let (kept, kept_queue) = request_device::<A>(global, adapter);
+        let cleared_buffer = create_buffer::<A>(global, cleared, 16, wgt::BufferUsages::COPY_DST);
+        let kept_buffer = create_buffer::<A>(global, kept, 16, wgt::BufferUsages::COPY_DST);
+
+        global.clear_device_resources::<A>(cleared);
+
+        // The other device and its resources are untouched.
+        global
+            .queue_write_buffer::<A>(kept_queue, kept_buffer, 0, &[1; 16])
+            .unwrap();
+        global.device_poll::<A>(kept, wgt::Maintain::Wait).unwrap();
+
+        // The ids of the cleared device are invalid, using and dropping them
+        // fails without panicking.
+        assert!(global
+            .queue_write_buffer::<A>(cleared_queue, cleared_buffer, 0, &[1; 16])
+            .is_err());
+        let (_, error) = global.device_create_buffer::<A>(
+            cleared,
+            &wgt::BufferDescriptor {
+                label: None,
+                size: 16,
+                usage: wgt::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+            (),
+        );
+        assert!(error.is_some());
+        global.buffer_drop::<A>(cleared_buffer, false);
+        global.queue_drop::<A>(cleared_queue);
+        global.device_drop::<A>(cleared);
+
+        global.buffer_drop::<A>(kept_buffer, false);
+        global.queue_drop::<A>(kept_queue);
+        global.device_drop::<A>(kept);
+    }
+}
+
+#[test]
+fn clear_device_resources() {
+    run_on_all_backends(ClearDeviceResources);
+}
+
+struct EnumerateRegisteredAdapters;
+
+impl GlobalTest for EnumerateRegisteredAdapters {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let backends = wgt::Backends::from(A::VARIANT);
+        assert_eq!(global.instance_enumerate_adapters(backends), [adapter]);
+
+        // Enumerating registers every adapter of the backend once more.
+        let enumerated =
+            global.enumerate_adapters(wgc::instance::AdapterInputs::Mask(backends, |_| ()));
+        assert!(!enumerated.is_empty());
+        let registered = global.instance_enumerate_adapters(backends);
+        assert_eq!(registered.len(), enumerated.len() + 1);
+        assert!(registered.contains(&adapter));
+        assert!(enumerated.iter().all(|id| registered.contains(id)));
+
+        // Other backends are not listed.
+        assert!(global
+            .instance_enumerate_adapters(wgt::Backends::all() - backends)
+            .is_empty());
+
+        for id in enumerated {
+            global.adapter_drop::<A>(id);
+        }
+        assert_eq!(global.instance_enumerate_adapters(backends), [adapter]);
+
+        global.adapter_drop::<A>(adapter);
+        assert!(global.instance_enumerate_adapters(backends).is_empty());
+    }
+}
+
+#[test]
+fn enumerate_registered_adapters() {
+    run_on_all_backends(EnumerateRegisteredAdapters);
+}
+
+struct DeviceDestroyThenMarkLost;
+
+impl GlobalTest for DeviceDestroyThenMarkLost {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let calls_clone = calls.clone();
+        global.device_set_device_lost_closure::<A>(
+            device,
+            DeviceLostClosure::from_rust(Box::new(move |reason, _message| {
+                calls_clone.lock().unwrap().push(reason);
+            })),
+        );
+
+        // The loss reported after destroying the device must not fire the
+        // closure a second time, nor override the reason from destroy.
+        global.device_destroy::<A>(device);
+        global.device_mark_lost::<A>(device, wgt::DeviceLostReason::Unknown, "lost");
+        global.device_mark_lost::<A>(device, wgt::DeviceLostReason::Unknown, "lost again");
+        let _ = global.device_poll::<A>(device, wgt::Maintain::Wait);
+
+        assert!(
+            matches!(
+                calls.lock().unwrap()[..],
+                [wgt::DeviceLostReason::Destroyed]
+            ),
+            "Device lost closure should have been called exactly once, with the reason from destroy."
+        );
+
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+        assert_eq!(calls.lock().unwrap().len(), 1);
+    }
+}
+
+#[test]
+fn device_destroy_then_mark_lost() {
+    run_on_all_backends(DeviceDestroyThenMarkLost);
+}
+
+struct QuerySetGetDescriptor;
+
+impl GlobalTest for QuerySetGetDescriptor {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let Some((device, queue)) =
+            request_device_with_features::<A>(global, adapter, wgt::Features::TIMESTAMP_QUERY)
+        else {
+            return;
+        };
+
+        let (query_set, error) = global.device_create_query_set::<A>(
+            device,
+            &wgt::QuerySetDescriptor {
+                label: Some("timestamps".into()),
+                ty: wgt::QueryType::Timestamp,
+                count: 8,
+            },
+            (),
+        );
+        assert!(error.is_none(), "{:?}", error);
+
+        let desc = global.query_set_get_descriptor::<A>(query_set).unwrap();
+        assert_eq!(desc.label.as_deref(), Some("timestamps"));
+        assert!(matches!(desc.ty, wgt::QueryType::Timestamp));
+        assert_eq!(desc.count, 8);
+
+        global.query_set_drop::<A>(query_set);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn query_set_get_descriptor() {
+    run_on_all_backends(QuerySetGetDescriptor);
+}
+
+struct CreateCommandEncoderOnLostDevice;
+
+impl GlobalTest for CreateCommandEncoderOnLostDevice {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+
+        let (encoder, error) = global.device_create_command_encoder::<A>(
+            device,
+            &wgt::CommandEncoderDescriptor { label: None },
+            (),
+        );
+        assert!(error.is_none(), "{:?}", error);
+        global.command_encoder_drop::<A>(encoder);
+
+        global.device_mark_lost::<A>(device, wgt::DeviceLostReason::Unknown, "lost");
+
+        let (encoder, error) = global.device_create_command_encoder::<A>(
+            device,
+            &wgt::CommandEncoderDescriptor { label: None },
+            (),
+        );
+        assert!(
+            matches!(error, Some(CreateCommandEncoderError::DeviceLost)),
+            "{:?}",
+            error
+        );
+        global.command_encoder_drop::<A>(encoder);
+
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn create_command_encoder_on_lost_device() {
+    run_on_all_backends(CreateCommandEncoderOnLostDevice);
+}
+
+struct TextureCreateViewReflect;
+
+impl GlobalTest for TextureCreateViewReflect {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+        let texture = create_texture::<A>(
+            global,
+            device,
+            &wgt::TextureDescriptor {
+                label: None,
+                size: wgt::Extent3d {
+                    width: 16,
+                    height: 16,
+                    depth_or_array_layers: 4,
+                },
+                mip_level_count: 2,
+                sample_count: 1,
+                dimension: wgt::TextureDimension::D2,
+                format: wgt::TextureFormat::Rgba8Unorm,
+                usage: wgt::TextureUsages::TEXTURE_BINDING,
+                view_formats: Vec::new(),
+            },
+        );
+
+        let (view, resolved) = global.texture_create_view_reflect::<A>(
+            texture,
+            &wgc::resource::TextureViewDescriptor::default(),
+            (),
+        );
+        let resolved = resolved.unwrap();
+        assert_eq!(resolved.format, Some(wgt::TextureFormat::Rgba8Unorm));
+        assert_eq!(resolved.dimension, Some(wgt::TextureViewDimension::D2Array));
+        assert_eq!(
+            resolved.range,
+            wgt::ImageSubresourceRange {
+                aspect: wgt::TextureAspect::All,
+                base_mip_level: 0,
+                mip_level_count: Some(2),
+                base_array_layer: 0,
+                array_layer_count: Some(4),
+            }
+        );
+
+        global.texture_view_drop::<A>(view, false).unwrap();
+        global.texture_drop::<A>(texture, false);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn texture_create_view_reflect() {
+    run_on_all_backends(TextureCreateViewReflect);
+}
+
+struct QueueValidateWriteTexture;
+
+impl GlobalTest for QueueValidateWriteTexture {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+        let size = wgt::Extent3d {
+            width: 64,
+            height: 4,
+            depth_or_array_layers: 1,
+        };
+        let texture = create_texture::<A>(
+            global,
+            device,
+            &wgt::TextureDescriptor {
+                label: None,
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgt::TextureDimension::D2,
+                format: wgt::TextureFormat::Rgba8Unorm,
+                usage: wgt::TextureUsages::COPY_DST,
+                view_formats: Vec::new(),
+            },
+        );
+        let destination = wgc::command::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgt::Origin3d::ZERO,
+            aspect: wgt::TextureAspect::All,
+        };
+        let layout = |bytes_per_row| wgt::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(bytes_per_row),
+            rows_per_image: None,
+        };
+
+        global
+            .queue_validate_write_texture::<A>(queue, &destination, &layout(256), &size)
+            .unwrap();
+
+        // A row of 64 texels takes 256 bytes, 255 doesn't fit it.
+        let error = global
+            .queue_validate_write_texture::<A>(queue, &destination, &layout(255), &size)
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                QueueWriteError::Transfer(TransferError::InvalidBytesPerRow)
+            ),
+            "{:?}",
+            error
+        );
+
+        // The real write agrees with the validator.
+        let error = global
+            .queue_write_texture::<A>(queue, &destination, &[0; 1024], &layout(255), &size)
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                QueueWriteError::Transfer(TransferError::InvalidBytesPerRow)
+            ),
+            "{:?}",
+            error
+        );
+
+        global.texture_drop::<A>(texture, false);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn queue_validate_write_texture() {
+    run_on_all_backends(QueueValidateWriteTexture);
+}
+
+struct DeviceTickFiresMapCallback;
+
+impl GlobalTest for DeviceTickFiresMapCallback {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+        let buffer = create_buffer::<A>(
+            global,
+            device,
+            16,
+            wgt::BufferUsages::MAP_READ | wgt::BufferUsages::COPY_DST,
+        );
+        global
+            .queue_write_buffer::<A>(queue, buffer, 0, &[1; 16])
+            .unwrap();
+        global.queue_submit::<A>(queue, &[]).unwrap();
+
+        let mapped = Arc::new(Mutex::new(None));
+        let mapped_clone = mapped.clone();
+        global
+            .buffer_map_async::<A>(
+                buffer,
+                0..16,
+                wgc::resource::BufferMapOperation {
+                    host: wgc::device::HostMap::Read,
+                    callback: Some(wgc::resource::BufferMapCallback::from_rust(Box::new(
+                        move |result| *mapped_clone.lock().unwrap() = Some(result),
+                    ))),
+                },
+            )
+            .unwrap();
+
+        // Ticking never waits, so keep ticking until the GPU is done with the
+        // write the mapping waits on.
+        let start = std::time::Instant::now();
+        while mapped.lock().unwrap().is_none() {
+            assert!(
+                start.elapsed() < std::time::Duration::from_secs(10),
+                "Map callback didn't fire"
+            );
+            global.device_tick::<A>(device).unwrap();
+            std::thread::yield_now();
+        }
+        assert!(matches!(*mapped.lock().unwrap(), Some(Ok(()))));
+
+        global.buffer_unmap::<A>(buffer).unwrap();
+        global.buffer_drop::<A>(buffer, false);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn device_tick_fires_map_callback() {
+    run_on_all_backends(DeviceTickFiresMapCallback);
+}
+
+struct ColoredDebugMarkers;
+
+impl GlobalTest for ColoredDebugMarkers {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+
+        let (encoder, error) = global.device_create_command_encoder::<A>(
+            device,
+            &wgt::CommandEncoderDescriptor { label: None },
+            (),
+        );
+        assert!(error.is_none(), "{:?}", error);
+        global
+            .command_encoder_push_debug_group_colored::<A>(encoder, "group", [1.0, 0.0, 0.0, 1.0])
+            .unwrap();
+        global
+            .command_encoder_insert_debug_marker_colored::<A>(
+                encoder,
+                "marker",
+                [0.0, 1.0, 0.0, 1.0],
+            )
+            .unwrap();
+        global
+            .command_encoder_pop_debug_group::<A>(encoder)
+            .unwrap();
+        let (command_buffer, error) = global
+            .command_encoder_finish::<A>(encoder, &wgt::CommandBufferDescriptor { label: None });
+        assert!(error.is_none(), "{:?}", error);
+
+        global.queue_submit::<A>(queue, &[command_buffer]).unwrap();
+        global
+            .device_poll::<A>(device, wgt::Maintain::Wait)
+            .unwrap();
+
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn colored_debug_markers() {
+    run_on_all_backends(ColoredDebugMarkers);
+}
+
+struct CoalescedWriteContents;
+
+impl GlobalTest for CoalescedWriteContents {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+        let buffer = create_buffer::<A>(
+            global,
+            device,
+            32,
+            wgt::BufferUsages::MAP_READ | wgt::BufferUsages::COPY_DST,
+        );
+
+        global.queue_set_write_coalescing::<A>(queue, true).unwrap();
+        // Two adjacent regions, and a third overlapping both. Later writes
+        // must win where they overlap.
+        global
+            .queue_write_buffer::<A>(queue, buffer, 0, &[1; 8])
+            .unwrap();
+        global
+            .queue_write_buffer::<A>(queue, buffer, 8, &[2; 8])
+            .unwrap();
+        global
+            .queue_write_buffer::<A>(queue, buffer, 4, &[3; 8])
+            .unwrap();
+        global.queue_submit::<A>(queue, &[]).unwrap();
+
+        let mut expected = [0; 32];
+        expected[0..4].fill(1);
+        expected[4..12].fill(3);
+        expected[12..16].fill(2);
+        assert_eq!(read_buffer::<A>(global, device, buffer, 32), expected);
+
+        global.buffer_drop::<A>(buffer, false);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn coalesced_write_contents() {
+    run_on_all_backends(CoalescedWriteContents);
+}
+
+struct ClearTextureAll;
+
+impl GlobalTest for ClearTextureAll {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let Some((device, queue)) =
+            request_device_with_features::<A>(global, adapter, wgt::Features::CLEAR_TEXTURE)
+        else {
+            return;
+        };
+        let size = wgt::Extent3d {
+            width: 16,
+            height: 16,
+            depth_or_array_layers: 1,
+        };
+        let texture = create_texture::<A>(
+            global,
+            device,
+            &wgt::TextureDescriptor {
+                label: None,
+                size,
+                mip_level_count: 3,
+                sample_count: 1,
+                dimension: wgt::TextureDimension::D2,
+                format: wgt::TextureFormat::Rgba8Unorm,
+                usage: wgt::TextureUsages::COPY_SRC | wgt::TextureUsages::COPY_DST,
+                view_formats: Vec::new(),
+            },
+        );
+
+        let mip_sizes = (0..3)
+            .map(|mip| (mip, size.mip_level_size(mip, wgt::TextureDimension::D2)))
+            .collect::<Vec<_>>();
+        for &(mip, mip_size) in &mip_sizes {
+            global
+                .queue_write_texture::<A>(
+                    queue,
+                    &wgc::command::ImageCopyTexture {
+                        texture,
+                        mip_level: mip,
+                        origin: wgt::Origin3d::ZERO,
+                        aspect: wgt::TextureAspect::All,
+                    },
+                    &vec![0xFF; (mip_size.width * mip_size.height * 4) as usize],
+                    &wgt::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(mip_size.width * 4),
+                        rows_per_image: None,
+                    },
+                    &mip_size,
+                )
+                .unwrap();
+        }
+
+        // Make sure the readback sees the written data, so zeroes afterwards
+        // can only come from the clear.
+        for &(mip, mip_size) in &mip_sizes {
+            let contents = read_texture::<A>(global, device, queue, texture, mip, mip_size);
+            assert!(
+                contents.iter().all(|&byte| byte == 0xFF),
+                "Mip level {mip} wasn't written"
+            );
+        }
+
+        let (encoder, error) = global.device_create_command_encoder::<A>(
+            device,
+            &wgt::CommandEncoderDescriptor { label: None },
+            (),
+        );
+        assert!(error.is_none(), "{:?}", error);
+        global
+            .command_encoder_clear_texture_all::<A>(encoder, texture)
+            .unwrap();
+        let (command_buffer, error) = global
+            .command_encoder_finish::<A>(encoder, &wgt::CommandBufferDescriptor { label: None });
+        assert!(error.is_none(), "{:?}", error);
+        global.queue_submit::<A>(queue, &[command_buffer]).unwrap();
+
+        for (mip, mip_size) in mip_sizes {
+            let contents = read_texture::<A>(global, device, queue, texture, mip, mip_size);
+            assert!(
+                contents.iter().all(|&byte| byte == 0),
+                "Mip level {mip} wasn't cleared"
+            );
+        }
+
+        global.texture_drop::<A>(texture, false);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn clear_texture_all() {
+    run_on_all_backends(ClearTextureAll);
+}
+
+struct BufferDropTracked;
+
+impl GlobalTest for BufferDropTracked {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+
+        // An unused buffer is freed right away.
+        let buffer = create_buffer::<A>(global, device, 16, wgt::BufferUsages::COPY_DST);
+        assert_eq!(
+            global.buffer_drop_tracked::<A>(buffer, false),
+            BufferDropResult::Freed
+        );
+
+        // Without waiting, a buffer with pending writes has to be deferred,
+        // since nothing has been submitted yet.
+        let buffer = create_buffer::<A>(global, device, 16, wgt::BufferUsages::COPY_DST);
+        global
+            .queue_write_buffer::<A>(queue, buffer, 0, &[1; 16])
+            .unwrap();
+        assert_eq!(
+            global.buffer_drop_tracked::<A>(buffer, false),
+            BufferDropResult::Deferred
+        );
+
+        // Waiting doesn't submit the pending writes, so there is still
+        // nothing to wait for.
+        let buffer = create_buffer::<A>(global, device, 16, wgt::BufferUsages::COPY_DST);
+        global
+            .queue_write_buffer::<A>(queue, buffer, 0, &[1; 16])
+            .unwrap();
+        assert_eq!(
+            global.buffer_drop_tracked::<A>(buffer, true),
+            BufferDropResult::Deferred
+        );
+
+        // Once the writes are submitted, waiting frees the buffer.
+        let buffer = create_buffer::<A>(global, device, 16, wgt::BufferUsages::COPY_DST);
+        global
+            .queue_write_buffer::<A>(queue, buffer, 0, &[1; 16])
+            .unwrap();
+        global.queue_submit::<A>(queue, &[]).unwrap();
+        assert_eq!(
+            global.buffer_drop_tracked::<A>(buffer, true),
+            BufferDropResult::Freed
+        );
+
+        // An invalid buffer isn't dropped at all.
+        let (buffer, error) = global.device_create_buffer::<A>(
+            device,
+            &wgt::BufferDescriptor {
+                label: None,
+                size: 16,
+                usage: wgt::BufferUsages::empty(),
+                mapped_at_creation: false,
+            },
+            (),
+        );
+        assert!(error.is_some());
+        assert_eq!(
+            global.buffer_drop_tracked::<A>(buffer, false),
+            BufferDropResult::Invalid
+        );
+
+        global
+            .device_poll::<A>(device, wgt::Maintain::Wait)
+            .unwrap();
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn buffer_drop_tracked() {
+    run_on_all_backends(BufferDropTracked);
+}
+
+struct LostReasonFilter;
+
+impl GlobalTest for LostReasonFilter {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let calls_clone = calls.clone();
+        global.device_set_device_lost_closure::<A>(
+            device,
+            DeviceLostClosure::from_rust(Box::new(move |reason, _message| {
+                calls_clone.lock().unwrap().push(reason);
+            })),
+        );
+        global.device_set_lost_reason_filter::<A>(
+            device,
+            Box::new(|reason| !matches!(reason, wgt::DeviceLostReason::Unknown)),
+        );
+
+        // A filtered loss leaves the device usable.
+        global.device_mark_lost::<A>(device, wgt::DeviceLostReason::Unknown, "transient");
+        assert!(calls.lock().unwrap().is_empty());
+        let buffer = create_buffer::<A>(global, device, 16, wgt::BufferUsages::COPY_DST);
+        global.buffer_drop::<A>(buffer, false);
+
+        // Any other reason still loses it.
+        global.device_mark_lost::<A>(device, wgt::DeviceLostReason::Destroyed, "lost");
+        assert!(matches!(
+            calls.lock().unwrap()[..],
+            [wgt::DeviceLostReason::Destroyed]
+        ));
+
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn lost_reason_filter() {
+    run_on_all_backends(LostReasonFilter);
+}
+
+struct MarkLostReason;
+
+impl GlobalTest for MarkLostReason {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let calls_clone = calls.clone();
+        global.device_set_device_lost_closure::<A>(
+            device,
+            DeviceLostClosure::from_rust(Box::new(move |reason, message| {
+                calls_clone.lock().unwrap().push((reason, message));
+            })),
+        );
+
+        global.device_mark_lost::<A>(device, wgt::DeviceLostReason::Destroyed, "gone");
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        let (reason, ref message) = calls[0];
+        assert!(matches!(reason, wgt::DeviceLostReason::Destroyed));
+        assert_eq!(message, "gone");
+        drop(calls);
+
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn mark_lost_reason() {
+    run_on_all_backends(MarkLostReason);
+}
+
+struct RenderBundleGetCompatibility;
+
+impl GlobalTest for RenderBundleGetCompatibility {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+
+        let bundle_encoder = wgc::command::RenderBundleEncoder::new(
+            &wgc::command::RenderBundleEncoderDescriptor {
+                label: None,
+                color_formats: Cow::Borrowed(&[Some(wgt::TextureFormat::Rgba8Unorm)]),
+                depth_stencil: None,
+                sample_count: 1,
+                multiview: None,
+            },
+            device,
+            None,
+        )
+        .unwrap();
+        let (bundle, error) = global.render_bundle_encoder_finish::<A>(
+            bundle_encoder,
+            &wgt::RenderBundleDescriptor { label: None },
+            (),
+        );
+        assert!(error.is_none(), "{:?}", error);
+
+        let compatibility = global.render_bundle_get_compatibility::<A>(bundle).unwrap();
+        assert_eq!(
+            compatibility.color_formats,
+            [Some(wgt::TextureFormat::Rgba8Unorm)]
+        );
+        assert_eq!(compatibility.depth_stencil_format, None);
+        assert_eq!(compatibility.sample_count, 1);
+
+        // Executing the bundle in a pass with another color format is
+        // rejected, like the reported formats say.
+        let texture = create_texture::<A>(
+            global,
+            device,
+            &wgt::TextureDescriptor {
+                label: None,
+                size: wgt::Extent3d {
+                    width: 4,
+                    height: 4,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgt::TextureDimension::D2,
+                format: wgt::TextureFormat::Bgra8Unorm,
+                usage: wgt::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: Vec::new(),
+            },
+        );
+        let (view, error) = global.texture_create_view::<A>(
+            texture,
+            &wgc::resource::TextureViewDescriptor::default(),
+            (),
+        );
+        assert!(error.is_none(), "{:?}", error);
+
+        let (encoder, error) = global.device_create_command_encoder::<A>(
+            device,
+            &wgt::CommandEncoderDescriptor { label: None },
+            (),
+        );
+        assert!(error.is_none(), "{:?}", error);
+        let mut pass = wgc::command::RenderPass::new(
+            encoder,
+            &wgc::command::RenderPassDescriptor {
+                label: None,
+                color_attachments: Cow::Borrowed(&[Some(
+                    wgc::command::RenderPassColorAttachment {
+                        view,
+                        resolve_target: None,
+                        channel: wgc::command::PassChannel {
+                            load_op: wgc::command::LoadOp::Clear,
+                            store_op: wgc::command::StoreOp::Store,
+                            clear_value: wgt::Color::BLACK,
+                            read_only: false,
+                        },
+                    },
+                )]),
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            },
+        );
+        unsafe {
+            wgc::command::render_ffi::wgpu_render_pass_execute_bundles(&mut pass, &bundle, 1);
+        }
+        let error = global
+            .command_encoder_run_render_pass::<A>(encoder, &pass)
+            .unwrap_err();
+        let inner = std::error::Error::source(&error).unwrap().to_string();
+        assert!(
+            inner.contains("Incompatible color attachments"),
+            "{}",
+            inner
+        );
+
+        global.command_encoder_drop::<A>(encoder);
+        global.texture_view_drop::<A>(view, false).unwrap();
+        global.texture_drop::<A>(texture, false);
+        global.render_bundle_drop::<A>(bundle);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn render_bundle_compatibility() {
+    run_on_all_backends(RenderBundleGetCompatibility);
+}
+
+struct CreateSamplerError;
+
+impl PassThroughTest for CreateSamplerError {
+    fn run<A: HalApi>(&self, global: &PassThroughGlobal, adapter: id::AdapterId) {
+        let backend = A::VARIANT;
+        let (device, queue) = request_device_pass_through::<A>(global, adapter);
+
+        let sampler = id::SamplerId::zip(0, 1, backend);
+        global.create_sampler_error::<A>(sampler, Some("invalid".into()));
+
+        let (layout, error) = global.device_create_bind_group_layout::<A>(
+            device,
+            &wgc::binding_model::BindGroupLayoutDescriptor {
+                label: None,
+                entries: Cow::Borrowed(&[wgt::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgt::ShaderStages::FRAGMENT,
+                    ty: wgt::BindingType::Sampler(wgt::SamplerBindingType::Filtering),
+                    count: None,
+                }]),
+                flags: wgt::BindGroupLayoutFlags::empty(),
+            },
+            id::BindGroupLayoutId::zip(0, 1, backend),
+        );
+        assert!(error.is_none(), "{:?}", error);
+
+        let (bind_group, error) = global.device_create_bind_group::<A>(
+            device,
+            &wgc::binding_model::BindGroupDescriptor {
+                label: None,
+                layout,
+                entries: Cow::Borrowed(&[wgc::binding_model::BindGroupEntry {
+                    binding: 0,
+                    resource: wgc::binding_model::BindingResource::Sampler(sampler),
+                }]),
+            },
+            id::BindGroupId::zip(0, 1, backend),
+        );
+        assert!(
+            matches!(error, Some(CreateBindGroupError::InvalidSampler(id)) if id == sampler),
+            "{:?}",
+            error
+        );
+
+        global.bind_group_drop::<A>(bind_group);
+        global.bind_group_layout_drop::<A>(layout);
+        global.sampler_drop::<A>(sampler);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn create_sampler_error() {
+    run_on_all_backends_pass_through(CreateSamplerError);
+}
+
+struct CreateLayoutErrors;
+
+impl PassThroughTest for CreateLayoutErrors {
+    fn run<A: HalApi>(&self, global: &PassThroughGlobal, adapter: id::AdapterId) {
+        let backend = A::VARIANT;
+        let (device, queue) = request_device_pass_through::<A>(global, adapter);
+
+        let bind_group_layout = id::BindGroupLayoutId::zip(0, 1, backend);
+        global.create_bind_group_layout_error::<A>(bind_group_layout, Some("invalid".into()));
+        let pipeline_layout = id::PipelineLayoutId::zip(0, 1, backend);
+        global.create_pipeline_layout_error::<A>(pipeline_layout, Some("invalid".into()));
+
+        let (bind_group, error) = global.device_create_bind_group::<A>(
+            device,
+            &wgc::binding_model::BindGroupDescriptor {
+                label: None,
+                layout: bind_group_layout,
+                entries: Cow::Borrowed(&[]),
+            },
+            id::BindGroupId::zip(0, 1, backend),
+        );
+        assert!(
+            matches!(error, Some(CreateBindGroupError::InvalidLayout)),
+            "{:?}",
+            error
+        );
+        global.bind_group_drop::<A>(bind_group);
+
+        let (layout, error) = global.device_create_pipeline_layout::<A>(
+            device,
+            &wgc::binding_model::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: Cow::Borrowed(&[bind_group_layout]),
+                push_constant_ranges: Cow::Borrowed(&[]),
+            },
+            id::PipelineLayoutId::zip(1, 1, backend),
+        );
+        assert!(
+            matches!(
+                error,
+                Some(CreatePipelineLayoutError::InvalidBindGroupLayout(id)) if id == bind_group_layout
+            ),
+            "{:?}",
+            error
+        );
+        global.pipeline_layout_drop::<A>(layout);
+
+        let downlevel = global.adapter_downlevel_capabilities::<A>(adapter).unwrap();
+        if downlevel
+            .flags
+            .contains(wgt::DownlevelFlags::COMPUTE_SHADERS)
+        {
+            let (module, error) = global.device_create_shader_module::<A>(
+                device,
+                &wgc::pipeline::ShaderModuleDescriptor {
+                    label: None,
+                    shader_bound_checks: wgt::ShaderBoundChecks::default(),
+                },
+                wgc::pipeline::ShaderModuleSource::Wgsl(Cow::Borrowed(
+                    "@compute @workgroup_size(1) fn main() {}",
+                )),
+                id::ShaderModuleId::zip(0, 1, backend),
+            );
+            assert!(error.is_none(), "{:?}", error);
+
+            let (pipeline, error) = global.device_create_compute_pipeline::<A>(
+                device,
+                &wgc::pipeline::ComputePipelineDescriptor {
+                    label: None,
+                    layout: Some(pipeline_layout),
+                    stage: wgc::pipeline::ProgrammableStageDescriptor {
+                        module,
+                        entry_point: Cow::Borrowed("main"),
+                    },
+                },
+                id::ComputePipelineId::zip(0, 1, backend),
+                None,
+            );
+            assert!(
+                matches!(error, Some(CreateComputePipelineError::InvalidLayout)),
+                "{:?}",
+                error
+            );
+            global.compute_pipeline_drop::<A>(pipeline);
+            global.shader_module_drop::<A>(module);
+        }
+
+        global.pipeline_layout_drop::<A>(pipeline_layout);
+        global.bind_group_layout_drop::<A>(bind_group_layout);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn create_layout_errors() {
+    run_on_all_backends_pass_through(CreateLayoutErrors);
+}
+
+struct TimestampCalibration;
+
+impl GlobalTest for TimestampCalibration {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let sample = || {
+            let before = std::time::Instant::now();
+            let calibration = global
+                .adapter_get_timestamp_calibration::<A>(adapter)
+                .unwrap();
+            let after = std::time::Instant::now();
+            assert!(before <= calibration.cpu && calibration.cpu <= after);
+            calibration
+        };
+
+        let first = sample();
+
+        if first.gpu.is_invalid() {
+            println!("Adapter has no presentation timestamps, skipping");
+            return;
+        }
+
+        // With a device open, backends with calibrated timestamps sample
+        // through it, which must stay on the same clock.
+        let (device, queue) = request_device::<A>(global, adapter);
+        let second = sample();
+        assert!(!second.gpu.is_invalid());
+        assert!(
+            first.gpu <= second.gpu,
+            "{:?} > {:?}",
+            first.gpu,
+            second.gpu
+        );
+
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn timestamp_calibration() {
+    run_on_all_backends(TimestampCalibration);
+}
+
+#[derive(Default)]
+struct CountingWaker(std::sync::atomic::AtomicUsize);
+
+impl std::task::Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+struct SubmittedWorkDoneFutureAwait;
+
+impl GlobalTest for SubmittedWorkDoneFutureAwait {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        use std::{
+            future::Future,
+            task::{Context, Poll, Waker},
+        };
+
+        let (device, queue) = request_device::<A>(global, adapter);
+        let buffer = create_buffer::<A>(global, device, 16, wgt::BufferUsages::COPY_DST);
+        global
+            .queue_write_buffer::<A>(queue, buffer, 0, &[1; 16])
+            .unwrap();
+        global.queue_submit::<A>(queue, &[]).unwrap();
+
+        let waker = Arc::new(CountingWaker::default());
+        let task_waker = Waker::from(waker.clone());
+        let mut cx = Context::from_waker(&task_waker);
+
+        let mut future = Box::pin(global.queue_on_submitted_work_done_future::<A>(queue));
+
+        // Nothing completes the future until the device is polled.
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+
+        global
+            .device_poll::<A>(device, wgt::Maintain::Wait)
+            .unwrap();
+        assert_eq!(waker.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(matches!(future.as_mut().poll(&mut cx), Poll::Ready(Ok(()))));
+
+        // Further polls of the device don't wake the future again.
+        global
+            .device_poll::<A>(device, wgt::Maintain::Wait)
+            .unwrap();
+        assert_eq!(waker.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        global.buffer_drop::<A>(buffer, false);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn submitted_work_done_future_await() {
+    run_on_all_backends(SubmittedWorkDoneFutureAwait);
+}
+
+struct BufferAccessAfterDestroy;
+
+impl GlobalTest for BufferAccessAfterDestroy {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+        let buffer = create_buffer::<A>(
+            global,
+            device,
+            16,
+            wgt::BufferUsages::MAP_WRITE | wgt::BufferUsages::COPY_DST,
+        );
+
+        global.buffer_destroy::<A>(buffer).unwrap();
+        let error = global.buffer_destroy::<A>(buffer).unwrap_err();
+        assert!(matches!(error, DestroyError::AlreadyDestroyed), "{error:?}");
+
+        let error = global
+            .buffer_map_async::<A>(
+                buffer,
+                0..16,
+                wgc::resource::BufferMapOperation {
+                    host: wgc::device::HostMap::Write,
+                    callback: None,
+                },
+            )
+            .unwrap_err();
+        assert!(matches!(error, BufferAccessError::Destroyed), "{error:?}");
+
+        let error = global
+            .buffer_get_mapped_range::<A>(buffer, 0, None)
+            .unwrap_err();
+        assert!(matches!(error, BufferAccessError::Destroyed), "{error:?}");
+
+        let error = global
+            .queue_write_buffer::<A>(queue, buffer, 0, &[1; 16])
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                QueueWriteError::Transfer(TransferError::DestroyedBuffer(id)) if id == buffer
+            ),
+            "{error:?}"
+        );
+
+        global.buffer_drop::<A>(buffer, false);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn buffer_access_after_destroy() {
+    run_on_all_backends(BufferAccessAfterDestroy);
+}
+
+struct PollAllDevicesCollect;
+
+impl GlobalTest for PollAllDevicesCollect {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (lost_device, lost_queue) = request_device::<A>(global, adapter);
+        let (device, queue) = request_device::<A>(global, adapter);
+
+        global.device_mark_lost::<A>(lost_device, wgt::DeviceLostReason::Unknown, "lost");
+
+        let buffer = create_buffer::<A>(
+            global,
+            device,
+            16,
+            wgt::BufferUsages::MAP_READ | wgt::BufferUsages::COPY_DST,
+        );
+        global
+            .queue_write_buffer::<A>(queue, buffer, 0, &[1; 16])
+            .unwrap();
+        global.queue_submit::<A>(queue, &[]).unwrap();
+
+        let mapped = Arc::new(Mutex::new(None));
+        let mapped_clone = mapped.clone();
+        global
+            .buffer_map_async::<A>(
+                buffer,
+                0..16,
+                wgc::resource::BufferMapOperation {
+                    host: wgc::device::HostMap::Read,
+                    callback: Some(wgc::resource::BufferMapCallback::from_rust(Box::new(
+                        move |result| *mapped_clone.lock().unwrap() = Some(result),
+                    ))),
+                },
+            )
+            .unwrap();
+
+        let results = global.poll_all_devices_collect(true);
+
+        // Every device is polled, each reporting its own result.
+        assert_eq!(results.len(), 2, "{results:?}");
+        assert!(results.iter().any(|&(id, _)| id == lost_device));
+
+        // The healthy device waited for its work and serviced the mapping.
+        let (_, result) = results.iter().find(|&&(id, _)| id == device).unwrap();
+        assert!(matches!(result, Ok(true)), "{result:?}");
+        assert!(matches!(*mapped.lock().unwrap(), Some(Ok(()))));
+
+        global.buffer_unmap::<A>(buffer).unwrap();
+        global.buffer_drop::<A>(buffer, false);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+        global.queue_drop::<A>(lost_queue);
+        global.device_drop::<A>(lost_device);
+    }
+}
+
+#[test]
+fn poll_all_devices_collect() {
+    run_on_all_backends(PollAllDevicesCollect);
+}
+
+struct WriteTextureMips;
+
+impl GlobalTest for WriteTextureMips {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        const MIP_LEVEL_COUNT: u32 = 4;
+
+        let (device, queue) = request_device::<A>(global, adapter);
+        let size = wgt::Extent3d {
+            width: 16,
+            height: 16,
+            depth_or_array_layers: 1,
+        };
+        let texture = create_texture::<A>(
+            global,
+            device,
+            &wgt::TextureDescriptor {
+                label: None,
+                size,
+                mip_level_count: MIP_LEVEL_COUNT,
+                sample_count: 1,
+                dimension: wgt::TextureDimension::D2,
+                format: wgt::TextureFormat::Rgba8Unorm,
+                usage: wgt::TextureUsages::COPY_SRC | wgt::TextureUsages::COPY_DST,
+                view_formats: Vec::new(),
+            },
+        );
+
+        // Every level gets its own pattern, so data written to the wrong
+        // level or at the wrong offset shows up.
+        let levels = (0..MIP_LEVEL_COUNT)
+            .map(|mip| {
+                let mip_size = size.mip_level_size(mip, wgt::TextureDimension::D2);
+                let len = (mip_size.width * mip_size.height * 4) as usize;
+                let data = (0..len)
+                    .map(|i| mip as u8 * 0x40 + (i % 0x40) as u8)
+                    .collect::<Vec<_>>();
+                (mip, mip_size, data)
+            })
+            .collect::<Vec<_>>();
+        let data = levels
+            .iter()
+            .flat_map(|(_, _, data)| data)
+            .copied()
+            .collect::<Vec<_>>();
+
+        // More levels than the texture has are rejected before anything is
+        // written.
+        let error = global
+            .queue_write_texture_mips::<A>(queue, texture, &data, MIP_LEVEL_COUNT + 1, &size)
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                QueueWriteError::Transfer(TransferError::InvalidMipLevel {
+                    requested: MIP_LEVEL_COUNT,
+                    count: MIP_LEVEL_COUNT,
+                })
+            ),
+            "{error:?}"
+        );
+
+        global
+            .queue_write_texture_mips::<A>(queue, texture, &data, MIP_LEVEL_COUNT, &size)
+            .unwrap();
+
+        for (mip, mip_size, expected) in &levels {
+            let contents = read_texture::<A>(global, device, queue, texture, *mip, *mip_size);
+            assert_eq!(&contents, expected, "mip level {mip}");
+        }
+
+        global.texture_drop::<A>(texture, false);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn write_texture_mips() {
+    run_on_all_backends(WriteTextureMips);
+}
+
+struct BufferWait;
+
+impl GlobalTest for BufferWait {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+        let buffer = create_buffer::<A>(
+            global,
+            device,
+            16,
+            wgt::BufferUsages::MAP_READ | wgt::BufferUsages::COPY_DST,
+        );
+
+        // A write which hasn't been submitted isn't in flight, and waiting on
+        // it must not block.
+        global
+            .queue_write_buffer::<A>(queue, buffer, 0, &[1; 16])
+            .unwrap();
+        global.buffer_wait::<A>(buffer, None).unwrap();
+
+        // Once submitted, waiting returns after the GPU is done with it, so
+        // a non-blocking poll is enough to map the buffer afterwards.
+        global.queue_submit::<A>(queue, &[]).unwrap();
+        global.buffer_wait::<A>(buffer, None).unwrap();
+
+        let mapped = Arc::new(Mutex::new(None));
+        let mapped_clone = mapped.clone();
+        global
+            .buffer_map_async::<A>(
+                buffer,
+                0..16,
+                wgc::resource::BufferMapOperation {
+                    host: wgc::device::HostMap::Read,
+                    callback: Some(wgc::resource::BufferMapCallback::from_rust(Box::new(
+                        move |result| *mapped_clone.lock().unwrap() = Some(result),
+                    ))),
+                },
+            )
+            .unwrap();
+        global
+            .device_poll::<A>(device, wgt::Maintain::Poll)
+            .unwrap();
+        assert!(matches!(*mapped.lock().unwrap(), Some(Ok(()))));
+
+        global.buffer_unmap::<A>(buffer).unwrap();
+        global.buffer_drop::<A>(buffer, false);
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn buffer_wait() {
+    run_on_all_backends(BufferWait);
+}
+
+struct ResourceLogger;
+
+impl GlobalTest for ResourceLogger {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let records_clone = records.clone();
+        global.set_resource_logger(Box::new(move |record: ResourceLogRecord| {
+            records_clone.lock().unwrap().push(record)
+        }));
+
+        let (device, queue) = request_device::<A>(global, adapter);
+        let buffer = create_buffer::<A>(global, device, 16, wgt::BufferUsages::COPY_DST);
+        global.buffer_drop::<A>(buffer, false);
+        global
+            .device_poll::<A>(device, wgt::Maintain::Wait)
+            .unwrap();
+
+        let events = records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|record| record.kind == "Buffer" && record.id == Some(buffer.into_raw()))
+            .map(|record| {
+                assert_eq!(record.backend, A::VARIANT);
+                record.event
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [ResourceLogEvent::Created, ResourceLogEvent::Destroyed]
+        );
+
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn resource_logger() {
+    run_on_all_backends(ResourceLogger);
+}
+
+struct QueueSubmitCommandBufferIndex;
+
+impl GlobalTest for QueueSubmitCommandBufferIndex {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+
+        let create_encoder = || {
+            let (encoder, error) = global.device_create_command_encoder::<A>(
+                device,
+                &wgt::CommandEncoderDescriptor { label: None },
+                (),
+            );
+            assert!(error.is_none(), "{:?}", error);
+            encoder
+        };
+        let finish = |encoder| {
+            let (command_buffer, error) = global.command_encoder_finish::<A>(
+                encoder,
+                &wgt::CommandBufferDescriptor { label: None },
+            );
+            assert!(error.is_none(), "{:?}", error);
+            command_buffer
+        };
+
+        // A command buffer using a buffer which has been destroyed since.
+        let buffer = create_buffer::<A>(global, device, 16, wgt::BufferUsages::COPY_DST);
+        let encoder = create_encoder();
+        global
+            .command_encoder_clear_buffer::<A>(encoder, buffer, 0, None)
+            .unwrap();
+        let destroyed = finish(encoder);
+        global.buffer_destroy::<A>(buffer).unwrap();
+
+        let valid = finish(create_encoder());
+        let err = global
+            .queue_submit::<A>(queue, &[valid, destroyed])
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                QueueSubmitError::CommandBuffer { index: 1, ref source }
+                    if matches!(**source, QueueSubmitError::DestroyedBuffer(id) if id == buffer)
+            ),
+            "{:?}",
+            err
+        );
+
+        // Nothing was consumed, so the valid command buffer can still be
+        // submitted on its own.
+        global.queue_submit::<A>(queue, &[valid]).unwrap();
+        global.command_buffer_drop::<A>(destroyed);
+
+        let valid = finish(create_encoder());
+        let unfinished = create_encoder();
+        let err = global
+            .queue_submit::<A>(queue, &[valid, unfinished])
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                QueueSubmitError::CommandBuffer { index: 1, ref source }
+                    if matches!(**source, QueueSubmitError::UnfinishedCommandBuffer(id) if id == unfinished)
+            ),
+            "{:?}",
+            err
+        );
+        global.command_buffer_drop::<A>(valid);
+        global.command_encoder_drop::<A>(unfinished);
+
+        global.buffer_drop::<A>(buffer, false);
+        global
+            .device_poll::<A>(device, wgt::Maintain::Wait)
+            .unwrap();
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn queue_submit_command_buffer_index() {
+    run_on_all_backends(QueueSubmitCommandBufferIndex);
+}
+
+struct BindGroupLayoutFlagErrors;
+
+impl GlobalTest for BindGroupLayoutFlagErrors {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let create_layout =
+            |device, entries: &[wgt::BindGroupLayoutEntry], flags: wgt::BindGroupLayoutFlags| {
+                let (layout, error) = global.device_create_bind_group_layout::<A>(
+                    device,
+                    &wgc::binding_model::BindGroupLayoutDescriptor {
+                        label: None,
+                        entries: Cow::Borrowed(entries),
+                        flags,
+                    },
+                    (),
+                );
+                global.bind_group_layout_drop::<A>(layout);
+                error
+            };
+        let uniform = |has_dynamic_offset| {
+            [wgt::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgt::ShaderStages::FRAGMENT,
+                ty: wgt::BindingType::Buffer {
+                    ty: wgt::BufferBindingType::Uniform,
+                    has_dynamic_offset,
+                    min_binding_size: None,
+                },
+                count: None,
+            }]
+        };
+
+        let (device, queue) = request_device::<A>(global, adapter);
+
+        let unknown = wgt::BindGroupLayoutFlags::from_bits_retain(1 << 31);
+        let error = create_layout(device, &[], unknown);
+        assert!(
+            matches!(error, Some(CreateBindGroupLayoutError::InvalidFlags(flags)) if flags == unknown),
+            "{:?}",
+            error
+        );
+
+        let error = create_layout(
+            device,
+            &uniform(false),
+            wgt::BindGroupLayoutFlags::UPDATE_AFTER_BIND,
+        );
+        assert!(
+            matches!(
+                error,
+                Some(CreateBindGroupLayoutError::MissingFeatures(flags, _))
+                    if flags == wgt::BindGroupLayoutFlags::UPDATE_AFTER_BIND
+            ),
+            "{:?}",
+            error
+        );
+
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+
+        let Some((device, queue)) = request_device_with_features::<A>(
+            global,
+            adapter,
+            wgt::Features::UPDATE_AFTER_BIND_BINDING_ARRAY,
+        ) else {
+            return;
+        };
+
+        let error = create_layout(
+            device,
+            &uniform(true),
+            wgt::BindGroupLayoutFlags::UPDATE_AFTER_BIND,
+        );
+        assert!(
+            matches!(
+                error,
+                Some(CreateBindGroupLayoutError::Entry {
+                    binding: 0,
+                    error: BindGroupLayoutEntryError::DynamicOffsetWithUpdateAfterBind,
+                })
+            ),
+            "{:?}",
+            error
+        );
+
+        let error = create_layout(
+            device,
+            &uniform(false),
+            wgt::BindGroupLayoutFlags::UPDATE_AFTER_BIND,
+        );
+        assert!(error.is_none(), "{:?}", error);
+
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn bind_group_layout_flag_errors() {
+    run_on_all_backends(BindGroupLayoutFlagErrors);
+}
+
+struct ShaderModuleCompilationInfo;
+
+impl GlobalTest for ShaderModuleCompilationInfo {
+    fn run<A: HalApi>(&self, global: &TestGlobal, adapter: id::AdapterId) {
+        let (device, queue) = request_device::<A>(global, adapter);
+        let desc = wgc::pipeline::ShaderModuleDescriptor {
+            label: None,
+            shader_bound_checks: wgt::ShaderBoundChecks::default(),
+        };
+
+        let (module, error) = global.device_create_shader_module::<A>(
+            device,
+            &desc,
+            wgc::pipeline::ShaderModuleSource::Wgsl(Cow::Borrowed("fn main() {}")),
+            (),
+        );
+        assert!(error.is_none(), "{:?}", error);
+        assert!(global
+            .shader_module_get_compilation_info::<A>(module)
+            .is_empty());
+        global.shader_module_drop::<A>(module);
+
+        let (module, error) = global.device_create_shader_module::<A>(
+            device,
+            &desc,
+            wgc::pipeline::ShaderModuleSource::Wgsl(Cow::Borrowed("\nfn main() {")),
+            (),
+        );
+        let error = error.expect("the shader module should fail to compile");
+        let messages = global.shader_module_get_compilation_info::<A>(module);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message, error.to_string());
+        let location = messages[0].location.expect("the error should be located");
+        assert_eq!(location.line_number, 2);
+
+        global.shader_module_drop::<A>(module);
+        assert!(global
+            .shader_module_get_compilation_info::<A>(module)
+            .is_empty());
+
+        global.queue_drop::<A>(queue);
+        global.device_drop::<A>(device);
+    }
+}
+
+#[test]
+fn shader_module_compilation_info() {
+    run_on_all_backends(ShaderModuleCompilationInfo);
+}
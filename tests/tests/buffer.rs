@@ -1,4 +1,6 @@
-use wgpu_test::{gpu_test, FailureCase, GpuTestConfiguration, TestParameters, TestingContext};
+use wgpu_test::{
+    fail, gpu_test, valid, FailureCase, GpuTestConfiguration, TestParameters, TestingContext,
+};
 
 fn test_empty_buffer_range(ctx: &TestingContext, buffer_size: u64, label: &str) {
     let r = wgpu::BufferUsages::MAP_READ;
@@ -154,3 +156,127 @@ static MAP_OFFSET: GpuTestConfiguration = GpuTestConfiguration::new().run_sync(|
         assert_eq!(*byte, 0);
     }
 });
+
+fn create_mappable_buffer(ctx: &TestingContext) -> wgpu::Buffer {
+    ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 16,
+        usage: wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    })
+}
+
+#[gpu_test]
+static MAP_ALREADY_MAPPED: GpuTestConfiguration = GpuTestConfiguration::new().run_sync(|ctx| {
+    let buffer = create_mappable_buffer(&ctx);
+
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, Result::unwrap);
+    ctx.device.poll(wgpu::MaintainBase::Wait);
+
+    // The buffer is mapped now, so mapping it again must fail right away.
+    let failed = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let failed_clone = failed.clone();
+    fail(&ctx.device, || {
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                failed_clone.store(result.is_err(), std::sync::atomic::Ordering::SeqCst);
+            });
+    });
+    assert!(failed.load(std::sync::atomic::Ordering::SeqCst));
+
+    buffer.unmap();
+});
+
+#[gpu_test]
+static MAP_WHILE_PENDING: GpuTestConfiguration = GpuTestConfiguration::new().run_sync(|ctx| {
+    let buffer = create_mappable_buffer(&ctx);
+
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, Result::unwrap);
+
+    // The first mapping has not been polled yet, so it is still pending.
+    let failed = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let failed_clone = failed.clone();
+    fail(&ctx.device, || {
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                failed_clone.store(result.is_err(), std::sync::atomic::Ordering::SeqCst);
+            });
+    });
+    assert!(failed.load(std::sync::atomic::Ordering::SeqCst));
+
+    ctx.device.poll(wgpu::MaintainBase::Wait);
+    buffer.unmap();
+});
+
+fn create_buffer(ctx: &TestingContext, size: u64, mapped_at_creation: bool) -> wgpu::Buffer {
+    ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size,
+        usage: wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation,
+    })
+}
+
+// Buffers mapped at creation must have a size which is a multiple of
+// `COPY_BUFFER_ALIGNMENT`.
+#[gpu_test]
+static CREATE_MAPPED_BUFFER_UNALIGNED_SIZE: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        fail(&ctx.device, || create_buffer(&ctx, 6, true));
+        valid(&ctx.device, || create_buffer(&ctx, 8, true)).unmap();
+    });
+
+// WebGPU allows buffers of size zero.
+#[gpu_test]
+static CREATE_ZERO_SIZED_BUFFER: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        valid(&ctx.device, || create_buffer(&ctx, 0, false));
+    });
+
+// The smallest non-empty buffer which can be mapped at creation.
+#[gpu_test]
+static CREATE_MINIMAL_MAPPED_BUFFER: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let buffer = valid(&ctx.device, || create_buffer(&ctx, 4, true));
+        buffer.slice(..).get_mapped_range_mut().fill(1);
+        buffer.unmap();
+    });
+
+// A buffer mapped for reading can be read, but its mapped range can't be
+// written to. wgpu panics on the error.
+#[cfg(not(target_arch = "wasm32"))]
+#[gpu_test]
+static WRITE_TO_READ_MAPPED_BUFFER: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, Result::unwrap);
+        ctx.device.poll(wgpu::MaintainBase::Wait);
+
+        assert_eq!(buffer.slice(..).get_mapped_range().len(), 16);
+
+        let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            buffer.slice(..).get_mapped_range_mut();
+        }))
+        .expect_err("getting a writable range of a read mapping should fail");
+        let message = panic
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .unwrap_or_default();
+        assert!(
+            message.contains("Buffer is mapped for reading"),
+            "unexpected panic: {message}"
+        );
+    });
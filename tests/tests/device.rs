@@ -61,6 +61,59 @@ fn device_lifetime_check() {
     );
 }
 
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+#[test]
+fn adapter_outlives_dropped_handle_while_device_alive() {
+    use pollster::FutureExt as _;
+
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::util::backend_bits_from_env().unwrap_or(wgpu::Backends::all()),
+        dx12_shader_compiler: wgpu::util::dx12_shader_compiler_from_env().unwrap_or_default(),
+        gles_minor_version: wgpu::util::gles_minor_version_from_env().unwrap_or_default(),
+        flags: wgpu::InstanceFlags::debugging().with_env(),
+    });
+
+    let adapter = wgpu::util::initialize_adapter_from_env_or_default(&instance, None)
+        .block_on()
+        .expect("failed to create adapter");
+    let backend = adapter.get_info().backend;
+
+    let (device, queue) = adapter
+        .request_device(&wgpu::DeviceDescriptor::default(), None)
+        .block_on()
+        .expect("failed to create device");
+
+    drop(adapter);
+
+    // The device keeps the adapter alive, and its id allocated.
+    let report = instance.generate_report();
+    let adapters = &report.hub_report(backend).adapters;
+    assert_eq!(adapters.num_kept_from_user, 0);
+    assert_eq!(adapters.num_allocated, 1);
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 16,
+        usage: wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    queue.write_buffer(&buffer, 0, &[0; 16]);
+    queue.submit(None);
+    device.poll(wgpu::Maintain::Wait);
+    drop(buffer);
+    device.poll(wgpu::Maintain::Wait);
+
+    drop(queue);
+    drop(device);
+
+    let report = instance.generate_report();
+    assert_eq!(
+        report.hub_report(backend).adapters.num_allocated,
+        0,
+        "Adapter has not been released along with its last device"
+    );
+}
+
 #[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
 #[gpu_test]
 static REQUEST_DEVICE_ERROR_MESSAGE_NATIVE: GpuTestConfiguration =
@@ -512,6 +565,39 @@ static DEVICE_DESTROY_THEN_LOST: GpuTestConfiguration = GpuTestConfiguration::ne
         );
     });
 
+#[gpu_test]
+static DEVICE_DESTROY_THEN_DROP: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().expect_fail(FailureCase::webgl2()))
+    .run_sync(|ctx| {
+        // This test checks that when device.destroy is called and the device
+        // is dropped before being polled, the provided DeviceLostClosure is
+        // called exactly once, with the reason from destroy.
+        // Fails on webgl because webgl doesn't implement drop.
+        let call_count = std::sync::Arc::<std::sync::atomic::AtomicUsize>::new(0.into());
+
+        // Set a LoseDeviceCallback on the device.
+        let call_count_clone = call_count.clone();
+        let callback = Box::new(move |reason, _m| {
+            call_count_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            assert!(
+                matches!(reason, wgt::DeviceLostReason::Destroyed),
+                "Device lost info reason should match DeviceLostReason::Destroyed."
+            );
+        });
+        ctx.device.set_device_lost_callback(callback);
+
+        // Destroy the device, then drop it, which would otherwise lose it with
+        // reason DeviceLostReason::Unknown.
+        ctx.device.destroy();
+        drop(ctx.device);
+
+        assert_eq!(
+            call_count.load(std::sync::atomic::Ordering::SeqCst),
+            1,
+            "Device lost callback should have been called exactly once."
+        );
+    });
+
 #[gpu_test]
 static DEVICE_DROP_THEN_LOST: GpuTestConfiguration = GpuTestConfiguration::new()
     .parameters(TestParameters::default().expect_fail(FailureCase::webgl2()))
@@ -1,4 +1,6 @@
-use wgpu_test::{fail, gpu_test, FailureCase, GpuTestConfiguration, TestParameters};
+use wgpu_test::{
+    fail, gpu_test, valid, FailureCase, GpuTestConfiguration, TestParameters, TestingContext,
+};
 
 #[gpu_test]
 static DROP_ENCODER: GpuTestConfiguration = GpuTestConfiguration::new().run_sync(|ctx| {
@@ -62,3 +64,245 @@ static DROP_ENCODER_AFTER_ERROR: GpuTestConfiguration = GpuTestConfiguration::ne
         // The encoder is still open!
         drop(encoder);
     });
+
+#[gpu_test]
+static FINISH_WITH_OPEN_DEBUG_GROUP: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.push_debug_group("outer");
+        encoder.push_debug_group("inner");
+        encoder.pop_debug_group();
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let _ = encoder.finish();
+        let error = pollster::block_on(ctx.device.pop_error_scope())
+            .expect("finishing with an open debug group should fail");
+        assert!(
+            error
+                .to_string()
+                .contains("finished with 1 debug group(s) still open"),
+            "{error}"
+        );
+    });
+
+fn execute_depth_bundle(
+    ctx: &TestingContext,
+    bundle_depth_read_only: bool,
+    pass_depth_read_only: bool,
+) {
+    let format = wgpu::TextureFormat::Depth32Float;
+    let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: 4,
+            height: 4,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let bundle = ctx
+        .device
+        .create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+            label: None,
+            color_formats: &[],
+            depth_stencil: Some(wgpu::RenderBundleDepthStencil {
+                format,
+                depth_read_only: bundle_depth_read_only,
+                stencil_read_only: true,
+            }),
+            sample_count: 1,
+            multiview: None,
+        })
+        .finish(&wgpu::RenderBundleDescriptor::default());
+
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &view,
+                depth_ops: (!pass_depth_read_only).then_some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.execute_bundles([&bundle]);
+    }
+    ctx.queue.submit([encoder.finish()]);
+}
+
+// A bundle which writes depth can't be executed in a pass where depth is
+// read-only.
+#[gpu_test]
+static EXECUTE_DEPTH_WRITING_BUNDLE_IN_READ_ONLY_PASS: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        fail(&ctx.device, || execute_depth_bundle(&ctx, false, true));
+    });
+
+// A bundle which only reads depth may be executed in a pass that writes it.
+#[gpu_test]
+static EXECUTE_DEPTH_READ_ONLY_BUNDLE_IN_WRITABLE_PASS: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        valid(&ctx.device, || execute_depth_bundle(&ctx, true, false));
+    });
+
+// Setting a bind group with the wrong number of dynamic offsets reports the
+// expected and provided counts.
+#[gpu_test]
+static SET_BIND_GROUP_WRONG_DYNAMIC_OFFSET_COUNT: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let layout = ctx
+            .device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 256,
+            usage: wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(64),
+                }),
+            }],
+        });
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_bind_group(0, &bind_group, &[0, 0]);
+        }
+        drop(encoder.finish());
+
+        let error = pollster::block_on(ctx.device.pop_error_scope())
+            .expect("setting a bind group with two offsets instead of one should fail");
+        let message = error.to_string();
+        assert!(
+            message.contains("expects 1 dynamic offset. However 2 dynamic offsets were provided"),
+            "unexpected error: {message}"
+        );
+    });
+
+/// Clear `size` bytes of a 16 byte buffer with `usage` from `offset`, and
+/// return the validation error message, if any.
+fn clear_buffer_error(
+    ctx: &TestingContext,
+    usage: wgpu::BufferUsages,
+    offset: wgpu::BufferAddress,
+    size: Option<wgpu::BufferAddress>,
+) -> Option<String> {
+    let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 16,
+        usage,
+        mapped_at_creation: false,
+    });
+
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.clear_buffer(&buffer, offset, size);
+    drop(encoder.finish());
+
+    pollster::block_on(ctx.device.pop_error_scope()).map(|error| error.to_string())
+}
+
+#[gpu_test]
+static CLEAR_BUFFER_UNALIGNED_OFFSET: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let message = clear_buffer_error(&ctx, wgpu::BufferUsages::COPY_DST, 2, Some(4))
+            .expect("clearing from an unaligned offset should fail");
+        assert!(
+            message.contains("Buffer clear offset 2 is not a multiple"),
+            "unexpected error: {message}"
+        );
+    });
+
+#[gpu_test]
+static CLEAR_BUFFER_UNALIGNED_SIZE: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let message = clear_buffer_error(&ctx, wgpu::BufferUsages::COPY_DST, 4, Some(6))
+            .expect("clearing an unaligned size should fail");
+        assert!(
+            message.contains("Buffer clear size 6 is not a multiple"),
+            "unexpected error: {message}"
+        );
+    });
+
+// Clearing without a size clears the rest of the buffer, which still requires
+// `COPY_DST`.
+#[gpu_test]
+static CLEAR_WHOLE_BUFFER: GpuTestConfiguration = GpuTestConfiguration::new().run_sync(|ctx| {
+    assert_eq!(
+        clear_buffer_error(&ctx, wgpu::BufferUsages::COPY_DST, 0, None),
+        None
+    );
+
+    let message = clear_buffer_error(&ctx, wgpu::BufferUsages::COPY_SRC, 0, None)
+        .expect("clearing a buffer without COPY_DST should fail");
+    assert!(message.contains("COPY_DST"), "unexpected error: {message}");
+});
+
+#[gpu_test]
+static POP_DEBUG_GROUP_WITHOUT_PUSH: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        encoder.pop_debug_group();
+        let error = pollster::block_on(ctx.device.pop_error_scope())
+            .expect("popping a debug group that was never pushed should fail");
+        assert!(
+            error
+                .to_string()
+                .contains("number of pushed debug groups is zero"),
+            "{error}"
+        );
+
+        // The rejected pop didn't unbalance the encoder.
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        encoder.push_debug_group("group");
+        encoder.pop_debug_group();
+        let _ = encoder.finish();
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+    });
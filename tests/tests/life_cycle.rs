@@ -93,3 +93,101 @@ static TEXTURE_DESTROY: GpuTestConfiguration = GpuTestConfiguration::new().run_s
 
     texture.destroy();
 });
+
+#[gpu_test]
+static TEXTURE_VIEW_DROP_DURING_SUBMISSION: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 128,
+                height: 128,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::GREEN),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        ctx.queue.submit(Some(encoder.finish()));
+
+        // The view and its texture are dropped without waiting, while the
+        // submission using them may still be in flight.
+        drop(view);
+        drop(texture);
+
+        ctx.device.poll(wgpu::MaintainBase::Wait);
+    });
+
+#[gpu_test]
+static BUFFER_WRITE_AFTER_DESTROY: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 256,
+            usage: wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        buffer.destroy();
+        // Destroying twice is not an error.
+        buffer.destroy();
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.queue.write_buffer(&buffer, 0, &[0; 16]);
+        let error = pollster::block_on(ctx.device.pop_error_scope())
+            .expect("writing to a destroyed buffer should fail")
+            .to_string();
+        assert!(error.contains("is destroyed"), "{error}");
+
+        ctx.device.poll(wgpu::MaintainBase::Wait);
+    });
+
+#[gpu_test]
+static BUFFER_MAP_AFTER_DESTROY: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 256,
+            usage: wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        buffer.destroy();
+
+        let result = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let result_clone = result.clone();
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        buffer.slice(..).map_async(wgpu::MapMode::Read, move |r| {
+            *result_clone.lock().unwrap() = Some(r);
+        });
+        let error = pollster::block_on(ctx.device.pop_error_scope())
+            .expect("mapping a destroyed buffer should fail")
+            .to_string();
+        assert!(error.contains("Buffer is destroyed"), "{error}");
+
+        ctx.device.poll(wgpu::MaintainBase::Wait);
+        assert!(matches!(*result.lock().unwrap(), Some(Err(_))));
+    });
@@ -290,3 +290,35 @@ static SIMPLE_DRAW_CHECK_MEM_LEAKS: wgpu_test::GpuTestConfiguration =
                 cmb.draw(0..6, 0..1);
             })
         });
+
+#[cfg(any(
+    not(target_arch = "wasm32"),
+    target_os = "emscripten",
+    feature = "webgl"
+))]
+#[wgpu_test::gpu_test]
+static LEAKED_BUFFER_IS_REPORTED: wgpu_test::GpuTestConfiguration =
+    wgpu_test::GpuTestConfiguration::new().run_sync(|ctx| {
+        let allowed = ["adapters", "devices", "queues"];
+
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let report = ctx.instance.generate_report().check_leaks(&allowed);
+        assert_eq!(
+            report.unwrap_err().leaks,
+            [(ctx.adapter_info.backend, "buffers", 1)]
+        );
+
+        drop(buffer);
+        ctx.device.poll(wgpu::Maintain::Wait);
+
+        ctx.instance
+            .generate_report()
+            .check_leaks(&allowed)
+            .unwrap();
+    });
@@ -1,4 +1,6 @@
-use wgpu_test::{fail, gpu_test, FailureCase, GpuTestConfiguration, TestParameters};
+use wgpu_test::{
+    fail, gpu_test, valid, FailureCase, GpuTestConfiguration, TestParameters, TestingContext,
+};
 
 // Create an invalid shader and a compute pipeline that uses it
 // with a default bindgroup layout, and then ask for that layout.
@@ -33,3 +35,118 @@ static PIPELINE_DEFAULT_LAYOUT_BAD_MODULE: GpuTestConfiguration = GpuTestConfigu
             pipeline.get_bind_group_layout(0);
         });
     });
+
+fn create_pipeline_with_workgroup_size(ctx: &TestingContext, size: [u32; 3]) {
+    let module = ctx
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "@compute @workgroup_size({}, {}, {}) fn main() {{}}",
+                    size[0], size[1], size[2]
+                )
+                .into(),
+            ),
+        });
+
+    ctx.device
+        .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+}
+
+/// Create a pipeline which should fail validation, returning the error.
+fn workgroup_size_error(ctx: &TestingContext, size: [u32; 3]) -> String {
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    create_pipeline_with_workgroup_size(ctx, size);
+    pollster::block_on(ctx.device.pop_error_scope())
+        .expect("an over-limit workgroup size should fail")
+        .to_string()
+}
+
+// A workgroup which is one wider than the X limit is rejected.
+#[gpu_test]
+static WORKGROUP_SIZE_X_OVER_LIMIT: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let limits = ctx.device.limits();
+        let x = limits.max_compute_workgroup_size_x;
+
+        valid(&ctx.device, || {
+            create_pipeline_with_workgroup_size(&ctx, [x, 1, 1]);
+        });
+        let error = workgroup_size_error(&ctx, [x + 1, 1, 1]);
+        let expected = format!(
+            "Workgroup size x of {} exceeds the device limit of {x}",
+            x + 1
+        );
+        assert!(error.contains(&expected), "{error}");
+    });
+
+// A workgroup within every per-dimension limit, but with too many invocations
+// in total, is rejected.
+#[gpu_test]
+static WORKGROUP_SIZE_INVOCATIONS_OVER_LIMIT: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        let limits = ctx.device.limits();
+        let x = limits.max_compute_workgroup_size_x;
+        let y = limits
+            .max_compute_workgroup_size_y
+            .min(limits.max_compute_invocations_per_workgroup / x + 1);
+
+        assert!(x * y > limits.max_compute_invocations_per_workgroup);
+        let error = workgroup_size_error(&ctx, [x, y, 1]);
+        let expected = format!(
+            "Workgroup size total invocations of {} exceeds the device limit of {}",
+            x * y,
+            limits.max_compute_invocations_per_workgroup
+        );
+        assert!(error.contains(&expected), "{error}");
+    });
+
+// Asking for a bind group layout past the end of the pipeline layout reports
+// how many bind groups there are.
+#[gpu_test]
+static GET_BIND_GROUP_LAYOUT_OUT_OF_RANGE: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default()
+            // wgpu panics on reflection errors.
+            .expect_fail(
+                FailureCase::always()
+                    .panic("Bind group index 5 is out of range, the pipeline has 2 bind groups"),
+            ),
+    )
+    .run_sync(|ctx| {
+        let bind_group_layout =
+            ctx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: None,
+                    entries: &[],
+                });
+        let layout = ctx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&bind_group_layout, &bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl("@compute @workgroup_size(1) fn main() {}".into()),
+            });
+        let pipeline = ctx
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: Some(&layout),
+                module: &module,
+                entry_point: "main",
+            });
+
+        pipeline.get_bind_group_layout(5);
+    });
@@ -113,3 +113,58 @@ static WAIT_OUT_OF_ORDER: GpuTestConfiguration = GpuTestConfiguration::new().run
     ctx.device.poll(Maintain::WaitForSubmissionIndex(index2));
     ctx.device.poll(Maintain::WaitForSubmissionIndex(index1));
 });
+
+/// Polling an idle device must not hold up a submission from another thread.
+#[cfg(not(target_arch = "wasm32"))]
+#[gpu_test]
+static POLL_IDLE_DURING_SUBMIT: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let data = DummyWorkData::new(&ctx);
+        ctx.device.poll(Maintain::Wait);
+
+        let done = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                while !done.load(Ordering::Acquire) {
+                    ctx.device.poll(Maintain::Poll);
+                }
+            });
+
+            let index = ctx.queue.submit(Some(data.cmd_buf));
+            ctx.device.poll(Maintain::WaitForSubmissionIndex(index));
+            done.store(true, Ordering::Release);
+        });
+    });
+
+/// A poll thread fires map callbacks without the application polling.
+#[cfg(not(target_arch = "wasm32"))]
+#[gpu_test]
+static POLL_THREAD_MAPS_BUFFER: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        use std::time::Duration;
+
+        let buffer = ctx.device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        ctx.device.start_poll_thread(Duration::from_millis(1));
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                sender.send(result).unwrap();
+            });
+        receiver
+            .recv_timeout(Duration::from_secs(5))
+            .expect("the poll thread didn't fire the map callback")
+            .unwrap();
+
+        ctx.device.stop_poll_thread();
+        buffer.unmap();
+    });
@@ -1,6 +1,6 @@
 use std::num::NonZeroU64;
 
-use wgpu_test::{gpu_test, GpuTestConfiguration, TestParameters, TestingContext};
+use wgpu_test::{fail_if, gpu_test, GpuTestConfiguration, TestParameters, TestingContext};
 
 /// We want to test that partial updates to push constants work as expected.
 ///
@@ -149,3 +149,95 @@ fn partial_update_test(ctx: TestingContext) {
     // second 4 floats the first update
     assert_eq!(floats, [1.0, 2.0, 3.0, 4.0, 1.0, 5.0, 3.0, 4.0]);
 }
+
+#[gpu_test]
+static OVERLAPPING_RANGES: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default()
+            .features(wgpu::Features::PUSH_CONSTANTS)
+            .limits(wgpu::Limits {
+                max_push_constant_size: 32,
+                ..Default::default()
+            }),
+    )
+    .run_sync(|ctx| {
+        // Each stage may only be provided by one range.
+        create_layout_with_ranges(
+            &ctx,
+            &[
+                wgpu::PushConstantRange {
+                    stages: wgpu::ShaderStages::VERTEX,
+                    range: 0..16,
+                },
+                wgpu::PushConstantRange {
+                    stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    range: 16..32,
+                },
+            ],
+            true,
+        );
+    });
+
+#[gpu_test]
+static RANGE_EXCEEDS_LIMIT: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(
+        TestParameters::default()
+            .features(wgpu::Features::PUSH_CONSTANTS)
+            .limits(wgpu::Limits {
+                max_push_constant_size: 32,
+                ..Default::default()
+            }),
+    )
+    .run_sync(|ctx| {
+        let max = ctx.device.limits().max_push_constant_size;
+        create_layout_with_ranges(
+            &ctx,
+            &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::COMPUTE,
+                range: 0..max,
+            }],
+            false,
+        );
+        create_layout_with_ranges(
+            &ctx,
+            &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::COMPUTE,
+                range: 0..max + wgpu::PUSH_CONSTANT_ALIGNMENT,
+            }],
+            true,
+        );
+    });
+
+#[gpu_test]
+static RANGES_WITHOUT_FEATURE: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default())
+    .run_sync(|ctx| {
+        // The feature is only enabled when the test parameters request it.
+        assert!(!ctx
+            .device
+            .features()
+            .contains(wgpu::Features::PUSH_CONSTANTS));
+        create_layout_with_ranges(
+            &ctx,
+            &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::COMPUTE,
+                range: 0..4,
+            }],
+            true,
+        );
+    });
+
+fn create_layout_with_ranges(
+    ctx: &TestingContext,
+    push_constant_ranges: &[wgpu::PushConstantRange],
+    should_fail: bool,
+) {
+    fail_if(&ctx.device, should_fail, || {
+        ctx.device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[],
+                push_constant_ranges,
+            })
+    });
+}
@@ -1,4 +1,4 @@
-use wgpu_test::{gpu_test, GpuTestConfiguration, TestParameters};
+use wgpu_test::{fail, gpu_test, GpuTestConfiguration, TestParameters, TestingContext};
 
 #[gpu_test]
 static DROP_FAILED_TIMESTAMP_QUERY_SET: GpuTestConfiguration = GpuTestConfiguration::new()
@@ -21,3 +21,130 @@ static DROP_FAILED_TIMESTAMP_QUERY_SET: GpuTestConfiguration = GpuTestConfigurat
 
         assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
     });
+
+#[gpu_test]
+static RESOLVE_QUERY_SET_INTO_SMALL_BUFFER: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default())
+    .run_sync(|ctx| {
+        let query_set = ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: None,
+            ty: wgpu::QueryType::Occlusion,
+            count: 4,
+        });
+        // Four 8 byte queries need 32 bytes.
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 8,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE,
+            mapped_at_creation: false,
+        });
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.resolve_query_set(&query_set, 0..4, &buffer, 0);
+        drop(encoder.finish());
+
+        let error = pollster::block_on(ctx.device.pop_error_scope())
+            .expect("resolving into a too small buffer should fail");
+        let message = error.to_string();
+        assert!(
+            message.contains("destination buffer of size 8 using offsets 0..32"),
+            "unexpected error: {message}"
+        );
+    });
+
+#[gpu_test]
+static CREATE_QUERY_SET_WITH_TOO_MANY_QUERIES: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default())
+    .run_sync(|ctx| {
+        // Creating this query set should fail, since it goes over
+        // QUERY_SET_MAX_QUERIES.
+        let bad_query_set = fail(&ctx.device, || {
+            ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("oversized query set"),
+                ty: wgpu::QueryType::Occlusion,
+                count: wgpu::QUERY_SET_MAX_QUERIES + 1,
+            })
+        });
+
+        drop(bad_query_set);
+    });
+
+fn dispatch_with_statistics_query(ctx: &TestingContext, end_query: bool) {
+    let query_set = ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+        label: None,
+        ty: wgpu::QueryType::PipelineStatistics(
+            wgpu::PipelineStatisticsTypes::COMPUTE_SHADER_INVOCATIONS,
+        ),
+        count: 1,
+    });
+    let module = ctx
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl("@compute @workgroup_size(1) fn main() {}".into()),
+        });
+    let pipeline = ctx
+        .device
+        .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+        pass.set_pipeline(&pipeline);
+        pass.begin_pipeline_statistics_query(&query_set, 0);
+        pass.dispatch_workgroups(1, 1, 1);
+        if end_query {
+            pass.end_pipeline_statistics_query();
+        }
+    }
+    ctx.queue.submit(Some(encoder.finish()));
+}
+
+#[gpu_test]
+static COMPUTE_PASS_PIPELINE_STATISTICS_QUERY: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().features(wgpu::Features::PIPELINE_STATISTICS_QUERY))
+    .run_sync(|ctx| {
+        dispatch_with_statistics_query(&ctx, true);
+
+        ctx.device.poll(wgpu::Maintain::Wait);
+    });
+
+#[gpu_test]
+static COMPUTE_PASS_UNBALANCED_PIPELINE_STATISTICS_QUERY: GpuTestConfiguration =
+    GpuTestConfiguration::new()
+        .parameters(TestParameters::default().features(wgpu::Features::PIPELINE_STATISTICS_QUERY))
+        .run_sync(|ctx| {
+            fail(&ctx.device, || {
+                // The pass ends while the query is still active.
+                dispatch_with_statistics_query(&ctx, false);
+            });
+        });
+
+#[gpu_test]
+static CREATE_PIPELINE_STATISTICS_QUERY_SET_WITHOUT_FEATURE: GpuTestConfiguration =
+    GpuTestConfiguration::new()
+        .parameters(TestParameters::default())
+        .run_sync(|ctx| {
+            // PIPELINE_STATISTICS_QUERY was not requested.
+            let bad_query_set = fail(&ctx.device, || {
+                ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: None,
+                    ty: wgpu::QueryType::PipelineStatistics(
+                        wgpu::PipelineStatisticsTypes::COMPUTE_SHADER_INVOCATIONS,
+                    ),
+                    count: 1,
+                })
+            });
+
+            drop(bad_query_set);
+        });
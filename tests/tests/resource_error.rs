@@ -46,3 +46,230 @@ static BAD_TEXTURE: GpuTestConfiguration = GpuTestConfiguration::new().run_sync(
     valid(&ctx.device, || texture.destroy());
     valid(&ctx.device, || texture.destroy());
 });
+
+#[gpu_test]
+static BIND_GROUP_WITH_BAD_BUFFER: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let buffer = fail(&ctx.device, || {
+            ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: 99999999,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            })
+        });
+        let layout = ctx
+            .device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let _ = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 3,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+        let error = pollster::block_on(ctx.device.pop_error_scope())
+            .expect("binding an invalid buffer should fail");
+        let message = error.to_string();
+        assert!(
+            message.contains("binding 3 is invalid"),
+            "unexpected error: {message}"
+        );
+    });
+
+/// Create a sampler from `desc`, and return the validation error message, if
+/// any.
+fn sampler_error(
+    ctx: &wgpu_test::TestingContext,
+    desc: &wgpu::SamplerDescriptor,
+) -> Option<String> {
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let _ = ctx.device.create_sampler(desc);
+    pollster::block_on(ctx.device.pop_error_scope()).map(|error| error.to_string())
+}
+
+#[gpu_test]
+static SAMPLER_LOD_MIN_CLAMP_ABOVE_MAX: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        let message = sampler_error(
+            &ctx,
+            &wgpu::SamplerDescriptor {
+                lod_min_clamp: 4.0,
+                lod_max_clamp: 2.0,
+                ..Default::default()
+            },
+        )
+        .expect("a sampler with lod_min_clamp above lod_max_clamp should fail");
+        assert!(
+            message.contains("Invalid lodMaxClamp: 2"),
+            "unexpected error: {message}"
+        );
+    });
+
+#[gpu_test]
+static SAMPLER_ANISOTROPY_WITH_NEAREST_FILTER: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        let message = sampler_error(
+            &ctx,
+            &wgpu::SamplerDescriptor {
+                mag_filter: wgpu::FilterMode::Nearest,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Linear,
+                anisotropy_clamp: 4,
+                ..Default::default()
+            },
+        )
+        .expect("an anisotropic sampler with a nearest filter should fail");
+        assert!(
+            message.contains("Invalid filter mode for magFilter: Nearest"),
+            "unexpected error: {message}"
+        );
+
+        assert_eq!(
+            sampler_error(
+                &ctx,
+                &wgpu::SamplerDescriptor {
+                    mag_filter: wgpu::FilterMode::Linear,
+                    min_filter: wgpu::FilterMode::Linear,
+                    mipmap_filter: wgpu::FilterMode::Linear,
+                    anisotropy_clamp: 4,
+                    ..Default::default()
+                },
+            ),
+            None
+        );
+    });
+
+/// Bind a view of a `Depth24PlusStencil8` texture with `aspect` as a depth
+/// texture, and return the validation error message, if any.
+fn bind_depth_stencil_view_error(
+    ctx: &wgpu_test::TestingContext,
+    aspect: wgpu::TextureAspect,
+) -> Option<String> {
+    let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: 4,
+            height: 4,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Depth24PlusStencil8,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let layout = ctx
+        .device
+        .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor {
+        aspect,
+        ..Default::default()
+    });
+    let _ = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout: &layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(&view),
+        }],
+    });
+    pollster::block_on(ctx.device.pop_error_scope()).map(|error| error.to_string())
+}
+
+// A view of both aspects can be created, it's binding it that fails.
+#[gpu_test]
+static BIND_DEPTH_STENCIL_VIEW_WITH_ALL_ASPECT: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        let message = bind_depth_stencil_view_error(&ctx, wgpu::TextureAspect::All)
+            .expect("binding a view of both depth and stencil should fail");
+        assert!(
+            message.contains("Use `TextureAspect::DepthOnly` or `TextureAspect::StencilOnly`"),
+            "unexpected error: {message}"
+        );
+    });
+
+#[gpu_test]
+static BIND_DEPTH_STENCIL_VIEW_WITH_DEPTH_ONLY_ASPECT: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        assert_eq!(
+            bind_depth_stencil_view_error(&ctx, wgpu::TextureAspect::DepthOnly),
+            None
+        );
+    });
+
+/// Create a 4x4 `Rgba8Unorm` render target with `sample_count` samples, and
+/// return the validation error message, if any.
+fn multisampled_texture_error(
+    ctx: &wgpu_test::TestingContext,
+    sample_count: u32,
+) -> Option<String> {
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let _ = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: 4,
+            height: 4,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    pollster::block_on(ctx.device.pop_error_scope()).map(|error| error.to_string())
+}
+
+// Without TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES only the guaranteed
+// sample counts of 1 and 4 are supported.
+#[gpu_test]
+static TEXTURE_WITH_UNSUPPORTED_SAMPLE_COUNT: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        let message = multisampled_texture_error(&ctx, 16)
+            .expect("a texture with an unsupported sample count should fail");
+        assert!(
+            message.contains(
+                "Sample count 16 is not supported by format Rgba8Unorm on this device. \
+                 The WebGPU spec guarentees [1, 4] samples are supported by this format."
+            ),
+            "unexpected error: {message}"
+        );
+    });
+
+#[gpu_test]
+static TEXTURE_WITH_SUPPORTED_SAMPLE_COUNT: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        assert_eq!(multisampled_texture_error(&ctx, 4), None);
+    });
@@ -1,4 +1,4 @@
-use wgpu_test::{fail, gpu_test, GpuTestConfiguration};
+use wgpu_test::{fail, gpu_test, GpuTestConfiguration, TestParameters, TestingContext};
 
 #[gpu_test]
 static COPY_OVERFLOW_Z: GpuTestConfiguration = GpuTestConfiguration::new().run_sync(|ctx| {
@@ -63,3 +63,364 @@ static COPY_OVERFLOW_Z: GpuTestConfiguration = GpuTestConfiguration::new().run_s
         ctx.queue.submit(Some(encoder.finish()));
     });
 });
+
+fn create_layered_texture(ctx: &TestingContext) -> wgpu::Texture {
+    ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        dimension: wgpu::TextureDimension::D2,
+        size: wgpu::Extent3d {
+            width: 64,
+            height: 64,
+            depth_or_array_layers: 4,
+        },
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+        mip_level_count: 2,
+        sample_count: 1,
+        view_formats: &[],
+    })
+}
+
+fn copy_within(
+    ctx: &TestingContext,
+    texture: &wgpu::Texture,
+    source: (u32, u32),
+    destination: (u32, u32),
+) {
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    let (src_mip_level, src_layer) = source;
+    let (dst_mip_level, dst_layer) = destination;
+    encoder.copy_texture_to_texture(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: src_mip_level,
+            origin: wgpu::Origin3d {
+                x: 0,
+                y: 0,
+                z: src_layer,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: dst_mip_level,
+            origin: wgpu::Origin3d {
+                x: 0,
+                y: 0,
+                z: dst_layer,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::Extent3d {
+            width: 32,
+            height: 32,
+            depth_or_array_layers: 2,
+        },
+    );
+    ctx.queue.submit(Some(encoder.finish()));
+}
+
+#[gpu_test]
+static COPY_SAME_TEXTURE_OVERLAPPING: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let texture = create_layered_texture(&ctx);
+
+        fail(&ctx.device, || {
+            // Layers 0..2 and 1..3 of mip level 0 share layer 1.
+            copy_within(&ctx, &texture, (0, 0), (0, 1));
+        });
+    });
+
+#[gpu_test]
+static COPY_SAME_TEXTURE_DISJOINT: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let texture = create_layered_texture(&ctx);
+
+        // Disjoint layers of the same mip level.
+        copy_within(&ctx, &texture, (0, 0), (0, 2));
+        // The same layers of different mip levels.
+        copy_within(&ctx, &texture, (0, 0), (1, 0));
+
+        ctx.device.poll(wgpu::Maintain::Wait);
+    });
+
+fn copy_empty_extent(
+    ctx: &TestingContext,
+    texture: &wgpu::Texture,
+    origin: wgpu::Origin3d,
+    buffer_usage: wgpu::BufferUsages,
+) {
+    let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 256,
+        usage: buffer_usage,
+        mapped_at_creation: false,
+    });
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout::default(),
+        },
+        wgpu::Extent3d {
+            width: 0,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+    );
+    ctx.queue.submit(Some(encoder.finish()));
+}
+
+#[gpu_test]
+static COPY_EMPTY_EXTENT_AT_EDGE: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let texture = create_layered_texture(&ctx);
+
+        // An empty copy starting right at the edge of the texture is valid.
+        copy_empty_extent(
+            &ctx,
+            &texture,
+            wgpu::Origin3d { x: 64, y: 0, z: 0 },
+            wgpu::BufferUsages::COPY_DST,
+        );
+
+        ctx.device.poll(wgpu::Maintain::Wait);
+    });
+
+#[gpu_test]
+static COPY_EMPTY_EXTENT_OUT_OF_BOUNDS: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        let texture = create_layered_texture(&ctx);
+
+        fail(&ctx.device, || {
+            // Empty copies are still validated against the texture size.
+            copy_empty_extent(
+                &ctx,
+                &texture,
+                wgpu::Origin3d { x: 65, y: 0, z: 0 },
+                wgpu::BufferUsages::COPY_DST,
+            );
+        });
+    });
+
+#[gpu_test]
+static COPY_EMPTY_EXTENT_INVALID_BUFFER_USAGE: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        let texture = create_layered_texture(&ctx);
+
+        fail(&ctx.device, || {
+            // Empty copies still require the buffer to be a copy destination.
+            copy_empty_extent(
+                &ctx,
+                &texture,
+                wgpu::Origin3d::ZERO,
+                wgpu::BufferUsages::MAP_READ,
+            );
+        });
+    });
+
+#[gpu_test]
+static WRITE_BUFFER_EMPTY: GpuTestConfiguration = GpuTestConfiguration::new().run_sync(|ctx| {
+    let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 256,
+        usage: wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    // An empty write to a copy destination is valid.
+    ctx.queue.write_buffer(&buffer, 0, &[]);
+    ctx.queue.submit(None);
+    ctx.device.poll(wgpu::Maintain::Wait);
+});
+
+#[gpu_test]
+static WRITE_BUFFER_EMPTY_INVALID_USAGE: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 256,
+            usage: wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        fail(&ctx.device, || {
+            // Empty writes are still validated against the buffer usage.
+            ctx.queue.write_buffer(&buffer, 0, &[]);
+        });
+    });
+
+fn copy_buffer_to_bc7(
+    ctx: &TestingContext,
+    origin: wgpu::Origin3d,
+    size: wgpu::Extent3d,
+) -> String {
+    let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        dimension: wgpu::TextureDimension::D2,
+        size: wgpu::Extent3d {
+            width: 16,
+            height: 16,
+            depth_or_array_layers: 1,
+        },
+        format: wgpu::TextureFormat::Bc7RgbaUnorm,
+        usage: wgpu::TextureUsages::COPY_DST,
+        mip_level_count: 1,
+        sample_count: 1,
+        view_formats: &[],
+    });
+    let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 1024,
+        usage: wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_buffer_to_texture(
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(256),
+                rows_per_image: None,
+            },
+        },
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin,
+            aspect: wgpu::TextureAspect::All,
+        },
+        size,
+    );
+    drop(encoder.finish());
+
+    pollster::block_on(ctx.device.pop_error_scope())
+        .expect("a misaligned copy into a block-compressed texture should fail")
+        .to_string()
+}
+
+#[gpu_test]
+static COPY_TO_BLOCK_FORMAT_UNALIGNED_ORIGIN: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().features(wgpu::Features::TEXTURE_COMPRESSION_BC))
+    .run_sync(|ctx| {
+        let message = copy_buffer_to_bc7(
+            &ctx,
+            wgpu::Origin3d { x: 2, y: 0, z: 0 },
+            wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+        );
+        assert!(
+            message.contains("Copy origin 2 on the X axis is not a multiple of the texture format's block size 4"),
+            "unexpected error: {message}"
+        );
+    });
+
+#[gpu_test]
+static COPY_TO_BLOCK_FORMAT_UNALIGNED_EXTENT: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default().features(wgpu::Features::TEXTURE_COMPRESSION_BC))
+    .run_sync(|ctx| {
+        let message = copy_buffer_to_bc7(
+            &ctx,
+            wgpu::Origin3d::ZERO,
+            wgpu::Extent3d {
+                width: 4,
+                height: 6,
+                depth_or_array_layers: 1,
+            },
+        );
+        assert!(
+            message.contains("Copy extent 6 on the Y axis is not a multiple of the texture format's block size 4"),
+            "unexpected error: {message}"
+        );
+    });
+
+/// Copy a 4x4 texture of `src_format` into one of `dst_format`, and return
+/// the validation error message, if any.
+fn copy_between_formats(
+    ctx: &TestingContext,
+    src_format: wgpu::TextureFormat,
+    dst_format: wgpu::TextureFormat,
+) -> Option<String> {
+    let create_texture = |format, usage| {
+        ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            dimension: wgpu::TextureDimension::D2,
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            format,
+            usage,
+            mip_level_count: 1,
+            sample_count: 1,
+            view_formats: &[],
+        })
+    };
+    let src = create_texture(src_format, wgpu::TextureUsages::COPY_SRC);
+    let dst = create_texture(dst_format, wgpu::TextureUsages::COPY_DST);
+
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_texture(
+        src.as_image_copy(),
+        dst.as_image_copy(),
+        wgpu::Extent3d {
+            width: 4,
+            height: 4,
+            depth_or_array_layers: 1,
+        },
+    );
+    drop(encoder.finish());
+
+    pollster::block_on(ctx.device.pop_error_scope()).map(|error| error.to_string())
+}
+
+// Formats which only differ in srgb-ness are copy-compatible.
+#[gpu_test]
+static COPY_BETWEEN_SRGB_AND_LINEAR_FORMATS: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        assert_eq!(
+            copy_between_formats(
+                &ctx,
+                wgpu::TextureFormat::Rgba8Unorm,
+                wgpu::TextureFormat::Rgba8UnormSrgb,
+            ),
+            None
+        );
+    });
+
+#[gpu_test]
+static COPY_BETWEEN_INCOMPATIBLE_FORMATS: GpuTestConfiguration = GpuTestConfiguration::new()
+    .run_sync(|ctx| {
+        let message = copy_between_formats(
+            &ctx,
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Rg16Float,
+        )
+        .expect("copying between formats of the same size but different kinds should fail");
+        assert!(
+            message.contains("Source format (Rgba8Unorm) and destination format (Rg16Float) are not copy-compatible"),
+            "unexpected error: {message}"
+        );
+    });
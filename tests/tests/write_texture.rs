@@ -187,3 +187,47 @@ static WRITE_TEXTURE_SUBSET_3D: GpuTestConfiguration =
             assert_eq!(*byte, 0);
         }
     });
+
+// Writing to a texture without `COPY_DST` reports the missing usage by name.
+#[gpu_test]
+static WRITE_TEXTURE_WITHOUT_COPY_DST: GpuTestConfiguration =
+    GpuTestConfiguration::new().run_sync(|ctx| {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            dimension: wgpu::TextureDimension::D2,
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            mip_level_count: 1,
+            sample_count: 1,
+            view_formats: &[],
+        });
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.queue.write_texture(
+            texture.as_image_copy(),
+            &[0; 64],
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(16),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let error = pollster::block_on(ctx.device.pop_error_scope())
+            .expect("writing to a texture without COPY_DST should fail");
+        let message = error.to_string();
+        assert!(
+            message.contains("missing the `COPY_DST` usage flag"),
+            "unexpected error: {message}"
+        );
+    });
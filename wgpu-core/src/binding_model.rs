@@ -11,8 +11,8 @@ use crate::{
         TextureViewId,
     },
     init_tracker::{BufferInitTrackerAction, TextureInitTrackerAction},
+    registry::ResourceLogEvent,
     resource::{Resource, ResourceInfo, ResourceType},
-    resource_log,
     snatch::SnatchGuard,
     track::{BindGroupStates, UsageConflict},
     validation::{MissingBufferUsageError, MissingTextureUsageError},
@@ -41,6 +41,10 @@ pub enum BindGroupLayoutEntryError {
     ArrayUnsupported,
     #[error("Multisampled binding with sample type `TextureSampleType::Float` must have filterable set to false.")]
     SampleTypeFloatFilterableBindingMultisampled,
+    #[error(
+        "Buffer bindings with a dynamic offset are not allowed in a layout with UPDATE_AFTER_BIND"
+    )]
+    DynamicOffsetWithUpdateAfterBind,
     #[error(transparent)]
     MissingFeatures(#[from] MissingFeatures),
     #[error(transparent)]
@@ -66,6 +70,10 @@ pub enum CreateBindGroupLayoutError {
     InvalidBindingIndex { binding: u32, maximum: u32 },
     #[error("Invalid visibility {0:?}")]
     InvalidVisibility(wgt::ShaderStages),
+    #[error("Invalid flags {0:?}")]
+    InvalidFlags(wgt::BindGroupLayoutFlags),
+    #[error("Bind group layout flags {0:?} can't be used due to missing features")]
+    MissingFeatures(wgt::BindGroupLayoutFlags, #[source] MissingFeatures),
 }
 
 //TODO: refactor this to move out `enum BindingError`.
@@ -85,6 +93,8 @@ pub enum CreateBindGroupError {
     InvalidTexture(TextureId),
     #[error("Sampler {0:?} is invalid")]
     InvalidSampler(SamplerId),
+    #[error("A resource bound at binding {binding} is invalid")]
+    InvalidResource { binding: u32 },
     #[error(
         "Binding count declared with at most {expected} items, but {actual} items were provided"
     )]
@@ -178,8 +188,12 @@ pub enum CreateBindGroupError {
         layout_flt: bool,
         sampler_flt: bool,
     },
-    #[error("Bound texture views can not have both depth and stencil aspects enabled")]
-    DepthStencilAspect,
+    #[error("Texture binding {binding} is given a view of both aspects of {format:?}, but bound views may only have one. Use `TextureAspect::DepthOnly` or `TextureAspect::StencilOnly` instead of {aspect:?}")]
+    InvalidAspectForFormat {
+        binding: u32,
+        format: wgt::TextureFormat,
+        aspect: wgt::TextureAspect,
+    },
     #[error("The adapter does not support read access for storages texture of format {0:?}")]
     StorageReadNotSupported(wgt::TextureFormat),
     #[error(transparent)]
@@ -442,6 +456,10 @@ pub struct BindGroupLayoutDescriptor<'a> {
     pub label: Label<'a>,
     /// Array of entries in this BindGroupLayout
     pub entries: Cow<'a, [wgt::BindGroupLayoutEntry]>,
+    /// Flags for the bindings of this BindGroupLayout, see
+    /// [`wgt::BindGroupLayoutFlags`].
+    #[cfg_attr(any(feature = "trace", feature = "replay"), serde(default))]
+    pub flags: wgt::BindGroupLayoutFlags,
 }
 
 pub type BindGroupLayouts<A> = crate::storage::Storage<BindGroupLayout<A>, BindGroupLayoutId>;
@@ -476,7 +494,11 @@ impl<A: HalApi> Drop for BindGroupLayout<A> {
                 t.add(trace::Action::DestroyBindGroupLayout(self.info.id()));
             }
 
-            resource_log!("Destroy raw BindGroupLayout {:?}", self.info.label());
+            self.log_event(
+                &self.device,
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw BindGroupLayout {:?}", self.info.label()),
+            );
             unsafe {
                 use hal::Device;
                 self.device.raw().destroy_bind_group_layout(raw);
@@ -496,8 +518,8 @@ impl<A: HalApi> Resource<BindGroupLayoutId> for BindGroupLayout<A> {
         &mut self.info
     }
 
-    fn label(&self) -> String {
-        self.label.clone()
+    fn label_into(&self, out: &mut String) {
+        out.push_str(&self.label);
     }
 }
 impl<A: HalApi> BindGroupLayout<A> {
@@ -614,7 +636,11 @@ pub struct PipelineLayout<A: HalApi> {
 impl<A: HalApi> Drop for PipelineLayout<A> {
     fn drop(&mut self) {
         if let Some(raw) = self.raw.take() {
-            resource_log!("Destroy raw PipelineLayout {:?}", self.info.label());
+            self.log_event(
+                &self.device,
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw PipelineLayout {:?}", self.info.label()),
+            );
 
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
@@ -849,7 +875,11 @@ pub struct BindGroup<A: HalApi> {
 impl<A: HalApi> Drop for BindGroup<A> {
     fn drop(&mut self) {
         if let Some(raw) = self.raw.take() {
-            resource_log!("Destroy raw BindGroup {:?}", self.info.label());
+            self.log_event(
+                &self.device,
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw BindGroup {:?}", self.info.label()),
+            );
 
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
@@ -942,8 +972,8 @@ impl<A: HalApi> Resource<BindGroupId> for BindGroup<A> {
 pub enum GetBindGroupLayoutError {
     #[error("Pipeline is invalid")]
     InvalidPipeline,
-    #[error("Invalid group index {0}")]
-    InvalidGroupIndex(u32),
+    #[error("Bind group index {index} is out of range, the pipeline has {count} bind groups")]
+    IndexOutOfRange { index: u32, count: u32 },
 }
 
 #[derive(Clone, Debug, Error, Eq, PartialEq)]
@@ -97,8 +97,8 @@ use crate::{
     id::{self, RenderBundleId},
     init_tracker::{BufferInitTrackerAction, MemoryInitKind, TextureInitTrackerAction},
     pipeline::{self, PipelineFlags, RenderPipeline},
+    registry::ResourceLogEvent,
     resource::{Resource, ResourceInfo, ResourceType},
-    resource_log,
     track::RenderBundleScope,
     validation::check_buffer_usage,
     Label, LabelHelpers,
@@ -768,7 +768,11 @@ pub struct RenderBundle<A: HalApi> {
 
 impl<A: HalApi> Drop for RenderBundle<A> {
     fn drop(&mut self) {
-        resource_log!("Destroy raw RenderBundle {:?}", self.info.label());
+        self.log_event(
+            &self.device,
+            ResourceLogEvent::Destroyed,
+            format_args!("Destroy raw RenderBundle {:?}", self.info.label()),
+        );
 
         #[cfg(feature = "trace")]
         if let Some(t) = self.device.trace.lock().as_mut() {
@@ -794,7 +798,49 @@ unsafe impl<A: HalApi> Send for RenderBundle<A> {}
 ))]
 unsafe impl<A: HalApi> Sync for RenderBundle<A> {}
 
+/// What a render pass must look like for a [`RenderBundle`] to be executed in
+/// it, see [`Global::render_bundle_get_compatibility`].
+///
+/// [`Global::render_bundle_get_compatibility`]: crate::global::Global::render_bundle_get_compatibility
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderBundleCompatibility {
+    pub color_formats: Vec<Option<wgt::TextureFormat>>,
+    pub depth_stencil_format: Option<wgt::TextureFormat>,
+    pub sample_count: u32,
+    pub multiview: Option<NonZeroU32>,
+    pub depth_read_only: bool,
+    pub stencil_read_only: bool,
+    /// The layouts of all bind groups set by the bundle.
+    pub bind_group_layouts: Vec<id::BindGroupLayoutId>,
+}
+
+#[derive(Clone, Debug, Error)]
+#[error("Render bundle is invalid")]
+pub struct InvalidRenderBundle;
+
 impl<A: HalApi> RenderBundle<A> {
+    pub(crate) fn compatibility(&self) -> RenderBundleCompatibility {
+        let mut bind_group_layouts = self
+            .used
+            .bind_groups
+            .read()
+            .used_resources()
+            .map(|bind_group| bind_group.layout.as_info().id())
+            .collect::<Vec<_>>();
+        bind_group_layouts.sort_unstable();
+        bind_group_layouts.dedup();
+
+        RenderBundleCompatibility {
+            color_formats: self.context.attachments.colors.iter().cloned().collect(),
+            depth_stencil_format: self.context.attachments.depth_stencil,
+            sample_count: self.context.sample_count,
+            multiview: self.context.multiview,
+            depth_read_only: self.is_depth_read_only,
+            stencil_read_only: self.is_stencil_read_only,
+            bind_group_layouts,
+        }
+    }
+
     /// Actually encode the contents into a native command buffer.
     ///
     /// This is partially duplicating the logic of `command_encoder_run_render_pass`.
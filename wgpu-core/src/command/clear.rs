@@ -1,4 +1,4 @@
-use std::{ops::Range, sync::Arc};
+use std::{fmt, ops::Range, sync::Arc};
 
 #[cfg(feature = "trace")]
 use crate::device::trace::Command as TraceCommand;
@@ -36,10 +36,14 @@ pub enum ClearError {
     InvalidTexture(TextureId),
     #[error("Texture {0:?} can not be cleared")]
     NoValidTextureClearMode(TextureId),
-    #[error("Buffer clear size {0:?} is not a multiple of `COPY_BUFFER_ALIGNMENT`")]
-    UnalignedFillSize(BufferAddress),
-    #[error("Buffer offset {0:?} is not a multiple of `COPY_BUFFER_ALIGNMENT`")]
-    UnalignedBufferOffset(BufferAddress),
+    #[error(
+        "Buffer clear {offset_or_size} {value} is not a multiple of `COPY_BUFFER_ALIGNMENT` ({})",
+        wgt::COPY_BUFFER_ALIGNMENT
+    )]
+    UnalignedClear {
+        offset_or_size: ClearOffsetOrSize,
+        value: BufferAddress,
+    },
     #[error("Clear of {start_offset}..{end_offset} would end up overrunning the bounds of the buffer of size {buffer_size}")]
     BufferOverrun {
         start_offset: BufferAddress,
@@ -71,7 +75,37 @@ whereas subesource range specified start {subresource_base_array_layer} and coun
     Device(#[from] DeviceError),
 }
 
+/// Which part of a buffer clear a [`ClearError::UnalignedClear`] is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClearOffsetOrSize {
+    Offset,
+    /// The size given, or if none was given, the size from the offset to the
+    /// end of the buffer.
+    Size,
+}
+
+impl fmt::Display for ClearOffsetOrSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Offset => f.write_str("offset"),
+            Self::Size => f.write_str("size"),
+        }
+    }
+}
+
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
+    /// Clear all of `dst` to zero.
+    ///
+    /// This is [`Global::command_encoder_clear_buffer`] with an offset of 0 and
+    /// no size, and likewise requires `dst` to have the `COPY_DST` usage.
+    pub fn command_encoder_clear_buffer_all<A: HalApi>(
+        &self,
+        command_encoder_id: CommandEncoderId,
+        dst: BufferId,
+    ) -> Result<(), ClearError> {
+        self.command_encoder_clear_buffer::<A>(command_encoder_id, dst, 0, None)
+    }
+
     pub fn command_encoder_clear_buffer<A: HalApi>(
         &self,
         command_encoder_id: CommandEncoderId,
@@ -116,26 +150,29 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         // Check if offset & size are valid.
         if offset % wgt::COPY_BUFFER_ALIGNMENT != 0 {
-            return Err(ClearError::UnalignedBufferOffset(offset));
-        }
-        if let Some(size) = size {
-            if size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
-                return Err(ClearError::UnalignedFillSize(size));
-            }
-            let destination_end_offset = offset + size;
-            if destination_end_offset > dst_buffer.size {
-                return Err(ClearError::BufferOverrun {
-                    start_offset: offset,
-                    end_offset: destination_end_offset,
-                    buffer_size: dst_buffer.size,
-                });
-            }
+            return Err(ClearError::UnalignedClear {
+                offset_or_size: ClearOffsetOrSize::Offset,
+                value: offset,
+            });
         }
-
         let end = match size {
             Some(size) => offset + size,
-            None => dst_buffer.size,
+            None => dst_buffer.size.max(offset),
         };
+        if end > dst_buffer.size {
+            return Err(ClearError::BufferOverrun {
+                start_offset: offset,
+                end_offset: end,
+                buffer_size: dst_buffer.size,
+            });
+        }
+        if (end - offset) % wgt::COPY_BUFFER_ALIGNMENT != 0 {
+            return Err(ClearError::UnalignedClear {
+                offset_or_size: ClearOffsetOrSize::Size,
+                value: end - offset,
+            });
+        }
+
         if offset == end {
             log::trace!("Ignoring fill_buffer of size 0");
             return Ok(());
@@ -245,6 +282,27 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             device.zero_buffer.as_ref().unwrap(),
         )
     }
+
+    /// Clear every aspect, mip level and array layer of `dst`.
+    ///
+    /// This is [`Global::command_encoder_clear_texture`] with a subresource
+    /// range covering the whole texture, and is validated the same way.
+    pub fn command_encoder_clear_texture_all<A: HalApi>(
+        &self,
+        command_encoder_id: CommandEncoderId,
+        dst: TextureId,
+    ) -> Result<(), ClearError> {
+        // Leaving the counts unset makes the range extend to the texture's
+        // mip level and array layer counts.
+        let subresource_range = ImageSubresourceRange {
+            aspect: TextureAspect::All,
+            base_mip_level: 0,
+            mip_level_count: None,
+            base_array_layer: 0,
+            array_layer_count: None,
+        };
+        self.command_encoder_clear_texture::<A>(command_encoder_id, dst, &subresource_range)
+    }
 }
 
 pub(crate) fn clear_texture<A: HalApi>(
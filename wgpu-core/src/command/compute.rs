@@ -393,6 +393,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             });
         }
 
+        cmd_buf_data
+            .count_commands(base.commands.len())
+            .map_pass_err(pass_scope)?;
+
         let encoder = &mut cmd_buf_data.encoder;
         let status = &mut cmd_buf_data.status;
         let tracker = &mut cmd_buf_data.trackers;
@@ -856,6 +860,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
         }
 
+        if let Some((_, query_index)) = active_query {
+            return Err(ComputePassErrorInner::QueryUse(QueryUseError::NotStopped {
+                active_query_index: query_index,
+            }))
+            .map_pass_err(pass_scope);
+        }
+
         unsafe {
             raw.end_compute_pass();
         }
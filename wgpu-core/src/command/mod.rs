@@ -13,7 +13,13 @@ use std::sync::Arc;
 
 pub(crate) use self::clear::clear_texture;
 pub use self::{
-    bundle::*, clear::ClearError, compute::*, draw::*, query::*, render::*, transfer::*,
+    bundle::*,
+    clear::{ClearError, ClearOffsetOrSize},
+    compute::*,
+    draw::*,
+    query::*,
+    render::*,
+    transfer::*,
 };
 
 use self::memory_init::CommandBufferTextureMemoryActions;
@@ -25,11 +31,11 @@ use crate::id::CommandBufferId;
 use crate::snatch::SnatchGuard;
 
 use crate::init_tracker::BufferInitTrackerAction;
+use crate::registry::ResourceLogEvent;
 use crate::resource::{Resource, ResourceInfo, ResourceType};
 use crate::track::{Tracker, UsageScope};
 use crate::{
-    api_log, global::Global, hal_api::HalApi, id, identity::GlobalIdentityHandlerFactory,
-    resource_log, Label,
+    api_log, global::Global, hal_api::HalApi, id, identity::GlobalIdentityHandlerFactory, Label,
 };
 
 use hal::CommandEncoder as _;
@@ -121,11 +127,45 @@ pub struct CommandBufferMutable<A: HalApi> {
     buffer_memory_init_actions: Vec<BufferInitTrackerAction<A>>,
     texture_memory_actions: CommandBufferTextureMemoryActions<A>,
     pub(crate) pending_query_resets: QueryResetMap<A>,
+    /// The number of debug groups pushed and not yet popped.
+    debug_scope_depth: u32,
+    command_limit: CommandLimit,
     #[cfg(feature = "trace")]
     pub(crate) commands: Option<Vec<TraceCommand>>,
 }
 
+/// The number of pass commands recorded into an encoder, and the limit set
+/// with [`Global::command_encoder_set_command_limit`], if any.
+#[derive(Debug, Default)]
+struct CommandLimit {
+    limit: Option<u32>,
+    count: u64,
+}
+
+impl CommandLimit {
+    fn record(&mut self, count: usize) -> Result<(), CommandEncoderError> {
+        self.count += count as u64;
+        match self.limit {
+            Some(limit) if self.count > u64::from(limit) => {
+                Err(CommandEncoderError::CommandLimitExceeded { limit })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 impl<A: HalApi> CommandBufferMutable<A> {
+    /// Count `count` commands of a pass against the command limit.
+    ///
+    /// The encoder becomes invalid once the limit is exceeded.
+    pub(crate) fn count_commands(&mut self, count: usize) -> Result<(), CommandEncoderError> {
+        let result = self.command_limit.record(count);
+        if result.is_err() {
+            self.status = CommandEncoderStatus::Error;
+        }
+        result
+    }
+
     pub(crate) fn open_encoder_and_tracker(
         &mut self,
     ) -> Result<(&mut A::CommandEncoder, &mut Tracker<A>), DeviceError> {
@@ -149,7 +189,11 @@ impl<A: HalApi> Drop for CommandBuffer<A> {
         if self.data.lock().is_none() {
             return;
         }
-        resource_log!("resource::CommandBuffer::drop {:?}", self.info.label());
+        self.log_event(
+            &self.device,
+            ResourceLogEvent::Destroyed,
+            format_args!("resource::CommandBuffer::drop {:?}", self.info.label()),
+        );
         let mut baked = self.extract_baked_commands();
         unsafe {
             baked.encoder.reset_all(baked.list.into_iter());
@@ -190,6 +234,8 @@ impl<A: HalApi> CommandBuffer<A> {
                 buffer_memory_init_actions: Default::default(),
                 texture_memory_actions: Default::default(),
                 pending_query_resets: QueryResetMap::new(),
+                debug_scope_depth: 0,
+                command_limit: CommandLimit::default(),
                 #[cfg(feature = "trace")]
                 commands: if enable_tracing {
                     Some(Vec::new())
@@ -307,12 +353,10 @@ impl<A: HalApi> Resource<CommandBufferId> for CommandBuffer<A> {
         &mut self.info
     }
 
-    fn label(&self) -> String {
-        let str = match self.data.lock().as_ref().unwrap().encoder.label.as_ref() {
-            Some(label) => label.clone(),
-            _ => String::new(),
-        };
-        str
+    fn label_into(&self, out: &mut String) {
+        if let Some(label) = self.data.lock().as_ref().unwrap().encoder.label.as_ref() {
+            out.push_str(label);
+        }
     }
 }
 
@@ -411,10 +455,44 @@ pub enum CommandEncoderError {
     Invalid,
     #[error("Command encoder must be active")]
     NotRecording,
+    #[error("Command encoder was finished with {open} debug group(s) still open")]
+    UnbalancedDebugGroups { open: u32 },
+    #[error("Cannot pop debug group, because number of pushed debug groups is zero")]
+    InvalidPopDebugGroup,
+    #[error("Command encoder recorded more than its limit of {limit} pass commands")]
+    CommandLimitExceeded { limit: u32 },
     #[error(transparent)]
     Device(#[from] DeviceError),
 }
 
+/// Error encountered when creating a command encoder.
+#[derive(Clone, Debug, Error)]
+#[non_exhaustive]
+pub enum CreateCommandEncoderError {
+    #[error("Parent device is invalid")]
+    InvalidDevice,
+    #[error("Parent device is lost")]
+    DeviceLost,
+    #[error("Not enough memory left to create the command encoder")]
+    OutOfMemory,
+    #[error("Queue of the parent device is invalid")]
+    InvalidQueue,
+    #[error(transparent)]
+    Device(DeviceError),
+}
+
+impl From<DeviceError> for CreateCommandEncoderError {
+    fn from(error: DeviceError) -> Self {
+        match error {
+            DeviceError::Invalid => Self::InvalidDevice,
+            DeviceError::Lost => Self::DeviceLost,
+            DeviceError::OutOfMemory => Self::OutOfMemory,
+            DeviceError::InvalidQueueId => Self::InvalidQueue,
+            error => Self::Device(error),
+        }
+    }
+}
+
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
     pub fn command_encoder_finish<A: HalApi>(
         &self,
@@ -430,6 +508,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 let mut cmd_buf_data = cmd_buf.data.lock();
                 let cmd_buf_data = cmd_buf_data.as_mut().unwrap();
                 match cmd_buf_data.status {
+                    CommandEncoderStatus::Recording if cmd_buf_data.debug_scope_depth > 0 => {
+                        cmd_buf_data.encoder.discard();
+                        cmd_buf_data.status = CommandEncoderStatus::Error;
+                        Some(CommandEncoderError::UnbalancedDebugGroups {
+                            open: cmd_buf_data.debug_scope_depth,
+                        })
+                    }
                     CommandEncoderStatus::Recording => {
                         if let Err(e) = cmd_buf_data.encoder.close() {
                             Some(e.into())
@@ -454,10 +539,56 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         (encoder_id, error)
     }
 
+    /// Limit the number of commands which may be recorded into the render and
+    /// compute passes of `encoder_id`, or remove the limit with `None`.
+    ///
+    /// Commands already recorded count against the limit. Running a pass which
+    /// takes the count over the limit fails with
+    /// [`CommandEncoderError::CommandLimitExceeded`] and invalidates the
+    /// encoder. This bounds the work of validating and replaying untrusted
+    /// command streams. Encoders have no limit by default.
+    pub fn command_encoder_set_command_limit<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        limit: Option<u32>,
+    ) -> Result<(), CommandEncoderError> {
+        api_log!("CommandEncoder::set_command_limit {encoder_id:?} {limit:?}");
+
+        let hub = A::hub(self);
+
+        let cmd_buf = CommandBuffer::get_encoder(hub, encoder_id)?;
+        let mut cmd_buf_data = cmd_buf.data.lock();
+        let cmd_buf_data = cmd_buf_data.as_mut().unwrap();
+        cmd_buf_data.command_limit.limit = limit;
+        Ok(())
+    }
+
     pub fn command_encoder_push_debug_group<A: HalApi>(
         &self,
         encoder_id: id::CommandEncoderId,
         label: &str,
+    ) -> Result<(), CommandEncoderError> {
+        self.command_encoder_push_debug_group_impl::<A>(encoder_id, label, None)
+    }
+
+    /// Like [`Global::command_encoder_push_debug_group`], with an RGBA
+    /// `color` shown by graphics debuggers which support it.
+    ///
+    /// Only Vulkan attaches the color. Metal, GLES and DX12 ignore it.
+    pub fn command_encoder_push_debug_group_colored<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        label: &str,
+        color: [f32; 4],
+    ) -> Result<(), CommandEncoderError> {
+        self.command_encoder_push_debug_group_impl::<A>(encoder_id, label, Some(color))
+    }
+
+    fn command_encoder_push_debug_group_impl<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        label: &str,
+        color: Option<[f32; 4]>,
     ) -> Result<(), CommandEncoderError> {
         profiling::scope!("CommandEncoder::push_debug_group");
         api_log!("CommandEncoder::push_debug_group {label}");
@@ -472,6 +603,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             list.push(TraceCommand::PushDebugGroup(label.to_string()));
         }
 
+        cmd_buf_data.debug_scope_depth += 1;
+
         let cmd_buf_raw = cmd_buf_data.encoder.open()?;
         if !self
             .instance
@@ -479,7 +612,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .contains(wgt::InstanceFlags::DISCARD_HAL_LABELS)
         {
             unsafe {
-                cmd_buf_raw.begin_debug_marker(label);
+                match color {
+                    Some(color) => cmd_buf_raw.begin_debug_marker_colored(label, color),
+                    None => cmd_buf_raw.begin_debug_marker(label),
+                }
             }
         }
         Ok(())
@@ -489,6 +625,28 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         &self,
         encoder_id: id::CommandEncoderId,
         label: &str,
+    ) -> Result<(), CommandEncoderError> {
+        self.command_encoder_insert_debug_marker_impl::<A>(encoder_id, label, None)
+    }
+
+    /// Like [`Global::command_encoder_insert_debug_marker`], with an RGBA
+    /// `color` shown by graphics debuggers which support it.
+    ///
+    /// Only Vulkan attaches the color. Metal, GLES and DX12 ignore it.
+    pub fn command_encoder_insert_debug_marker_colored<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        label: &str,
+        color: [f32; 4],
+    ) -> Result<(), CommandEncoderError> {
+        self.command_encoder_insert_debug_marker_impl::<A>(encoder_id, label, Some(color))
+    }
+
+    fn command_encoder_insert_debug_marker_impl<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        label: &str,
+        color: Option<[f32; 4]>,
     ) -> Result<(), CommandEncoderError> {
         profiling::scope!("CommandEncoder::insert_debug_marker");
         api_log!("CommandEncoder::insert_debug_marker {label}");
@@ -511,7 +669,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         {
             let cmd_buf_raw = cmd_buf_data.encoder.open()?;
             unsafe {
-                cmd_buf_raw.insert_debug_marker(label);
+                match color {
+                    Some(color) => cmd_buf_raw.insert_debug_marker_colored(label, color),
+                    None => cmd_buf_raw.insert_debug_marker(label),
+                }
             }
         }
         Ok(())
@@ -530,11 +691,17 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let mut cmd_buf_data = cmd_buf.data.lock();
         let cmd_buf_data = cmd_buf_data.as_mut().unwrap();
 
+        if cmd_buf_data.debug_scope_depth == 0 {
+            return Err(CommandEncoderError::InvalidPopDebugGroup);
+        }
+
         #[cfg(feature = "trace")]
         if let Some(ref mut list) = cmd_buf_data.commands {
             list.push(TraceCommand::PopDebugGroup);
         }
 
+        cmd_buf_data.debug_scope_depth -= 1;
+
         let cmd_buf_raw = cmd_buf_data.encoder.open()?;
         if !self
             .instance
@@ -738,3 +905,42 @@ impl PrettyError for PassErrorScope {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandEncoderError, CommandLimit, CreateCommandEncoderError};
+    use crate::device::DeviceError;
+
+    #[test]
+    fn command_limit() {
+        let mut unlimited = CommandLimit::default();
+        assert!(unlimited.record(1_000_000).is_ok());
+
+        let mut limited = CommandLimit {
+            limit: Some(3),
+            ..Default::default()
+        };
+        assert!(limited.record(2).is_ok());
+        assert!(limited.record(1).is_ok());
+        assert!(matches!(
+            limited.record(1),
+            Err(CommandEncoderError::CommandLimitExceeded { limit: 3 })
+        ));
+    }
+
+    #[test]
+    fn create_command_encoder_error_from_device_error() {
+        assert!(matches!(
+            CreateCommandEncoderError::from(DeviceError::Lost),
+            CreateCommandEncoderError::DeviceLost
+        ));
+        assert!(matches!(
+            CreateCommandEncoderError::from(DeviceError::OutOfMemory),
+            CreateCommandEncoderError::OutOfMemory
+        ));
+        assert!(matches!(
+            CreateCommandEncoderError::from(DeviceError::WrongDevice),
+            CreateCommandEncoderError::Device(DeviceError::WrongDevice)
+        ));
+    }
+}
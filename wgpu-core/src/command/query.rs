@@ -149,6 +149,8 @@ pub enum QueryUseError {
     },
     #[error("Query was stopped while there was no active query")]
     AlreadyStopped,
+    #[error("Query {active_query_index} was still active at the end of the pass")]
+    NotStopped { active_query_index: u32 },
     #[error("A query of type {query_type:?} was started using a query set of type {set_type:?}")]
     IncompatibleType {
         set_type: SimplifiedQueryType,
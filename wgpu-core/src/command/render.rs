@@ -1343,6 +1343,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 return Err(DeviceError::Lost).map_pass_err(pass_scope);
             }
 
+            cmd_buf_data
+                .count_commands(base.commands.len())
+                .map_pass_err(pass_scope)?;
+
             let encoder = &mut cmd_buf_data.encoder;
             let status = &mut cmd_buf_data.status;
             let tracker = &mut cmd_buf_data.trackers;
@@ -2831,3 +2835,54 @@ pub mod render_ffi {
         pass.current_bind_groups.reset();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        LoadOp, PassChannel, RenderPassDepthStencilAttachment, RenderPassErrorInner, StoreOp,
+    };
+    use crate::id::{self, TypedId as _};
+
+    fn attachment(depth: PassChannel<f32>) -> RenderPassDepthStencilAttachment {
+        RenderPassDepthStencilAttachment {
+            view: id::TextureViewId::zip(0, 1, wgt::Backend::Empty),
+            depth,
+            stencil: PassChannel {
+                load_op: LoadOp::Load,
+                store_op: StoreOp::Store,
+                clear_value: 0,
+                read_only: true,
+            },
+        }
+    }
+
+    #[test]
+    fn read_only_depth_with_clear() {
+        let attachment = attachment(PassChannel {
+            load_op: LoadOp::Clear,
+            store_op: StoreOp::Store,
+            clear_value: 1.0,
+            read_only: true,
+        });
+
+        assert!(matches!(
+            attachment.depth_stencil_read_only(hal::FormatAspects::DEPTH),
+            Err(RenderPassErrorInner::InvalidDepthOps)
+        ));
+    }
+
+    #[test]
+    fn read_only_depth_with_load() {
+        let attachment = attachment(PassChannel {
+            load_op: LoadOp::Load,
+            store_op: StoreOp::Store,
+            clear_value: 0.0,
+            read_only: true,
+        });
+
+        assert!(matches!(
+            attachment.depth_stencil_read_only(hal::FormatAspects::DEPTH_STENCIL),
+            Ok((true, true))
+        ));
+    }
+}
@@ -14,7 +14,7 @@ use crate::{
         has_copy_partial_init_tracker_coverage, MemoryInitKind, TextureInitRange,
         TextureInitTrackerAction,
     },
-    resource::{Resource, Texture, TextureErrorDimension},
+    resource::{Buffer, Resource, Texture, TextureErrorDimension},
     track::{TextureSelector, Tracker},
 };
 
@@ -45,10 +45,16 @@ pub enum TransferError {
     InvalidDevice(DeviceId),
     #[error("Buffer {0:?} is invalid or destroyed")]
     InvalidBuffer(BufferId),
+    #[error("Buffer {0:?} is destroyed")]
+    DestroyedBuffer(BufferId),
     #[error("Texture {0:?} is invalid or destroyed")]
     InvalidTexture(TextureId),
     #[error("Source and destination cannot be the same buffer")]
     SameSourceDestinationBuffer,
+    #[error("Destination ranges of copy regions {first} and {second} overlap")]
+    OverlappingDestinationRegions { first: usize, second: usize },
+    #[error("Source and destination subresources of a copy within texture {0:?} overlap")]
+    OverlappingSubresource(TextureId),
     #[error("Source buffer/texture is missing the `COPY_SRC` usage flag")]
     MissingCopySrcUsageFlag,
     #[error("Destination buffer/texture is missing the `COPY_DST` usage flag")]
@@ -83,14 +89,18 @@ pub enum TransferError {
     UnalignedBufferOffset(BufferAddress),
     #[error("Copy size {0} does not respect `COPY_BUFFER_ALIGNMENT`")]
     UnalignedCopySize(BufferAddress),
-    #[error("Copy width is not a multiple of block width")]
-    UnalignedCopyWidth,
-    #[error("Copy height is not a multiple of block height")]
-    UnalignedCopyHeight,
-    #[error("Copy origin's x component is not a multiple of block width")]
-    UnalignedCopyOriginX,
-    #[error("Copy origin's y component is not a multiple of block height")]
-    UnalignedCopyOriginY,
+    #[error("Copy origin {origin} on the {axis:?} axis is not a multiple of the texture format's block size {block}")]
+    UnalignedCopyOriginForBlockFormat {
+        axis: TextureErrorDimension,
+        origin: u32,
+        block: u32,
+    },
+    #[error("Copy extent {extent} on the {axis:?} axis is not a multiple of the texture format's block size {block}")]
+    UnalignedCopyExtentForBlockFormat {
+        axis: TextureErrorDimension,
+        extent: u32,
+        block: u32,
+    },
     #[error("Bytes per row does not respect `COPY_BYTES_PER_ROW_ALIGNMENT`")]
     UnalignedBytesPerRow,
     #[error("Number of bytes per row needs to be specified since more than one row is copied")]
@@ -233,6 +243,20 @@ pub(crate) fn extract_texture_selector<A: HalApi>(
     Ok((selector, base))
 }
 
+/// Check whether two texture copies touch a common subresource.
+///
+/// A copy within a single texture has to read from and write to disjoint sets
+/// of subresources, since each subresource can only be in one state at a time.
+/// For 3D textures the whole mip level is a single subresource, so any copy
+/// within the same mip level overlaps.
+fn texture_selectors_overlap(a: &TextureSelector, b: &TextureSelector) -> bool {
+    fn ranges_overlap(a: &std::ops::Range<u32>, b: &std::ops::Range<u32>) -> bool {
+        a.start < b.end && b.start < a.end
+    }
+
+    ranges_overlap(&a.mips, &b.mips) && ranges_overlap(&a.layers, &b.layers)
+}
+
 /// WebGPU's [validating linear texture data][vltd] algorithm.
 ///
 /// Copied with some modifications from WebGPU standard.
@@ -263,16 +287,25 @@ pub(crate) fn validate_linear_texture_data(
 
     let block_size = format.block_copy_size(Some(aspect)).unwrap() as BufferAddress;
     let (block_width, block_height) = format.block_dimensions();
-    let block_width = block_width as BufferAddress;
-    let block_height = block_height as BufferAddress;
 
-    if copy_width % block_width != 0 {
-        return Err(TransferError::UnalignedCopyWidth);
+    if copy_size.width % block_width != 0 {
+        return Err(TransferError::UnalignedCopyExtentForBlockFormat {
+            axis: TextureErrorDimension::X,
+            extent: copy_size.width,
+            block: block_width,
+        });
     }
-    if copy_height % block_height != 0 {
-        return Err(TransferError::UnalignedCopyHeight);
+    if copy_size.height % block_height != 0 {
+        return Err(TransferError::UnalignedCopyExtentForBlockFormat {
+            axis: TextureErrorDimension::Y,
+            extent: copy_size.height,
+            block: block_height,
+        });
     }
 
+    let block_width = block_width as BufferAddress;
+    let block_height = block_height as BufferAddress;
+
     let width_in_blocks = copy_width / block_width;
     let height_in_blocks = copy_height / block_height;
 
@@ -418,16 +451,32 @@ pub(crate) fn validate_texture_copy_range(
     )?;
 
     if texture_copy_view.origin.x % block_width != 0 {
-        return Err(TransferError::UnalignedCopyOriginX);
+        return Err(TransferError::UnalignedCopyOriginForBlockFormat {
+            axis: TextureErrorDimension::X,
+            origin: texture_copy_view.origin.x,
+            block: block_width,
+        });
     }
     if texture_copy_view.origin.y % block_height != 0 {
-        return Err(TransferError::UnalignedCopyOriginY);
+        return Err(TransferError::UnalignedCopyOriginForBlockFormat {
+            axis: TextureErrorDimension::Y,
+            origin: texture_copy_view.origin.y,
+            block: block_height,
+        });
     }
     if copy_size.width % block_width != 0 {
-        return Err(TransferError::UnalignedCopyWidth);
+        return Err(TransferError::UnalignedCopyExtentForBlockFormat {
+            axis: TextureErrorDimension::X,
+            extent: copy_size.width,
+            block: block_width,
+        });
     }
     if copy_size.height % block_height != 0 {
-        return Err(TransferError::UnalignedCopyHeight);
+        return Err(TransferError::UnalignedCopyExtentForBlockFormat {
+            axis: TextureErrorDimension::Y,
+            extent: copy_size.height,
+            block: block_height,
+        });
     }
 
     let (depth, array_layer_count) = match desc.dimension {
@@ -554,6 +603,104 @@ fn handle_dst_texture_init<A: HalApi>(
     Ok(())
 }
 
+/// Check that `source` and `destination` may be used together in a buffer
+/// copy on `device`.
+fn validate_buffer_copy_usages<A: HalApi>(
+    device: &Device<A>,
+    src_buffer: &Buffer<A>,
+    dst_buffer: &Buffer<A>,
+) -> Result<(), TransferError> {
+    if !device
+        .downlevel
+        .flags
+        .contains(wgt::DownlevelFlags::UNRESTRICTED_INDEX_BUFFER)
+        && (src_buffer.usage.contains(wgt::BufferUsages::INDEX)
+            || dst_buffer.usage.contains(wgt::BufferUsages::INDEX))
+    {
+        let forbidden_usages = wgt::BufferUsages::VERTEX
+            | wgt::BufferUsages::UNIFORM
+            | wgt::BufferUsages::INDIRECT
+            | wgt::BufferUsages::STORAGE;
+        if src_buffer.usage.intersects(forbidden_usages)
+            || dst_buffer.usage.intersects(forbidden_usages)
+        {
+            return Err(TransferError::MissingDownlevelFlags(MissingDownlevelFlags(
+                wgt::DownlevelFlags::UNRESTRICTED_INDEX_BUFFER,
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check the alignment and bounds of a single buffer-to-buffer copy region.
+fn validate_buffer_copy_region(
+    source_offset: BufferAddress,
+    destination_offset: BufferAddress,
+    size: BufferAddress,
+    source_size: BufferAddress,
+    destination_size: BufferAddress,
+) -> Result<(), TransferError> {
+    if size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
+        return Err(TransferError::UnalignedCopySize(size));
+    }
+    if source_offset % wgt::COPY_BUFFER_ALIGNMENT != 0 {
+        return Err(TransferError::UnalignedBufferOffset(source_offset));
+    }
+    if destination_offset % wgt::COPY_BUFFER_ALIGNMENT != 0 {
+        return Err(TransferError::UnalignedBufferOffset(destination_offset));
+    }
+
+    let source_end_offset = source_offset + size;
+    let destination_end_offset = destination_offset + size;
+    if source_end_offset > source_size {
+        return Err(TransferError::BufferOverrun {
+            start_offset: source_offset,
+            end_offset: source_end_offset,
+            buffer_size: source_size,
+            side: CopySide::Source,
+        });
+    }
+    if destination_end_offset > destination_size {
+        return Err(TransferError::BufferOverrun {
+            start_offset: destination_offset,
+            end_offset: destination_end_offset,
+            buffer_size: destination_size,
+            side: CopySide::Destination,
+        });
+    }
+    Ok(())
+}
+
+/// Find a pair of regions in `regions` whose destination ranges overlap.
+///
+/// Each region is a `(source_offset, destination_offset, size)` triple.
+/// Returns the indices of the two regions, in ascending order.
+fn find_overlapping_destinations(
+    regions: &[(BufferAddress, BufferAddress, BufferAddress)],
+) -> Option<(usize, usize)> {
+    let mut order = (0..regions.len())
+        .filter(|&index| regions[index].2 != 0)
+        .collect::<Vec<_>>();
+    order.sort_by_key(|&index| regions[index].1);
+
+    order.windows(2).find_map(|pair| {
+        let (_, first_offset, first_size) = regions[pair[0]];
+        let (_, second_offset, _) = regions[pair[1]];
+        (first_offset + first_size > second_offset)
+            .then(|| (pair[0].min(pair[1]), pair[0].max(pair[1])))
+    })
+}
+
+/// Round `layout.bytes_per_row` up to `COPY_BYTES_PER_ROW_ALIGNMENT`.
+fn pad_bytes_per_row(layout: wgt::ImageDataLayout) -> wgt::ImageDataLayout {
+    wgt::ImageDataLayout {
+        bytes_per_row: layout.bytes_per_row.map(|bytes_per_row| {
+            wgt::math::align_to(bytes_per_row, wgt::COPY_BYTES_PER_ROW_ALIGNMENT)
+        }),
+        ..layout
+    }
+}
+
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
     pub fn command_encoder_copy_buffer_to_buffer<A: HalApi>(
         &self,
@@ -637,56 +784,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
         let dst_barrier = dst_pending.map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
 
-        if size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
-            return Err(TransferError::UnalignedCopySize(size).into());
-        }
-        if source_offset % wgt::COPY_BUFFER_ALIGNMENT != 0 {
-            return Err(TransferError::UnalignedBufferOffset(source_offset).into());
-        }
-        if destination_offset % wgt::COPY_BUFFER_ALIGNMENT != 0 {
-            return Err(TransferError::UnalignedBufferOffset(destination_offset).into());
-        }
-        if !device
-            .downlevel
-            .flags
-            .contains(wgt::DownlevelFlags::UNRESTRICTED_INDEX_BUFFER)
-            && (src_buffer.usage.contains(wgt::BufferUsages::INDEX)
-                || dst_buffer.usage.contains(wgt::BufferUsages::INDEX))
-        {
-            let forbidden_usages = wgt::BufferUsages::VERTEX
-                | wgt::BufferUsages::UNIFORM
-                | wgt::BufferUsages::INDIRECT
-                | wgt::BufferUsages::STORAGE;
-            if src_buffer.usage.intersects(forbidden_usages)
-                || dst_buffer.usage.intersects(forbidden_usages)
-            {
-                return Err(TransferError::MissingDownlevelFlags(MissingDownlevelFlags(
-                    wgt::DownlevelFlags::UNRESTRICTED_INDEX_BUFFER,
-                ))
-                .into());
-            }
-        }
-
-        let source_end_offset = source_offset + size;
-        let destination_end_offset = destination_offset + size;
-        if source_end_offset > src_buffer.size {
-            return Err(TransferError::BufferOverrun {
-                start_offset: source_offset,
-                end_offset: source_end_offset,
-                buffer_size: src_buffer.size,
-                side: CopySide::Source,
-            }
-            .into());
-        }
-        if destination_end_offset > dst_buffer.size {
-            return Err(TransferError::BufferOverrun {
-                start_offset: destination_offset,
-                end_offset: destination_end_offset,
-                buffer_size: dst_buffer.size,
-                side: CopySide::Destination,
-            }
-            .into());
-        }
+        validate_buffer_copy_usages(device, &src_buffer, &dst_buffer)?;
+        validate_buffer_copy_region(
+            source_offset,
+            destination_offset,
+            size,
+            src_buffer.size,
+            dst_buffer.size,
+        )?;
 
         if size == 0 {
             log::trace!("Ignoring copy_buffer_to_buffer of size 0");
@@ -722,6 +827,152 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 
+    /// Record several copies from `source` to `destination` at once.
+    ///
+    /// Each region is a `(source_offset, destination_offset, size)` triple.
+    /// All regions are validated before anything is recorded, so if any of
+    /// them is invalid, or if any two destination ranges overlap, none of the
+    /// copies are recorded.
+    pub fn command_encoder_copy_buffer_to_buffer_multiple<A: HalApi>(
+        &self,
+        command_encoder_id: CommandEncoderId,
+        source: BufferId,
+        destination: BufferId,
+        regions: &[(BufferAddress, BufferAddress, BufferAddress)],
+    ) -> Result<(), CopyError> {
+        profiling::scope!("CommandEncoder::copy_buffer_to_buffer_multiple");
+        api_log!(
+            "CommandEncoder::copy_buffer_to_buffer_multiple {source:?} -> {destination:?} {} regions",
+            regions.len()
+        );
+
+        if source == destination {
+            return Err(TransferError::SameSourceDestinationBuffer.into());
+        }
+        let hub = A::hub(self);
+
+        let cmd_buf = CommandBuffer::get_encoder(hub, command_encoder_id)?;
+        let mut cmd_buf_data = cmd_buf.data.lock();
+        let cmd_buf_data = cmd_buf_data.as_mut().unwrap();
+
+        let device = &cmd_buf.device;
+        if !device.is_valid() {
+            return Err(TransferError::InvalidDevice(cmd_buf.device.as_info().id()).into());
+        }
+
+        let (src_buffer, dst_buffer) = {
+            let buffer_guard = hub.buffers.read();
+            let src_buffer = buffer_guard
+                .get_owned(source)
+                .map_err(|_| TransferError::InvalidBuffer(source))?;
+            let dst_buffer = buffer_guard
+                .get_owned(destination)
+                .map_err(|_| TransferError::InvalidBuffer(destination))?;
+            (src_buffer, dst_buffer)
+        };
+
+        if !src_buffer.usage.contains(BufferUsages::COPY_SRC) {
+            return Err(TransferError::MissingCopySrcUsageFlag.into());
+        }
+        if !dst_buffer.usage.contains(BufferUsages::COPY_DST) {
+            return Err(TransferError::MissingCopyDstUsageFlag(Some(destination), None).into());
+        }
+        validate_buffer_copy_usages(device, &src_buffer, &dst_buffer)?;
+        for &(source_offset, destination_offset, size) in regions {
+            validate_buffer_copy_region(
+                source_offset,
+                destination_offset,
+                size,
+                src_buffer.size,
+                dst_buffer.size,
+            )?;
+        }
+        if let Some((first, second)) = find_overlapping_destinations(regions) {
+            return Err(TransferError::OverlappingDestinationRegions { first, second }.into());
+        }
+
+        #[cfg(feature = "trace")]
+        if let Some(ref mut list) = cmd_buf_data.commands {
+            for &(source_offset, destination_offset, size) in regions {
+                list.push(TraceCommand::CopyBufferToBuffer {
+                    src: source,
+                    src_offset: source_offset,
+                    dst: destination,
+                    dst_offset: destination_offset,
+                    size,
+                });
+            }
+        }
+
+        let snatch_guard = device.snatchable_lock.read();
+
+        let src_raw = src_buffer
+            .raw
+            .get(&snatch_guard)
+            .ok_or(TransferError::InvalidBuffer(source))?;
+        let dst_raw = dst_buffer
+            .raw
+            .get(&snatch_guard)
+            .ok_or(TransferError::InvalidBuffer(destination))?;
+
+        let src_pending = cmd_buf_data
+            .trackers
+            .buffers
+            .set_single(&src_buffer, hal::BufferUses::COPY_SRC)
+            .ok_or(TransferError::InvalidBuffer(source))?
+            .1;
+        let src_barrier = src_pending.map(|pending| pending.into_hal(&src_buffer, &snatch_guard));
+        let dst_pending = cmd_buf_data
+            .trackers
+            .buffers
+            .set_single(&dst_buffer, hal::BufferUses::COPY_DST)
+            .ok_or(TransferError::InvalidBuffer(destination))?
+            .1;
+        let dst_barrier = dst_pending.map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
+
+        let mut hal_regions = Vec::with_capacity(regions.len());
+        for &(source_offset, destination_offset, size) in regions {
+            let size = match wgt::BufferSize::new(size) {
+                Some(size) => size,
+                None => continue,
+            };
+
+            // Make sure source is initialized memory and mark dest as initialized.
+            cmd_buf_data.buffer_memory_init_actions.extend(
+                dst_buffer.initialization_status.read().create_action(
+                    &dst_buffer,
+                    destination_offset..(destination_offset + size.get()),
+                    MemoryInitKind::ImplicitlyInitialized,
+                ),
+            );
+            cmd_buf_data.buffer_memory_init_actions.extend(
+                src_buffer.initialization_status.read().create_action(
+                    &src_buffer,
+                    source_offset..(source_offset + size.get()),
+                    MemoryInitKind::NeedsInitializedMemory,
+                ),
+            );
+
+            hal_regions.push(hal::BufferCopy {
+                src_offset: source_offset,
+                dst_offset: destination_offset,
+                size,
+            });
+        }
+
+        if hal_regions.is_empty() {
+            log::trace!("Ignoring copy_buffer_to_buffer_multiple without non-empty regions");
+            return Ok(());
+        }
+
+        let cmd_buf_raw = cmd_buf_data.encoder.open()?;
+        unsafe {
+            cmd_buf_raw.transition_buffers(src_barrier.into_iter().chain(dst_barrier));
+            cmd_buf_raw.copy_buffer_to_buffer(src_raw, dst_raw, hal_regions.into_iter());
+        }
+        Ok(())
+    }
+
     pub fn command_encoder_copy_buffer_to_texture<A: HalApi>(
         &self,
         command_encoder_id: CommandEncoderId,
@@ -761,11 +1012,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let buffer_memory_init_actions = &mut cmd_buf_data.buffer_memory_init_actions;
         let texture_memory_actions = &mut cmd_buf_data.texture_memory_actions;
 
-        if copy_size.width == 0 || copy_size.height == 0 || copy_size.depth_or_array_layers == 0 {
-            log::trace!("Ignoring copy_buffer_to_texture of size 0");
-            return Ok(());
-        }
-
         let dst_texture = hub
             .textures
             .get(destination.texture)
@@ -780,53 +1026,18 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (dst_range, dst_base) = extract_texture_selector(destination, copy_size, &dst_texture)?;
 
-        // Handle texture init *before* dealing with barrier transitions so we
-        // have an easier time inserting "immediate-inits" that may be required
-        // by prior discards in rare cases.
-        handle_dst_texture_init(
-            encoder,
-            tracker,
-            texture_memory_actions,
-            device,
-            destination,
-            copy_size,
-            &dst_texture,
-        )?;
-
-        let snatch_guard = device.snatchable_lock.read();
-
-        let (src_buffer, src_pending) = {
-            let buffer_guard = hub.buffers.read();
-            let src_buffer = buffer_guard
-                .get(source.buffer)
-                .map_err(|_| TransferError::InvalidBuffer(source.buffer))?;
-            tracker
-                .buffers
-                .set_single(src_buffer, hal::BufferUses::COPY_SRC)
-                .ok_or(TransferError::InvalidBuffer(source.buffer))?
-        };
-        let src_raw = src_buffer
-            .raw
-            .get(&snatch_guard)
-            .ok_or(TransferError::InvalidBuffer(source.buffer))?;
+        let src_buffer = hub
+            .buffers
+            .get(source.buffer)
+            .map_err(|_| TransferError::InvalidBuffer(source.buffer))?;
         if !src_buffer.usage.contains(BufferUsages::COPY_SRC) {
             return Err(TransferError::MissingCopySrcUsageFlag.into());
         }
-        let src_barrier = src_pending.map(|pending| pending.into_hal(&src_buffer, &snatch_guard));
-
-        let dst_pending = tracker
-            .textures
-            .set_single(&dst_texture, dst_range, hal::TextureUses::COPY_DST)
-            .ok_or(TransferError::InvalidTexture(destination.texture))?;
-        let dst_raw = dst_texture
-            .raw(&snatch_guard)
-            .ok_or(TransferError::InvalidTexture(destination.texture))?;
         if !dst_texture.desc.usage.contains(TextureUsages::COPY_DST) {
             return Err(
                 TransferError::MissingCopyDstUsageFlag(None, Some(destination.texture)).into(),
             );
         }
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(dst_raw));
 
         if !dst_base.aspect.is_one() {
             return Err(TransferError::CopyAspectNotOne.into());
@@ -856,6 +1067,46 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .map_err(TransferError::from)?;
         }
 
+        // A copy with an empty extent is a no-op, but it must still be valid.
+        if copy_size.width == 0 || copy_size.height == 0 || copy_size.depth_or_array_layers == 0 {
+            log::trace!("Ignoring copy_buffer_to_texture of size 0");
+            return Ok(());
+        }
+
+        // Handle texture init *before* dealing with barrier transitions so we
+        // have an easier time inserting "immediate-inits" that may be required
+        // by prior discards in rare cases.
+        handle_dst_texture_init(
+            encoder,
+            tracker,
+            texture_memory_actions,
+            device,
+            destination,
+            copy_size,
+            &dst_texture,
+        )?;
+
+        let snatch_guard = device.snatchable_lock.read();
+
+        let (_, src_pending) = tracker
+            .buffers
+            .set_single(&src_buffer, hal::BufferUses::COPY_SRC)
+            .ok_or(TransferError::InvalidBuffer(source.buffer))?;
+        let src_raw = src_buffer
+            .raw
+            .get(&snatch_guard)
+            .ok_or(TransferError::InvalidBuffer(source.buffer))?;
+        let src_barrier = src_pending.map(|pending| pending.into_hal(&src_buffer, &snatch_guard));
+
+        let dst_pending = tracker
+            .textures
+            .set_single(&dst_texture, dst_range, hal::TextureUses::COPY_DST)
+            .ok_or(TransferError::InvalidTexture(destination.texture))?;
+        let dst_raw = dst_texture
+            .raw(&snatch_guard)
+            .ok_or(TransferError::InvalidTexture(destination.texture))?;
+        let dst_barrier = dst_pending.map(|pending| pending.into_hal(dst_raw));
+
         buffer_memory_init_actions.extend(src_buffer.initialization_status.read().create_action(
             &src_buffer,
             source.layout.offset..(source.layout.offset + required_buffer_bytes_in_copy),
@@ -921,11 +1172,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let buffer_memory_init_actions = &mut cmd_buf_data.buffer_memory_init_actions;
         let texture_memory_actions = &mut cmd_buf_data.texture_memory_actions;
 
-        if copy_size.width == 0 || copy_size.height == 0 || copy_size.depth_or_array_layers == 0 {
-            log::trace!("Ignoring copy_texture_to_buffer of size 0");
-            return Ok(());
-        }
-
         let src_texture = hub
             .textures
             .get(source.texture)
@@ -936,28 +1182,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (src_range, src_base) = extract_texture_selector(source, copy_size, &src_texture)?;
 
-        // Handle texture init *before* dealing with barrier transitions so we
-        // have an easier time inserting "immediate-inits" that may be required
-        // by prior discards in rare cases.
-        handle_src_texture_init(
-            encoder,
-            tracker,
-            texture_memory_actions,
-            device,
-            source,
-            copy_size,
-            &src_texture,
-        )?;
-
-        let snatch_guard = device.snatchable_lock.read();
-
-        let src_pending = tracker
-            .textures
-            .set_single(&src_texture, src_range, hal::TextureUses::COPY_SRC)
-            .ok_or(TransferError::InvalidTexture(source.texture))?;
-        let src_raw = src_texture
-            .raw(&snatch_guard)
-            .ok_or(TransferError::InvalidTexture(source.texture))?;
         if !src_texture.desc.usage.contains(TextureUsages::COPY_SRC) {
             return Err(TransferError::MissingCopySrcUsageFlag.into());
         }
@@ -974,28 +1198,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
             .into());
         }
-        let src_barrier = src_pending.map(|pending| pending.into_hal(src_raw));
 
-        let (dst_buffer, dst_pending) = {
-            let buffer_guard = hub.buffers.read();
-            let dst_buffer = buffer_guard
-                .get(destination.buffer)
-                .map_err(|_| TransferError::InvalidBuffer(destination.buffer))?;
-            tracker
-                .buffers
-                .set_single(dst_buffer, hal::BufferUses::COPY_DST)
-                .ok_or(TransferError::InvalidBuffer(destination.buffer))?
-        };
-        let dst_raw = dst_buffer
-            .raw
-            .get(&snatch_guard)
-            .ok_or(TransferError::InvalidBuffer(destination.buffer))?;
+        let dst_buffer = hub
+            .buffers
+            .get(destination.buffer)
+            .map_err(|_| TransferError::InvalidBuffer(destination.buffer))?;
         if !dst_buffer.usage.contains(BufferUsages::COPY_DST) {
             return Err(
                 TransferError::MissingCopyDstUsageFlag(Some(destination.buffer), None).into(),
             );
         }
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
 
         if !src_base.aspect.is_one() {
             return Err(TransferError::CopyAspectNotOne.into());
@@ -1025,6 +1237,46 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .map_err(TransferError::from)?;
         }
 
+        // A copy with an empty extent is a no-op, but it must still be valid.
+        if copy_size.width == 0 || copy_size.height == 0 || copy_size.depth_or_array_layers == 0 {
+            log::trace!("Ignoring copy_texture_to_buffer of size 0");
+            return Ok(());
+        }
+
+        // Handle texture init *before* dealing with barrier transitions so we
+        // have an easier time inserting "immediate-inits" that may be required
+        // by prior discards in rare cases.
+        handle_src_texture_init(
+            encoder,
+            tracker,
+            texture_memory_actions,
+            device,
+            source,
+            copy_size,
+            &src_texture,
+        )?;
+
+        let snatch_guard = device.snatchable_lock.read();
+
+        let src_pending = tracker
+            .textures
+            .set_single(&src_texture, src_range, hal::TextureUses::COPY_SRC)
+            .ok_or(TransferError::InvalidTexture(source.texture))?;
+        let src_raw = src_texture
+            .raw(&snatch_guard)
+            .ok_or(TransferError::InvalidTexture(source.texture))?;
+        let src_barrier = src_pending.map(|pending| pending.into_hal(src_raw));
+
+        let (_, dst_pending) = tracker
+            .buffers
+            .set_single(&dst_buffer, hal::BufferUses::COPY_DST)
+            .ok_or(TransferError::InvalidBuffer(destination.buffer))?;
+        let dst_raw = dst_buffer
+            .raw
+            .get(&snatch_guard)
+            .ok_or(TransferError::InvalidBuffer(destination.buffer))?;
+        let dst_barrier = dst_pending.map(|pending| pending.into_hal(&dst_buffer, &snatch_guard));
+
         buffer_memory_init_actions.extend(dst_buffer.initialization_status.read().create_action(
             &dst_buffer,
             destination.layout.offset..(destination.layout.offset + required_buffer_bytes_in_copy),
@@ -1056,6 +1308,34 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 
+    /// Like [`Global::command_encoder_copy_texture_to_buffer`], but
+    /// `destination.layout.bytes_per_row` doesn't need to be a multiple of
+    /// `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    ///
+    /// The stride is rounded up to the alignment, and the layout actually used
+    /// for the copy is returned, so the caller can strip the padding after
+    /// reading the buffer back. The destination buffer must be large enough
+    /// for the padded layout.
+    pub fn command_encoder_copy_texture_to_buffer_padded<A: HalApi>(
+        &self,
+        command_encoder_id: CommandEncoderId,
+        source: &ImageCopyTexture,
+        destination: &ImageCopyBuffer,
+        copy_size: &Extent3d,
+    ) -> Result<wgt::ImageDataLayout, CopyError> {
+        let destination = ImageCopyBuffer {
+            buffer: destination.buffer,
+            layout: pad_bytes_per_row(destination.layout),
+        };
+        self.command_encoder_copy_texture_to_buffer::<A>(
+            command_encoder_id,
+            source,
+            &destination,
+            copy_size,
+        )?;
+        Ok(destination.layout)
+    }
+
     pub fn command_encoder_copy_texture_to_texture<A: HalApi>(
         &self,
         command_encoder_id: CommandEncoderId,
@@ -1095,11 +1375,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let tracker = &mut cmd_buf_data.trackers;
         let texture_memory_actions = &mut cmd_buf_data.texture_memory_actions;
 
-        if copy_size.width == 0 || copy_size.height == 0 || copy_size.depth_or_array_layers == 0 {
-            log::trace!("Ignoring copy_texture_to_texture of size 0");
-            return Ok(());
-        }
-
         let src_texture = hub
             .textures
             .get(source.texture)
@@ -1133,6 +1408,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (src_range, src_tex_base) = extract_texture_selector(source, copy_size, &src_texture)?;
         let (dst_range, dst_tex_base) =
             extract_texture_selector(destination, copy_size, &dst_texture)?;
+        if source.texture == destination.texture
+            && texture_selectors_overlap(&src_range, &dst_range)
+        {
+            return Err(TransferError::OverlappingSubresource(source.texture).into());
+        }
         let src_texture_aspects = hal::FormatAspects::from(src_texture.desc.format);
         let dst_texture_aspects = hal::FormatAspects::from(dst_texture.desc.format);
         if src_tex_base.aspect != src_texture_aspects {
@@ -1141,6 +1421,20 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if dst_tex_base.aspect != dst_texture_aspects {
             return Err(TransferError::CopyDstMissingAspects.into());
         }
+        if !src_texture.desc.usage.contains(TextureUsages::COPY_SRC) {
+            return Err(TransferError::MissingCopySrcUsageFlag.into());
+        }
+        if !dst_texture.desc.usage.contains(TextureUsages::COPY_DST) {
+            return Err(
+                TransferError::MissingCopyDstUsageFlag(None, Some(destination.texture)).into(),
+            );
+        }
+
+        // A copy with an empty extent is a no-op, but it must still be valid.
+        if copy_size.width == 0 || copy_size.height == 0 || copy_size.depth_or_array_layers == 0 {
+            log::trace!("Ignoring copy_texture_to_texture of size 0");
+            return Ok(());
+        }
 
         // Handle texture init *before* dealing with barrier transitions so we
         // have an easier time inserting "immediate-inits" that may be required
@@ -1172,9 +1466,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let src_raw = src_texture
             .raw(&snatch_guard)
             .ok_or(TransferError::InvalidTexture(source.texture))?;
-        if !src_texture.desc.usage.contains(TextureUsages::COPY_SRC) {
-            return Err(TransferError::MissingCopySrcUsageFlag.into());
-        }
 
         //TODO: try to avoid this the collection. It's needed because both
         // `src_pending` and `dst_pending` try to hold `trackers.textures` mutably.
@@ -1190,11 +1481,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let dst_raw = dst_texture
             .raw(&snatch_guard)
             .ok_or(TransferError::InvalidTexture(destination.texture))?;
-        if !dst_texture.desc.usage.contains(TextureUsages::COPY_DST) {
-            return Err(
-                TransferError::MissingCopyDstUsageFlag(None, Some(destination.texture)).into(),
-            );
-        }
 
         barriers.extend(dst_pending.map(|pending| pending.into_hal(dst_raw)));
 
@@ -1228,3 +1514,86 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        find_overlapping_destinations, pad_bytes_per_row, texture_selectors_overlap,
+        validate_buffer_copy_region, TransferError,
+    };
+    use crate::track::TextureSelector;
+
+    #[test]
+    fn overlapping_destination_regions() {
+        let regions = [(0, 0, 16), (16, 64, 16), (32, 8, 16)];
+        assert_eq!(find_overlapping_destinations(&regions), Some((0, 2)));
+
+        // Touching ranges and empty regions do not overlap.
+        let regions = [(0, 16, 16), (16, 0, 16), (32, 4, 0)];
+        assert_eq!(find_overlapping_destinations(&regions), None);
+    }
+
+    #[test]
+    fn clean_batch() {
+        let regions = [(0, 0, 4), (4, 8, 8), (16, 32, 16), (0, 64, 0)];
+        assert_eq!(find_overlapping_destinations(&regions), None);
+        for &(source_offset, destination_offset, size) in &regions {
+            validate_buffer_copy_region(source_offset, destination_offset, size, 64, 64).unwrap();
+        }
+
+        assert!(matches!(
+            validate_buffer_copy_region(0, 60, 8, 64, 64),
+            Err(TransferError::BufferOverrun { .. })
+        ));
+        assert!(matches!(
+            validate_buffer_copy_region(2, 0, 4, 64, 64),
+            Err(TransferError::UnalignedBufferOffset(2))
+        ));
+    }
+
+    #[test]
+    fn same_texture_subresources() {
+        let selector = |mips: std::ops::Range<u32>, layers: std::ops::Range<u32>| TextureSelector {
+            mips,
+            layers,
+        };
+
+        // Same mip level, overlapping array layers.
+        assert!(texture_selectors_overlap(
+            &selector(0..1, 0..2),
+            &selector(0..1, 1..3)
+        ));
+        // Same mip level, disjoint array layers.
+        assert!(!texture_selectors_overlap(
+            &selector(0..1, 0..2),
+            &selector(0..1, 2..4)
+        ));
+        // Different mip levels.
+        assert!(!texture_selectors_overlap(
+            &selector(0..1, 0..1),
+            &selector(1..2, 0..1)
+        ));
+    }
+
+    #[test]
+    fn padded_bytes_per_row() {
+        // A 3x3 Rgba8 texture has 12 bytes per row.
+        let layout = wgt::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(3 * 4),
+            rows_per_image: Some(3),
+        };
+        let padded = pad_bytes_per_row(layout);
+        assert_eq!(padded.bytes_per_row, Some(256));
+        assert_eq!(padded.rows_per_image, Some(3));
+
+        // Aligned and missing strides are left alone.
+        for bytes_per_row in [Some(512), None] {
+            let layout = wgt::ImageDataLayout {
+                bytes_per_row,
+                ..layout
+            };
+            assert_eq!(pad_bytes_per_row(layout).bytes_per_row, bytes_per_row);
+        }
+    }
+}
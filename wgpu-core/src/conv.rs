@@ -240,11 +240,19 @@ pub fn check_texture_dimension_size(
     Ok(())
 }
 
-pub fn bind_group_layout_flags(features: wgt::Features) -> hal::BindGroupLayoutFlags {
+pub fn bind_group_layout_flags(
+    features: wgt::Features,
+    layout_flags: wgt::BindGroupLayoutFlags,
+) -> hal::BindGroupLayoutFlags {
     let mut flags = hal::BindGroupLayoutFlags::empty();
     flags.set(
         hal::BindGroupLayoutFlags::PARTIALLY_BOUND,
-        features.contains(wgt::Features::PARTIALLY_BOUND_BINDING_ARRAY),
+        features.contains(wgt::Features::PARTIALLY_BOUND_BINDING_ARRAY)
+            || layout_flags.contains(wgt::BindGroupLayoutFlags::PARTIALLY_BOUND),
+    );
+    flags.set(
+        hal::BindGroupLayoutFlags::UPDATE_AFTER_BIND,
+        layout_flags.contains(wgt::BindGroupLayoutFlags::UPDATE_AFTER_BIND),
     );
     flags
 }
@@ -14,7 +14,8 @@ pub enum Origin {
     Derived,
 }
 
-/// A HashMap-like structure that stores a BindGroupLayouts [`wgt::BindGroupLayoutEntry`]s.
+/// A HashMap-like structure that stores a BindGroupLayouts [`wgt::BindGroupLayoutEntry`]s,
+/// along with its [`wgt::BindGroupLayoutFlags`].
 ///
 /// It is hashable, so bind group layouts can be deduplicated.
 #[derive(Debug, Default, Clone, Eq)]
@@ -28,6 +29,9 @@ pub struct EntryMap {
     /// We only need sorted if it is used in a Hash or PartialEq, so we never need
     /// to actively sort it.
     sorted: bool,
+    /// Layouts only differing in their flags aren't interchangeable, so these
+    /// take part in PartialEq and Hash too.
+    flags: wgt::BindGroupLayoutFlags,
 }
 
 impl PartialEq for EntryMap {
@@ -35,7 +39,7 @@ impl PartialEq for EntryMap {
         self.assert_sorted();
         other.assert_sorted();
 
-        self.inner == other.inner
+        self.inner == other.inner && self.flags == other.flags
     }
 }
 
@@ -50,6 +54,7 @@ impl Hash for EntryMap {
         for entry in self.inner.values() {
             entry.hash(state);
         }
+        self.flags.hash(state);
     }
 }
 
@@ -58,13 +63,15 @@ impl EntryMap {
         assert!(self.sorted);
     }
 
-    /// Create a new [`BindGroupLayoutEntryMap`] from a slice of [`wgt::BindGroupLayoutEntry`]s.
+    /// Create a new [`BindGroupLayoutEntryMap`] from a slice of [`wgt::BindGroupLayoutEntry`]s
+    /// and the layout's flags.
     ///
     /// Errors if there are duplicate bindings or if any binding index is greater than
     /// the device's limits.
     pub fn from_entries(
         device_limits: &wgt::Limits,
         entries: &[wgt::BindGroupLayoutEntry],
+        flags: wgt::BindGroupLayoutFlags,
     ) -> Result<Self, binding_model::CreateBindGroupLayoutError> {
         let mut inner = FastIndexMap::with_capacity_and_hasher(entries.len(), Default::default());
         for entry in entries {
@@ -87,9 +94,15 @@ impl EntryMap {
         Ok(Self {
             inner,
             sorted: true,
+            flags,
         })
     }
 
+    /// Get the flags of the layout.
+    pub fn flags(&self) -> wgt::BindGroupLayoutFlags {
+        self.flags
+    }
+
     /// Get the count of [`wgt::BindGroupLayoutEntry`]s in this map.
     pub fn len(&self) -> usize {
         self.inner.len()
@@ -127,3 +140,34 @@ impl EntryMap {
         self.inner.entry(key)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::EntryMap;
+
+    #[test]
+    fn flags_distinguish_layouts() {
+        let limits = wgt::Limits::default();
+        let entries = [wgt::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgt::ShaderStages::FRAGMENT,
+            ty: wgt::BindingType::Sampler(wgt::SamplerBindingType::Filtering),
+            count: None,
+        }];
+
+        let plain =
+            EntryMap::from_entries(&limits, &entries, wgt::BindGroupLayoutFlags::empty()).unwrap();
+        let update_after_bind = EntryMap::from_entries(
+            &limits,
+            &entries,
+            wgt::BindGroupLayoutFlags::UPDATE_AFTER_BIND,
+        )
+        .unwrap();
+
+        assert_eq!(
+            plain,
+            EntryMap::from_entries(&limits, &entries, wgt::BindGroupLayoutFlags::empty()).unwrap()
+        );
+        assert_ne!(plain, update_after_bind);
+    }
+}
@@ -1,10 +1,12 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::device::poll_thread::PollThread;
 #[cfg(feature = "trace")]
 use crate::device::trace;
 use crate::{
     api_log, binding_model, command, conv,
     device::{
         bgl, life::WaitIdleError, map_buffer, queue, DeviceError, DeviceLostClosure,
-        DeviceLostReason, HostMap, IMPLICIT_BIND_GROUP_LAYOUT_ERROR_LABEL,
+        DeviceLostReason, DeviceLostReasonFilter, HostMap, IMPLICIT_BIND_GROUP_LAYOUT_ERROR_LABEL,
     },
     global::Global,
     hal_api::HalApi,
@@ -13,6 +15,7 @@ use crate::{
     init_tracker::TextureInitTracker,
     instance::{self, Adapter, Surface},
     pipeline, present,
+    registry::ResourceLogEvent,
     resource::{self, BufferAccessResult},
     resource::{BufferAccessError, BufferMapOperation, CreateBufferError, Resource},
     validation::check_buffer_usage,
@@ -35,6 +38,16 @@ use std::{
 
 use super::{ImplicitPipelineIds, InvalidDevice, UserClosures};
 
+/// What [`Global::buffer_drop_impl`] found out about a buffer it dropped.
+struct DroppedBuffer<A: HalApi> {
+    device: Arc<super::Device<A>>,
+    last_submit_index: crate::SubmissionIndex,
+    /// The buffer has writes which haven't been submitted yet.
+    pending_write: bool,
+    /// `wait` was set, and waiting for the last submission succeeded.
+    waited: bool,
+}
+
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
     pub fn adapter_is_surface_supported<A: HalApi>(
         &self,
@@ -61,17 +74,19 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> Result<wgt::SurfaceCapabilities, instance::GetSurfaceSupportError> {
         profiling::scope!("Surface::get_capabilities");
         self.fetch_adapter_and_surface::<A, _, _>(surface_id, adapter_id, |adapter, surface| {
-            let mut hal_caps = surface.get_capabilities(adapter)?;
+            surface.capabilities.get_or_query(adapter_id, || {
+                let mut hal_caps = surface.get_capabilities(adapter)?;
 
-            hal_caps.formats.sort_by_key(|f| !f.is_srgb());
+                hal_caps.formats.sort_by_key(|f| !f.is_srgb());
 
-            let usages = conv::map_texture_usage_from_hal(hal_caps.usage);
+                let usages = conv::map_texture_usage_from_hal(hal_caps.usage);
 
-            Ok(wgt::SurfaceCapabilities {
-                formats: hal_caps.formats,
-                present_modes: hal_caps.present_modes,
-                alpha_modes: hal_caps.composite_alpha_modes,
-                usages,
+                Ok(wgt::SurfaceCapabilities {
+                    formats: hal_caps.formats,
+                    present_modes: hal_caps.present_modes,
+                    alpha_modes: hal_caps.composite_alpha_modes,
+                    usages,
+                })
             })
         })
     }
@@ -258,6 +273,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
             let (id, resource) = fid.assign(buffer);
             api_log!("Device::create_buffer({desc:?}) -> {id:?}");
+            resource.log_event(
+                &device,
+                ResourceLogEvent::Created,
+                format_args!("Created Buffer {:?}", id),
+            );
 
             device
                 .trackers
@@ -337,6 +357,44 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         fid.assign_error(label.borrow_or_default());
     }
 
+    /// Assign `id_in` an error with the given `label`.
+    ///
+    /// See `create_buffer_error` for more context and explaination.
+    pub fn create_sampler_error<A: HalApi>(&self, id_in: Input<G, id::SamplerId>, label: Label) {
+        let hub = A::hub(self);
+        let fid = hub.samplers.prepare::<G>(id_in);
+
+        fid.assign_error(label.borrow_or_default());
+    }
+
+    /// Assign `id_in` an error with the given `label`.
+    ///
+    /// See `create_buffer_error` for more context and explaination.
+    pub fn create_bind_group_layout_error<A: HalApi>(
+        &self,
+        id_in: Input<G, id::BindGroupLayoutId>,
+        label: Label,
+    ) {
+        let hub = A::hub(self);
+        let fid = hub.bind_group_layouts.prepare::<G>(id_in);
+
+        fid.assign_error(label.borrow_or_default());
+    }
+
+    /// Assign `id_in` an error with the given `label`.
+    ///
+    /// See `create_buffer_error` for more context and explaination.
+    pub fn create_pipeline_layout_error<A: HalApi>(
+        &self,
+        id_in: Input<G, id::PipelineLayoutId>,
+        label: Label,
+    ) {
+        let hub = A::hub(self);
+        let fid = hub.pipeline_layouts.prepare::<G>(id_in);
+
+        fid.assign_error(label.borrow_or_default());
+    }
+
     #[cfg(feature = "replay")]
     pub fn device_wait_for_buffer<A: HalApi>(
         &self,
@@ -359,6 +417,82 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .wait_for_submit(last_submission)
     }
 
+    /// Block until the GPU is done with every submission that used
+    /// `buffer_id` so far, or until `timeout` passes.
+    ///
+    /// This only waits, it doesn't run any callbacks. Mapping callbacks of the
+    /// buffer still need the device to be polled. Waiting on an invalid
+    /// buffer returns immediately, and so does waiting on a buffer whose only
+    /// pending use is a queue write which hasn't been submitted yet.
+    pub fn buffer_wait<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<(), WaitIdleError> {
+        api_log!("Buffer::wait {buffer_id:?} {timeout:?}");
+
+        // How long to wait between checks of the fence. Sub-millisecond
+        // remainders of `timeout` are rounded up to this, rather than
+        // checking again right away.
+        const WAIT_SLICE: std::time::Duration = std::time::Duration::from_millis(1);
+
+        let hub = A::hub(self);
+        let buffer = match hub.buffers.get(buffer_id) {
+            Ok(buffer) => buffer,
+            Err(_) => return Ok(()),
+        };
+        let device = &buffer.device;
+        if !device.is_valid() {
+            return Err(DeviceError::Lost.into());
+        }
+
+        let submission_index = buffer.info.submission_index();
+        let deadline = timeout.map(|timeout| std::time::Instant::now() + timeout);
+        let raw = device.raw();
+
+        loop {
+            let done = {
+                let fence = device.fence.read();
+                let fence = fence.as_ref().unwrap();
+
+                // Submissions bump the index while holding the fence, so
+                // anything past it is still waiting in the pending writes
+                // and nothing would ever signal it.
+                if submission_index > device.active_submission_index.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+
+                let last_done_index =
+                    unsafe { raw.get_fence_value(fence) }.map_err(DeviceError::from)?;
+                if last_done_index >= submission_index {
+                    return Ok(());
+                }
+
+                // Holding the fence blocks submissions and maintenance, so
+                // it's only waited on where there are no other threads to
+                // block.
+                #[cfg(target_arch = "wasm32")]
+                {
+                    unsafe { raw.wait(fence, submission_index, WAIT_SLICE.as_millis() as u32) }
+                        .map_err(DeviceError::from)?
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    false
+                }
+            };
+
+            if done {
+                return Ok(());
+            }
+            if deadline.map_or(false, |deadline| std::time::Instant::now() >= deadline) {
+                return Err(WaitIdleError::Timeout);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            std::thread::sleep(WAIT_SLICE);
+        }
+    }
+
     #[doc(hidden)]
     pub fn device_set_buffer_sub_data<A: HalApi>(
         &self,
@@ -478,6 +612,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).buffers.label_for_resource(id)
     }
 
+    pub fn buffer_label_into<A: HalApi>(&self, id: id::BufferId, out: &mut String) {
+        A::hub(self).buffers.label_for_resource_into(id, out)
+    }
+
     pub fn buffer_destroy<A: HalApi>(
         &self,
         buffer_id: id::BufferId,
@@ -498,17 +636,61 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     }
 
     pub fn buffer_drop<A: HalApi>(&self, buffer_id: id::BufferId, wait: bool) {
+        self.buffer_drop_impl::<A>(buffer_id, wait);
+    }
+
+    /// Like [`Global::buffer_drop`], but reports whether the GPU was done
+    /// with the buffer, so that its memory is released on the next
+    /// maintenance without waiting.
+    ///
+    /// With `wait` set this blocks until the last submission using the
+    /// buffer is done. A buffer written by [`Global::queue_write_buffer`]
+    /// since the last submit is still [`resource::BufferDropResult::Deferred`]
+    /// though, since there is no submission to wait for yet. Nothing is
+    /// submitted on its behalf.
+    pub fn buffer_drop_tracked<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+        wait: bool,
+    ) -> resource::BufferDropResult {
+        let Some(dropped) = self.buffer_drop_impl::<A>(buffer_id, wait) else {
+            return resource::BufferDropResult::Invalid;
+        };
+
+        if dropped.pending_write {
+            return resource::BufferDropResult::Deferred;
+        }
+
+        let done = if wait {
+            dropped.waited
+        } else {
+            match dropped.device.is_submission_done(dropped.last_submit_index) {
+                Ok(done) => done,
+                Err(e) => {
+                    log::error!("Failed to query buffer {:?}: {}", buffer_id, e);
+                    false
+                }
+            }
+        };
+        if done {
+            resource::BufferDropResult::Freed
+        } else {
+            resource::BufferDropResult::Deferred
+        }
+    }
+
+    /// Drop `buffer_id`, returning `None` if it wasn't a valid buffer.
+    fn buffer_drop_impl<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+        wait: bool,
+    ) -> Option<DroppedBuffer<A>> {
         profiling::scope!("Buffer::drop");
         api_log!("Buffer::drop {buffer_id:?}");
 
         let hub = A::hub(self);
 
-        let buffer = match hub.buffers.unregister(buffer_id) {
-            Some(buffer) => buffer,
-            None => {
-                return;
-            }
-        };
+        let buffer = hub.buffers.unregister(buffer_id)?;
 
         let _ = buffer.unmap();
 
@@ -516,14 +698,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let device = buffer.device.clone();
 
-        if device
+        let pending_write = device
             .pending_writes
             .lock()
             .as_ref()
             .unwrap()
             .dst_buffers
-            .contains_key(&buffer_id)
-        {
+            .contains_key(&buffer_id);
+
+        if pending_write {
             device.lock_life().future_suspected_buffers.push(buffer);
         } else {
             device
@@ -533,12 +716,21 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .insert(buffer_id, buffer);
         }
 
-        if wait {
-            match device.wait_for_submit(last_submit_index) {
-                Ok(()) => (),
-                Err(e) => log::error!("Failed to wait for buffer {:?}: {}", buffer_id, e),
-            }
-        }
+        let waited = wait
+            && match device.wait_for_submit(last_submit_index) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::error!("Failed to wait for buffer {:?}: {}", buffer_id, e);
+                    false
+                }
+            };
+
+        Some(DroppedBuffer {
+            device,
+            last_submit_index,
+            pending_write,
+            waited,
+        })
     }
 
     pub fn device_create_texture<A: HalApi>(
@@ -589,6 +781,37 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         (id, Some(error))
     }
 
+    /// Estimate the number of bytes a texture described by `desc` would take
+    /// on `device_id`, including its whole mip chain.
+    ///
+    /// This lets callers compare against their memory budget before calling
+    /// [`Global::device_create_texture`]. The estimate doesn't include any
+    /// alignment or metadata added by the backend.
+    pub fn device_estimate_texture_allocation<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        desc: &resource::TextureDescriptor,
+    ) -> Result<u64, resource::CreateTextureError> {
+        let hub = A::hub(self);
+        let device = hub
+            .devices
+            .get(device_id)
+            .map_err(|_| DeviceError::Invalid)?;
+        if !device.is_valid() {
+            return Err(DeviceError::Lost.into());
+        }
+
+        let maximum = desc.size.max_mips(desc.dimension).min(hal::MAX_MIP_LEVELS);
+        if desc.mip_level_count == 0 || desc.mip_level_count > maximum {
+            return Err(resource::CreateTextureError::InvalidMipLevelCount {
+                requested: desc.mip_level_count,
+                maximum,
+            });
+        }
+
+        Ok(resource::estimate_texture_allocation(desc))
+    }
+
     /// # Safety
     ///
     /// - `hal_texture` must be created from `device_id` corresponding raw handle.
@@ -720,6 +943,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).textures.label_for_resource(id)
     }
 
+    pub fn texture_label_into<A: HalApi>(&self, id: id::TextureId, out: &mut String) {
+        A::hub(self).textures.label_for_resource_into(id, out)
+    }
+
     pub fn texture_destroy<A: HalApi>(
         &self,
         texture_id: id::TextureId,
@@ -828,10 +1055,56 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         (id, Some(error))
     }
 
+    /// Like [`Global::texture_create_view`], but on success also returns the
+    /// descriptor of the view with every field inferred from the texture
+    /// filled in.
+    ///
+    /// The returned descriptor always has a `format` and a `dimension`, and
+    /// its `range` has explicit mip level and array layer counts.
+    pub fn texture_create_view_reflect<A: HalApi>(
+        &self,
+        texture_id: id::TextureId,
+        desc: &resource::TextureViewDescriptor,
+        id_in: Input<G, id::TextureViewId>,
+    ) -> (
+        id::TextureViewId,
+        Result<resource::TextureViewDescriptor<'static>, resource::CreateTextureViewError>,
+    ) {
+        let (id, error) = self.texture_create_view::<A>(texture_id, desc, id_in);
+        if let Some(error) = error {
+            return (id, Err(error));
+        }
+
+        let view = match A::hub(self).texture_views.get(id) {
+            Ok(view) => view,
+            Err(_) => return (id, Err(resource::CreateTextureViewError::InvalidTexture)),
+        };
+        let resolved = resource::TextureViewDescriptor {
+            label: desc
+                .label
+                .as_ref()
+                .map(|label| Cow::Owned(label.to_string())),
+            format: Some(view.desc.format),
+            dimension: Some(view.desc.dimension),
+            range: view.desc.range,
+        };
+        (id, Ok(resolved))
+    }
+
     pub fn texture_view_label<A: HalApi>(&self, id: id::TextureViewId) -> String {
         A::hub(self).texture_views.label_for_resource(id)
     }
 
+    pub fn texture_view_label_into<A: HalApi>(&self, id: id::TextureViewId, out: &mut String) {
+        A::hub(self).texture_views.label_for_resource_into(id, out)
+    }
+
+    /// Drop the user's handle to `texture_view_id`.
+    ///
+    /// The view itself is only destroyed once no submission which uses it is
+    /// in flight. With `wait` set this blocks until the last such submission
+    /// is done, otherwise destruction is deferred to the device maintenance
+    /// which observes it completing.
     pub fn texture_view_drop<A: HalApi>(
         &self,
         texture_view_id: id::TextureViewId,
@@ -908,6 +1181,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).samplers.label_for_resource(id)
     }
 
+    pub fn sampler_label_into<A: HalApi>(&self, id: id::SamplerId, out: &mut String) {
+        A::hub(self).samplers.label_for_resource_into(id, out)
+    }
+
     pub fn sampler_drop<A: HalApi>(&self, sampler_id: id::SamplerId) {
         profiling::scope!("Sampler::drop");
         api_log!("Sampler::drop {sampler_id:?}");
@@ -952,10 +1229,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 trace.add(trace::Action::CreateBindGroupLayout(fid.id(), desc.clone()));
             }
 
-            let entry_map = match bgl::EntryMap::from_entries(&device.limits, &desc.entries) {
-                Ok(map) => map,
-                Err(e) => break e,
-            };
+            let entry_map =
+                match bgl::EntryMap::from_entries(&device.limits, &desc.entries, desc.flags) {
+                    Ok(map) => map,
+                    Err(e) => break e,
+                };
 
             // Currently we make a distinction between fid.assign and fid.assign_existing. This distinction is incorrect,
             // but see https://github.com/gfx-rs/wgpu/issues/4912.
@@ -1007,6 +1285,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).bind_group_layouts.label_for_resource(id)
     }
 
+    pub fn bind_group_layout_label_into<A: HalApi>(
+        &self,
+        id: id::BindGroupLayoutId,
+        out: &mut String,
+    ) {
+        A::hub(self)
+            .bind_group_layouts
+            .label_for_resource_into(id, out)
+    }
+
     pub fn bind_group_layout_drop<A: HalApi>(&self, bind_group_layout_id: id::BindGroupLayoutId) {
         profiling::scope!("BindGroupLayout::drop");
         api_log!("BindGroupLayout::drop {bind_group_layout_id:?}");
@@ -1069,6 +1357,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).pipeline_layouts.label_for_resource(id)
     }
 
+    pub fn pipeline_layout_label_into<A: HalApi>(
+        &self,
+        id: id::PipelineLayoutId,
+        out: &mut String,
+    ) {
+        A::hub(self)
+            .pipeline_layouts
+            .label_for_resource_into(id, out)
+    }
+
     pub fn pipeline_layout_drop<A: HalApi>(&self, pipeline_layout_id: id::PipelineLayoutId) {
         profiling::scope!("PipelineLayout::drop");
         api_log!("PipelineLayout::drop {pipeline_layout_id:?}");
@@ -1142,6 +1440,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).bind_groups.label_for_resource(id)
     }
 
+    pub fn bind_group_label_into<A: HalApi>(&self, id: id::BindGroupId, out: &mut String) {
+        A::hub(self).bind_groups.label_for_resource_into(id, out)
+    }
+
     pub fn bind_group_drop<A: HalApi>(&self, bind_group_id: id::BindGroupId) {
         profiling::scope!("BindGroup::drop");
         api_log!("BindGroup::drop {bind_group_id:?}");
@@ -1219,6 +1521,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         log::error!("Device::create_shader_module error: {error}");
 
         let id = fid.assign_error(desc.label.borrow_or_default());
+        hub.shader_module_errors
+            .lock()
+            .insert(id, error.compilation_message());
         (id, Some(error))
     }
 
@@ -1276,6 +1581,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         log::error!("Device::create_shader_module_spirv error: {error}");
 
         let id = fid.assign_error(desc.label.borrow_or_default());
+        hub.shader_module_errors
+            .lock()
+            .insert(id, error.compilation_message());
         (id, Some(error))
     }
 
@@ -1283,12 +1591,30 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).shader_modules.label_for_resource(id)
     }
 
+    pub fn shader_module_label_into<A: HalApi>(&self, id: id::ShaderModuleId, out: &mut String) {
+        A::hub(self).shader_modules.label_for_resource_into(id, out)
+    }
+
+    /// The messages produced when compiling `shader_module_id`.
+    ///
+    /// naga doesn't report warnings, so this is empty for a shader module
+    /// which compiled, and holds the compilation error of one which didn't.
+    pub fn shader_module_get_compilation_info<A: HalApi>(
+        &self,
+        shader_module_id: id::ShaderModuleId,
+    ) -> Vec<pipeline::ShaderCompilationMessage> {
+        let hub = A::hub(self);
+        let errors = hub.shader_module_errors.lock();
+        errors.get(&shader_module_id).cloned().into_iter().collect()
+    }
+
     pub fn shader_module_drop<A: HalApi>(&self, shader_module_id: id::ShaderModuleId) {
         profiling::scope!("ShaderModule::drop");
         api_log!("ShaderModule::drop {shader_module_id:?}");
 
         let hub = A::hub(self);
         hub.shader_modules.unregister(shader_module_id);
+        hub.shader_module_errors.lock().remove(&shader_module_id);
     }
 
     pub fn device_create_command_encoder<A: HalApi>(
@@ -1296,7 +1622,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         device_id: DeviceId,
         desc: &wgt::CommandEncoderDescriptor<Label>,
         id_in: Input<G, id::CommandEncoderId>,
-    ) -> (id::CommandEncoderId, Option<DeviceError>) {
+    ) -> (
+        id::CommandEncoderId,
+        Option<command::CreateCommandEncoderError>,
+    ) {
         profiling::scope!("Device::create_command_encoder");
 
         let hub = A::hub(self);
@@ -1305,14 +1634,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let error = loop {
             let device = match hub.devices.get(device_id) {
                 Ok(device) => device,
-                Err(_) => break DeviceError::Invalid,
+                Err(_) => break command::CreateCommandEncoderError::InvalidDevice,
             };
             if !device.is_valid() {
-                break DeviceError::Lost;
+                break command::CreateCommandEncoderError::DeviceLost;
             }
             let queue = match hub.queues.get(device.queue_id.read().unwrap()) {
                 Ok(queue) => queue,
-                Err(_) => break DeviceError::InvalidQueueId,
+                Err(_) => break command::CreateCommandEncoderError::InvalidQueue,
             };
             let encoder = match device
                 .command_allocator
@@ -1322,7 +1651,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .acquire_encoder(device.raw(), queue.raw.as_ref().unwrap())
             {
                 Ok(raw) => raw,
-                Err(_) => break DeviceError::OutOfMemory,
+                Err(_) => break command::CreateCommandEncoderError::OutOfMemory,
             };
             let command_buffer = command::CommandBuffer::new(
                 encoder,
@@ -1347,6 +1676,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).command_buffers.label_for_resource(id)
     }
 
+    pub fn command_buffer_label_into<A: HalApi>(&self, id: id::CommandBufferId, out: &mut String) {
+        A::hub(self)
+            .command_buffers
+            .label_for_resource_into(id, out)
+    }
+
     pub fn command_encoder_drop<A: HalApi>(&self, command_encoder_id: id::CommandEncoderId) {
         profiling::scope!("CommandEncoder::drop");
         api_log!("CommandEncoder::drop {command_encoder_id:?}");
@@ -1437,6 +1772,25 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).render_bundles.label_for_resource(id)
     }
 
+    pub fn render_bundle_label_into<A: HalApi>(&self, id: id::RenderBundleId, out: &mut String) {
+        A::hub(self).render_bundles.label_for_resource_into(id, out)
+    }
+
+    /// Report the render pass attachments a render bundle can be executed
+    /// with, and the bind group layouts it uses.
+    pub fn render_bundle_get_compatibility<A: HalApi>(
+        &self,
+        render_bundle_id: id::RenderBundleId,
+    ) -> Result<command::RenderBundleCompatibility, command::InvalidRenderBundle> {
+        let hub = A::hub(self);
+
+        let bundle = hub
+            .render_bundles
+            .get(render_bundle_id)
+            .map_err(|_| command::InvalidRenderBundle)?;
+        Ok(bundle.compatibility())
+    }
+
     pub fn render_bundle_drop<A: HalApi>(&self, render_bundle_id: id::RenderBundleId) {
         profiling::scope!("RenderBundle::drop");
         api_log!("RenderBundle::drop {render_bundle_id:?}");
@@ -1527,6 +1881,30 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).query_sets.label_for_resource(id)
     }
 
+    pub fn query_set_label_into<A: HalApi>(&self, id: id::QuerySetId, out: &mut String) {
+        A::hub(self).query_sets.label_for_resource_into(id, out)
+    }
+
+    /// Return the descriptor `query_set_id` was created with.
+    ///
+    /// This lets callers check that `start_query + query_count` fits in the
+    /// query set before recording a resolve.
+    pub fn query_set_get_descriptor<A: HalApi>(
+        &self,
+        query_set_id: id::QuerySetId,
+    ) -> Result<resource::QuerySetDescriptor<'static>, resource::InvalidQuerySet> {
+        let hub = A::hub(self);
+
+        let query_set = hub
+            .query_sets
+            .get(query_set_id)
+            .map_err(|_| resource::InvalidQuerySet)?;
+        let label = &query_set.info.label;
+        Ok(query_set
+            .desc
+            .map_label(|_| (!label.is_empty()).then(|| Cow::Owned(label.clone()))))
+    }
+
     pub fn device_create_render_pipeline<A: HalApi>(
         &self,
         device_id: DeviceId,
@@ -1627,7 +2005,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     .bind_group_layouts
                     .prepare::<G>(id_in)
                     .assign_existing(bg),
-                None => break binding_model::GetBindGroupLayoutError::InvalidGroupIndex(index),
+                None => {
+                    break binding_model::GetBindGroupLayoutError::IndexOutOfRange {
+                        index,
+                        count: pipeline.layout.bind_group_layouts.len() as u32,
+                    }
+                }
             };
             return (id, None);
         };
@@ -1643,6 +2026,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).render_pipelines.label_for_resource(id)
     }
 
+    pub fn render_pipeline_label_into<A: HalApi>(
+        &self,
+        id: id::RenderPipelineId,
+        out: &mut String,
+    ) {
+        A::hub(self)
+            .render_pipelines
+            .label_for_resource_into(id, out)
+    }
+
     pub fn render_pipeline_drop<A: HalApi>(&self, render_pipeline_id: id::RenderPipelineId) {
         profiling::scope!("RenderPipeline::drop");
         api_log!("RenderPipeline::drop {render_pipeline_id:?}");
@@ -1761,7 +2154,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     .bind_group_layouts
                     .prepare::<G>(id_in)
                     .assign_existing(bg),
-                None => break binding_model::GetBindGroupLayoutError::InvalidGroupIndex(index),
+                None => {
+                    break binding_model::GetBindGroupLayoutError::IndexOutOfRange {
+                        index,
+                        count: pipeline.layout.bind_group_layouts.len() as u32,
+                    }
+                }
             };
 
             return (id, None);
@@ -1778,6 +2176,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).compute_pipelines.label_for_resource(id)
     }
 
+    pub fn compute_pipeline_label_into<A: HalApi>(
+        &self,
+        id: id::ComputePipelineId,
+        out: &mut String,
+    ) {
+        A::hub(self)
+            .compute_pipelines
+            .label_for_resource_into(id, out)
+    }
+
     pub fn compute_pipeline_drop<A: HalApi>(&self, compute_pipeline_id: id::ComputePipelineId) {
         profiling::scope!("ComputePipeline::drop");
         api_log!("ComputePipeline::drop {compute_pipeline_id:?}");
@@ -2053,6 +2461,17 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 }
 
                 let mut presentation = surface.presentation.lock();
+                // Moving the surface onto another device may change what it
+                // supports.
+                let same_device = presentation.as_ref().map_or(true, |present| {
+                    present
+                        .device
+                        .downcast_ref::<A>()
+                        .map_or(false, |d| d.as_info().id() == device_id)
+                });
+                if !same_device {
+                    surface.capabilities.clear();
+                }
                 *presentation = Some(present::Presentation {
                     device: super::any_device::AnyDevice::new(device.clone()),
                     config: config.clone(),
@@ -2107,6 +2526,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .devices
                 .get(device_id)
                 .map_err(|_| DeviceError::Invalid)?;
+
+            // Nothing was queued since the last maintenance found the device
+            // idle, so there is nothing to poll for.
+            if let wgt::Maintain::Poll = maintain {
+                if device.is_valid() && !device.has_pending_work.load(Ordering::Acquire) {
+                    return Ok(true);
+                }
+            }
+
             let fence = device.fence.read();
             let fence = fence.as_ref().unwrap();
             device.maintain(fence, maintain)?
@@ -2117,6 +2545,77 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(queue_empty)
     }
 
+    /// Run the non-blocking part of device maintenance for `device_id`.
+    ///
+    /// This fires the callbacks of completed buffer mappings and submissions
+    /// and frees resources the GPU is done with, but never waits for the GPU.
+    /// It's the same as calling [`Global::device_poll`] with
+    /// [`wgt::Maintain::Poll`] and ignoring whether the queue is empty.
+    pub fn device_tick<A: HalApi>(&self, device_id: DeviceId) -> Result<(), WaitIdleError> {
+        self.device_poll::<A>(device_id, wgt::Maintain::Poll)?;
+        Ok(())
+    }
+
+    /// Poll `device_id` every `interval` on a background thread, which fires
+    /// the callbacks of completed work without the application having to call
+    /// [`Global::device_poll`].
+    ///
+    /// The application may still poll the device itself, each callback is
+    /// called once either way. A thread already polling the device is
+    /// replaced. The thread is stopped by [`Global::stop_poll_thread`], by
+    /// dropping the device, or by dropping the `Global`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_poll_thread<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        interval: std::time::Duration,
+    ) -> Result<(), DeviceError> {
+        api_log!("Device::start_poll_thread {device_id:?} {interval:?}");
+
+        let hub = A::hub(self);
+        let device = hub
+            .devices
+            .get(device_id)
+            .map_err(|_| DeviceError::Invalid)?;
+
+        let thread = PollThread::for_device(format!("wgpu poll {device_id:?}"), &device, interval);
+        let previous = {
+            let mut threads = self.poll_threads.lock();
+            match threads.iter_mut().find(|&&mut (id, _)| id == device_id) {
+                Some(&mut (_, ref mut running)) => Some(std::mem::replace(running, thread)),
+                None => {
+                    threads.push((device_id, thread));
+                    None
+                }
+            }
+        };
+        if let Some(previous) = previous {
+            previous.stop();
+        }
+        Ok(())
+    }
+
+    /// Stop the thread started by [`Global::start_poll_thread`] for
+    /// `device_id`, if any, and wait for it to exit.
+    ///
+    /// When called from the poll thread itself, such as from a callback it
+    /// fires, the thread exits after its current poll instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stop_poll_thread(&self, device_id: DeviceId) {
+        api_log!("Device::stop_poll_thread {device_id:?}");
+
+        let thread = {
+            let mut threads = self.poll_threads.lock();
+            threads
+                .iter()
+                .position(|&(id, _)| id == device_id)
+                .map(|index| threads.swap_remove(index).1)
+        };
+        if let Some(thread) = thread {
+            thread.stop();
+        }
+    }
+
     /// Poll all devices belonging to the backend `A`.
     ///
     /// If `force_wait` is true, block until all buffer mappings are done.
@@ -2190,6 +2689,68 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(all_queue_empty)
     }
 
+    fn poll_device_collect<A: HalApi>(
+        &self,
+        force_wait: bool,
+        closures: &mut UserClosures,
+        results: &mut Vec<(DeviceId, Result<bool, WaitIdleError>)>,
+    ) {
+        profiling::scope!("poll_device_collect");
+
+        let hub = A::hub(self);
+        let device_guard = hub.devices.read();
+
+        for (id, device) in device_guard.iter(A::VARIANT) {
+            let maintain = if force_wait {
+                wgt::Maintain::Wait
+            } else {
+                wgt::Maintain::Poll
+            };
+            let fence = device.fence.read();
+            let fence = fence.as_ref().unwrap();
+            let result = device.maintain(fence, maintain).map(|(cbs, queue_empty)| {
+                closures.extend(cbs);
+                queue_empty
+            });
+            results.push((id, result));
+        }
+    }
+
+    /// Poll all devices on all backends, like [`Global::poll_all_devices`],
+    /// but without stopping at the first device which fails.
+    ///
+    /// Return, for each device, whether its queue is empty or the error
+    /// polling it produced.
+    pub fn poll_all_devices_collect(
+        &self,
+        force_wait: bool,
+    ) -> Vec<(DeviceId, Result<bool, WaitIdleError>)> {
+        api_log!("poll_all_devices_collect");
+        let mut closures = UserClosures::default();
+        let mut results = Vec::new();
+
+        #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
+        {
+            self.poll_device_collect::<hal::api::Vulkan>(force_wait, &mut closures, &mut results);
+        }
+        #[cfg(all(feature = "metal", any(target_os = "macos", target_os = "ios")))]
+        {
+            self.poll_device_collect::<hal::api::Metal>(force_wait, &mut closures, &mut results);
+        }
+        #[cfg(all(feature = "dx12", windows))]
+        {
+            self.poll_device_collect::<hal::api::Dx12>(force_wait, &mut closures, &mut results);
+        }
+        #[cfg(feature = "gles")]
+        {
+            self.poll_device_collect::<hal::api::Gles>(force_wait, &mut closures, &mut results);
+        }
+
+        closures.fire();
+
+        results
+    }
+
     pub fn device_label<A: HalApi>(&self, id: DeviceId) -> String {
         A::hub(self).devices.label_for_resource(id)
     }
@@ -2224,11 +2785,20 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         profiling::scope!("Device::drop");
         api_log!("Device::drop {device_id:?}");
 
+        #[cfg(not(target_arch = "wasm32"))]
+        self.stop_poll_thread(device_id);
+
         let hub = A::hub(self);
         if let Some(device) = hub.devices.unregister(device_id) {
             let device_lost_closure = device.lock_life().device_lost_closure.take();
             if let Some(closure) = device_lost_closure {
-                closure.call(DeviceLostReason::Unknown, String::from("Device dropped."));
+                // A device which is invalid but still holds its closure was
+                // destroyed, and the closure must report that reason.
+                if device.is_valid() {
+                    closure.call(DeviceLostReason::Unknown, String::from("Device dropped."));
+                } else {
+                    closure.call(DeviceLostReason::Destroyed, String::new());
+                }
             }
 
             // The things `Device::prepare_to_die` takes care are mostly
@@ -2261,6 +2831,25 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
     }
 
+    /// Install a filter deciding whether a device loss reported through
+    /// [`Global::device_mark_lost`] actually loses the device.
+    ///
+    /// If the filter returns `false`, the loss is treated as transient: the
+    /// device stays valid and the device lost closure is not called. Losing
+    /// the device through [`Global::device_destroy`] or dropping it is not
+    /// filtered.
+    pub fn device_set_lost_reason_filter<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        filter: DeviceLostReasonFilter,
+    ) {
+        let hub = A::hub(self);
+
+        if let Ok(device) = hub.devices.get(device_id) {
+            *device.lost_reason_filter.lock() = Some(filter);
+        }
+    }
+
     pub fn device_destroy<A: HalApi>(&self, device_id: DeviceId) {
         api_log!("Device::destroy {device_id:?}");
 
@@ -2282,18 +2871,39 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             // setting valid to false, and then relying upon maintain to
             // check for empty queues and a DeviceLostClosure. At that time,
             // the DeviceLostClosure will be called with "destroyed" as the
-            // reason.
-            device.valid.store(false, Ordering::Relaxed);
+            // reason. Any later call to `device_mark_lost` or `device_drop`
+            // which finds the closure still pending reports it the same way,
+            // so the closure fires exactly once, with the `Destroyed` reason.
+            device.valid.store(false, Ordering::Release);
         }
     }
 
-    pub fn device_mark_lost<A: HalApi>(&self, device_id: DeviceId, message: &str) {
-        api_log!("Device::mark_lost {device_id:?}");
+    /// Lose the device `device_id` for `reason`.
+    ///
+    /// The device lost closure is called with `reason` and `message`, unless
+    /// the device was already destroyed, in which case it's called with
+    /// [`DeviceLostReason::Destroyed`] and no message.
+    pub fn device_mark_lost<A: HalApi>(
+        &self,
+        device_id: DeviceId,
+        reason: DeviceLostReason,
+        message: &str,
+    ) {
+        api_log!("Device::mark_lost {device_id:?} {reason:?}");
 
         let hub = A::hub(self);
 
         if let Ok(device) = hub.devices.get(device_id) {
-            device.lose(message);
+            // A loss the filter rejects is treated as transient.
+            if device.is_valid() {
+                if let Some(ref filter) = *device.lost_reason_filter.lock() {
+                    if !filter(&reason) {
+                        log::info!("Ignoring filtered loss of device {device_id:?}: {message}");
+                        return;
+                    }
+                }
+            }
+            device.lose(reason, message);
         }
     }
 
@@ -2329,6 +2939,29 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 
+    /// Like [`Global::buffer_map_async`], but returns a future resolving to
+    /// the result of the mapping instead of taking a callback.
+    ///
+    /// The future does not drive the device. It only completes once the
+    /// device has been polled past the mapping, for example with
+    /// [`Global::device_poll`]. Errors detected up front resolve the future
+    /// immediately.
+    pub fn buffer_map_async_future<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+        range: Range<BufferAddress>,
+        host: HostMap,
+    ) -> resource::BufferMapFuture {
+        let (callback, future) = resource::BufferMapFuture::new();
+        let op = BufferMapOperation {
+            host,
+            callback: Some(callback),
+        };
+        // Any error is also reported to the future through the callback.
+        let _ = self.buffer_map_async::<A>(buffer_id, range, op);
+        future
+    }
+
     // Returns the mapping callback in case of error so that the callback can be fired outside
     // of the locks that are held in this function.
     fn buffer_map_async_inner<A: HalApi>(
@@ -2433,6 +3066,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 
+    /// Get a pointer to `size` bytes of the mapped buffer `buffer_id` from
+    /// `offset`, along with that size, to write to or read from.
+    ///
+    /// Fails with [`BufferAccessError::MappedForReadOnly`] if the buffer was
+    /// mapped for reading, use [`Global::buffer_get_mapped_const_range`] for
+    /// those.
     pub fn buffer_get_mapped_range<A: HalApi>(
         &self,
         buffer_id: id::BufferId,
@@ -2442,6 +3081,35 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         profiling::scope!("Buffer::get_mapped_range");
         api_log!("Buffer::get_mapped_range {buffer_id:?}");
 
+        match self.buffer_get_mapped_range_impl::<A>(buffer_id, offset, size)? {
+            (_, _, HostMap::Read) => Err(BufferAccessError::MappedForReadOnly),
+            (ptr, range_size, HostMap::Write) => Ok((ptr, range_size)),
+        }
+    }
+
+    /// Like [`Global::buffer_get_mapped_range`], but for reading only, which
+    /// makes it work for buffers mapped either way.
+    pub fn buffer_get_mapped_const_range<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        size: Option<BufferAddress>,
+    ) -> Result<(*const u8, u64), BufferAccessError> {
+        profiling::scope!("Buffer::get_mapped_const_range");
+        api_log!("Buffer::get_mapped_const_range {buffer_id:?}");
+
+        let (ptr, range_size, _) =
+            self.buffer_get_mapped_range_impl::<A>(buffer_id, offset, size)?;
+        Ok((ptr.cast_const(), range_size))
+    }
+
+    /// Get the mapped range, along with how the buffer was mapped.
+    fn buffer_get_mapped_range_impl<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        size: Option<BufferAddress>,
+    ) -> Result<(*mut u8, u64, HostMap), BufferAccessError> {
         let hub = A::hub(self);
 
         let buffer = hub
@@ -2474,16 +3142,23 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         match *map_state {
             resource::BufferMapState::Init { ref ptr, .. } => {
                 // offset (u64) can not be < 0, so no need to validate the lower bound
-                if offset + range_size > buffer.size {
-                    return Err(BufferAccessError::OutOfBoundsOverrun {
-                        index: offset + range_size - 1,
-                        max: buffer.size,
-                    });
+                match offset.checked_add(range_size) {
+                    Some(end) if end <= buffer.size => {}
+                    _ => {
+                        return Err(BufferAccessError::OutOfBoundsOverrun {
+                            index: offset.saturating_add(range_size) - 1,
+                            max: buffer.size,
+                        });
+                    }
                 }
-                unsafe { Ok((ptr.as_ptr().offset(offset as isize), range_size)) }
+                // Buffers mapped at creation are always writable.
+                let ptr = unsafe { ptr.as_ptr().offset(offset as isize) };
+                Ok((ptr, range_size, HostMap::Write))
             }
             resource::BufferMapState::Active {
-                ref ptr, ref range, ..
+                ref ptr,
+                ref range,
+                host,
             } => {
                 if offset < range.start {
                     return Err(BufferAccessError::OutOfBoundsUnderrun {
@@ -2491,16 +3166,20 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         min: range.start,
                     });
                 }
-                if offset + range_size > range.end {
-                    return Err(BufferAccessError::OutOfBoundsOverrun {
-                        index: offset + range_size - 1,
-                        max: range.end,
-                    });
+                match offset.checked_add(range_size) {
+                    Some(end) if end <= range.end => {}
+                    _ => {
+                        return Err(BufferAccessError::OutOfBoundsOverrun {
+                            index: offset.saturating_add(range_size) - 1,
+                            max: range.end,
+                        });
+                    }
                 }
                 // ptr points to the beginning of the range we mapped in map_async
                 // rather thant the beginning of the buffer.
                 let relative_offset = (offset - range.start) as isize;
-                unsafe { Ok((ptr.as_ptr().offset(relative_offset), range_size)) }
+                let ptr = unsafe { ptr.as_ptr().offset(relative_offset) };
+                Ok((ptr, range_size, host))
             }
             resource::BufferMapState::Idle | resource::BufferMapState::Waiting(_) => {
                 Err(BufferAccessError::NotMapped)
@@ -97,6 +97,39 @@ impl<A: HalApi> ResourceMaps<A> {
         destroyed_textures.clear();
     }
 
+    pub(crate) fn is_empty(&self) -> bool {
+        let ResourceMaps {
+            buffers,
+            staging_buffers,
+            textures,
+            texture_views,
+            samplers,
+            bind_groups,
+            bind_group_layouts,
+            render_pipelines,
+            compute_pipelines,
+            pipeline_layouts,
+            render_bundles,
+            query_sets,
+            destroyed_buffers,
+            destroyed_textures,
+        } = self;
+        buffers.is_empty()
+            && staging_buffers.is_empty()
+            && textures.is_empty()
+            && texture_views.is_empty()
+            && samplers.is_empty()
+            && bind_groups.is_empty()
+            && bind_group_layouts.is_empty()
+            && render_pipelines.is_empty()
+            && compute_pipelines.is_empty()
+            && pipeline_layouts.is_empty()
+            && render_bundles.is_empty()
+            && query_sets.is_empty()
+            && destroyed_buffers.is_empty()
+            && destroyed_textures.is_empty()
+    }
+
     pub(crate) fn extend(&mut self, mut other: Self) {
         let ResourceMaps {
             buffers,
@@ -169,6 +202,8 @@ pub enum WaitIdleError {
     WrongSubmissionIndex(id::QueueId, id::DeviceId),
     #[error("GPU got stuck :(")]
     StuckGpu,
+    #[error("Timed out waiting for the GPU")]
+    Timeout,
 }
 
 /// Resource tracking for a device.
@@ -260,6 +295,19 @@ impl<A: HalApi> LifetimeTracker<A> {
         }
     }
 
+    /// Return true if maintaining the device would have nothing to do: no
+    /// submissions in flight, no pending mappings or closures, and no
+    /// resources waiting to be freed.
+    pub fn is_idle(&self) -> bool {
+        self.active.is_empty()
+            && self.mapped.is_empty()
+            && self.ready_to_map.is_empty()
+            && self.work_done_closures.is_empty()
+            && self.future_suspected_buffers.is_empty()
+            && self.future_suspected_textures.is_empty()
+            && self.suspected_resources.is_empty()
+    }
+
     /// Return true if there are no queue submissions still in flight.
     pub fn queue_empty(&self) -> bool {
         self.active.is_empty()
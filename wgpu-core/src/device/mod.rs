@@ -22,6 +22,8 @@ pub mod any_device;
 pub(crate) mod bgl;
 pub mod global;
 mod life;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod poll_thread;
 pub mod queue;
 pub mod resource;
 #[cfg(any(feature = "trace", feature = "replay"))]
@@ -222,6 +224,27 @@ pub type DeviceLostCallback = Box<dyn Fn(DeviceLostReason, String) + Send + 'sta
 )))]
 pub type DeviceLostCallback = Box<dyn Fn(DeviceLostReason, String) + 'static>;
 
+/// Decides whether a device loss is reported, see
+/// [`Global::device_set_lost_reason_filter`](crate::global::Global::device_set_lost_reason_filter).
+#[cfg(any(
+    not(target_arch = "wasm32"),
+    all(
+        feature = "fragile-send-sync-non-atomic-wasm",
+        not(target_feature = "atomics")
+    )
+))]
+pub type DeviceLostReasonFilter = Box<dyn Fn(&DeviceLostReason) -> bool + Send + Sync + 'static>;
+/// Decides whether a device loss is reported, see
+/// [`Global::device_set_lost_reason_filter`](crate::global::Global::device_set_lost_reason_filter).
+#[cfg(not(any(
+    not(target_arch = "wasm32"),
+    all(
+        feature = "fragile-send-sync-non-atomic-wasm",
+        not(target_feature = "atomics")
+    )
+)))]
+pub type DeviceLostReasonFilter = Box<dyn Fn(&DeviceLostReason) -> bool + 'static>;
+
 pub struct DeviceLostClosureRust {
     pub callback: DeviceLostCallback,
     called: bool,
@@ -0,0 +1,185 @@
+//! Background threads polling a device, see [`Global::start_poll_thread`].
+//!
+//! [`Global::start_poll_thread`]: crate::global::Global::start_poll_thread
+
+use std::{
+    sync::{Arc, Weak},
+    thread,
+    time::Duration,
+};
+
+use parking_lot::{Condvar, Mutex};
+
+use crate::{device::Device, hal_api::HalApi};
+
+/// A thread calling a poll function at an interval until it's stopped.
+#[derive(Debug)]
+pub(crate) struct PollThread {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl PollThread {
+    /// Spawn a thread calling `poll` every `interval`, until it's stopped or
+    /// `poll` returns `false`.
+    pub(crate) fn spawn<F>(name: String, interval: Duration, mut poll: F) -> Self
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_stop = stop.clone();
+        let handle = thread::Builder::new()
+            .name(name)
+            .spawn(move || {
+                let (ref stopped, ref condvar) = *thread_stop;
+                loop {
+                    {
+                        let mut stopped = stopped.lock();
+                        if !*stopped {
+                            condvar.wait_for(&mut stopped, interval);
+                        }
+                        if *stopped {
+                            break;
+                        }
+                    }
+                    if !poll() {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed to spawn the device poll thread");
+        Self { stop, handle }
+    }
+
+    /// Spawn a thread polling `device` every `interval`, firing the callbacks
+    /// of the work which completed.
+    ///
+    /// The thread doesn't keep the device alive, and exits once it's gone.
+    pub(crate) fn for_device<A: HalApi>(
+        name: String,
+        device: &Arc<Device<A>>,
+        interval: Duration,
+    ) -> Self {
+        let device = Arc::downgrade(device);
+        Self::spawn(name, interval, move || poll_device(&device))
+    }
+
+    /// Stop the thread and wait for it to exit.
+    ///
+    /// On the thread itself, it's detached instead and exits once the current
+    /// poll returns, since joining it would never return.
+    pub(crate) fn stop(self) {
+        let (ref stopped, ref condvar) = *self.stop;
+        *stopped.lock() = true;
+        condvar.notify_one();
+        if thread::current().id() == self.handle.thread().id() {
+            return;
+        }
+        if self.handle.join().is_err() {
+            log::error!("Device poll thread panicked");
+        }
+    }
+}
+
+/// Poll `device` once, returning `false` if it no longer exists.
+fn poll_device<A: HalApi>(device: &Weak<Device<A>>) -> bool {
+    let Some(device) = device.upgrade() else {
+        return false;
+    };
+
+    // Like a `Maintain::Poll` through `Global::device_poll`, don't bother if
+    // nothing was queued since the device was last found idle. Callbacks are
+    // taken out of the device as they're fired, so polling here while the
+    // application polls too never fires one twice.
+    if !device
+        .has_pending_work
+        .load(std::sync::atomic::Ordering::Acquire)
+    {
+        return true;
+    }
+
+    let result = {
+        let fence = device.fence.read();
+        let fence = fence.as_ref().unwrap();
+        device.maintain(fence, wgt::Maintain::Poll)
+    };
+    match result {
+        Ok((closures, _queue_empty)) => closures.fire(),
+        Err(err) => log::error!("Failed to poll device in the background: {err}"),
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use parking_lot::Mutex;
+
+    use super::PollThread;
+
+    #[test]
+    fn poll_until_stopped() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counter = polls.clone();
+        let thread = PollThread::spawn(String::from("test"), Duration::from_millis(1), move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+            true
+        });
+
+        while polls.load(Ordering::Relaxed) < 3 {
+            std::thread::yield_now();
+        }
+        thread.stop();
+
+        // Nothing polls once the thread has been joined.
+        let stopped_at = polls.load(Ordering::Relaxed);
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(polls.load(Ordering::Relaxed), stopped_at);
+    }
+
+    #[test]
+    fn stop_from_poll_thread() {
+        let slot = Arc::new(Mutex::new(None::<PollThread>));
+        let polls = Arc::new(AtomicUsize::new(0));
+        let thread_slot = slot.clone();
+        let counter = polls.clone();
+        let mut guard = slot.lock();
+        *guard = Some(PollThread::spawn(
+            String::from("test"),
+            Duration::from_millis(1),
+            move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+                // Stopping the thread from within must not try to join it.
+                if let Some(thread) = thread_slot.lock().take() {
+                    thread.stop();
+                }
+                true
+            },
+        ));
+        drop(guard);
+
+        while slot.lock().is_some() {
+            std::thread::yield_now();
+        }
+
+        // The detached thread exits after the poll which stopped it.
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(polls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn stops_when_poll_returns_false() {
+        let thread = PollThread::spawn(String::from("test"), Duration::from_millis(1), || false);
+        while !thread.handle.is_finished() {
+            std::thread::yield_now();
+        }
+        thread.stop();
+    }
+}
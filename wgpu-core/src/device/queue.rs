@@ -15,19 +15,25 @@ use crate::{
     id::{self, QueueId},
     identity::{GlobalIdentityHandlerFactory, Input},
     init_tracker::{has_copy_partial_init_tracker_coverage, TextureInitRange},
+    registry::ResourceLogEvent,
     resource::{
         Buffer, BufferAccessError, BufferMapState, DestroyedBuffer, DestroyedTexture, Resource,
         ResourceInfo, ResourceType, StagingBuffer, Texture, TextureInner,
     },
-    resource_log, track, FastHashMap, SubmissionIndex,
+    snatch::SnatchGuard,
+    track, FastHashMap, SubmissionIndex,
 };
 
 use hal::{CommandEncoder as _, Device as _, Queue as _, RawSet as _};
 use parking_lot::Mutex;
 
 use std::{
-    iter, mem, ptr,
+    future::Future,
+    iter, mem,
+    pin::Pin,
+    ptr,
     sync::{atomic::Ordering, Arc},
+    task::{Context, Poll, Waker},
 };
 use thiserror::Error;
 
@@ -139,6 +145,77 @@ impl SubmittedWorkDoneClosure {
     }
 }
 
+#[derive(Debug, Default)]
+struct SubmittedWorkDoneFutureState {
+    result: Option<Result<(), InvalidQueue>>,
+    waker: Option<Waker>,
+}
+
+/// Completes the [`SubmittedWorkDoneFuture`] it was created with, or fails it
+/// if dropped without being called.
+struct SubmittedWorkDoneFutureSender {
+    state: Option<Arc<Mutex<SubmittedWorkDoneFutureState>>>,
+}
+
+impl SubmittedWorkDoneFutureSender {
+    fn send(&mut self, result: Result<(), InvalidQueue>) {
+        let Some(state) = self.state.take() else {
+            return;
+        };
+        let waker = {
+            let mut state = state.lock();
+            state.result = Some(result);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for SubmittedWorkDoneFutureSender {
+    fn drop(&mut self) {
+        self.send(Err(InvalidQueue));
+    }
+}
+
+/// A future resolving once the work submitted to a queue is done.
+///
+/// Returned by [`Global::queue_on_submitted_work_done_future`]. It does not
+/// drive the device on its own: it only completes once the device has been
+/// polled past the submissions.
+#[derive(Debug)]
+pub struct SubmittedWorkDoneFuture {
+    state: Arc<Mutex<SubmittedWorkDoneFutureState>>,
+}
+
+impl SubmittedWorkDoneFuture {
+    /// Create a future together with the closure which completes it.
+    pub(crate) fn new() -> (SubmittedWorkDoneClosure, Self) {
+        let state = Arc::new(Mutex::new(SubmittedWorkDoneFutureState::default()));
+        let mut sender = SubmittedWorkDoneFutureSender {
+            state: Some(state.clone()),
+        };
+        let closure = SubmittedWorkDoneClosure::from_rust(Box::new(move || sender.send(Ok(()))));
+        (closure, Self { state })
+    }
+}
+
+impl Future for SubmittedWorkDoneFuture {
+    type Output = Result<(), InvalidQueue>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct WrappedSubmissionIndex {
@@ -209,6 +286,25 @@ pub(crate) struct PendingWrites<A: HalApi> {
     pub dst_buffers: FastHashMap<id::BufferId, Arc<Buffer<A>>>,
     pub dst_textures: FastHashMap<id::TextureId, Arc<Texture<A>>>,
     pub executing_command_buffers: Vec<A::CommandBuffer>,
+    /// Whether `queue_write_buffer` defers its writes to the next submit.
+    ///
+    /// See [`Global::queue_set_write_coalescing`].
+    pub coalesce_writes: bool,
+    /// Buffer writes deferred while `coalesce_writes` is set, in the order
+    /// they were made. Their data is stored back to back in `coalesced_data`.
+    coalesced_writes: Vec<CoalescedWrite<A>>,
+    coalesced_data: Vec<u8>,
+}
+
+/// A `queue_write_buffer` whose data is waiting in
+/// [`PendingWrites::coalesced_data`].
+#[derive(Debug)]
+struct CoalescedWrite<A: HalApi> {
+    buffer_id: id::BufferId,
+    buffer: Arc<Buffer<A>>,
+    buffer_offset: wgt::BufferAddress,
+    data_offset: wgt::BufferAddress,
+    size: wgt::BufferAddress,
 }
 
 impl<A: HalApi> PendingWrites<A> {
@@ -220,6 +316,9 @@ impl<A: HalApi> PendingWrites<A> {
             dst_buffers: FastHashMap::default(),
             dst_textures: FastHashMap::default(),
             executing_command_buffers: Vec::new(),
+            coalesce_writes: false,
+            coalesced_writes: Vec::new(),
+            coalesced_data: Vec::new(),
         }
     }
 
@@ -234,6 +333,7 @@ impl<A: HalApi> PendingWrites<A> {
         }
 
         self.temp_resources.clear();
+        self.coalesced_writes.clear();
     }
 
     pub fn consume_temp(&mut self, resource: TempResource<A>) {
@@ -354,6 +454,10 @@ pub enum QueueWriteError {
     Transfer(#[from] TransferError),
     #[error(transparent)]
     MemoryInitFailure(#[from] ClearError),
+    #[error("Mip chain data is {actual} bytes, but the mip chain needs {expected}")]
+    MipChainSizeMismatch { expected: u64, actual: u64 },
+    #[error("Format {0:?} has no single block size, so its mip chain can't be written as a whole")]
+    MipChainUnsupportedFormat(wgt::TextureFormat),
 }
 
 #[derive(Clone, Debug, Error)]
@@ -369,12 +473,68 @@ pub enum QueueSubmitError {
     Unmap(#[from] BufferAccessError),
     #[error("Buffer {0:?} is still mapped")]
     BufferStillMapped(id::BufferId),
+    #[error("Command buffer {0:?} is invalid")]
+    InvalidCommandBuffer(id::CommandBufferId),
+    #[error("Command buffer {0:?} is not finished")]
+    UnfinishedCommandBuffer(id::CommandBufferId),
     #[error("Surface output was dropped before the command buffer got submitted")]
     SurfaceOutputDropped,
     #[error("Surface was unconfigured before the command buffer got submitted")]
     SurfaceUnconfigured,
     #[error("GPU got stuck :(")]
     StuckGpu,
+    #[error(transparent)]
+    Write(#[from] QueueWriteError),
+    #[error("Command buffer at index {index} can't be submitted")]
+    CommandBuffer {
+        index: usize,
+        #[source]
+        source: Box<QueueSubmitError>,
+    },
+}
+
+impl QueueSubmitError {
+    fn in_command_buffer(self, index: usize) -> Self {
+        Self::CommandBuffer {
+            index,
+            source: Box::new(self),
+        }
+    }
+}
+
+/// Check that `cmdbuf` can be submitted to `queue_id`, without consuming it.
+fn validate_command_buffer<A: HalApi>(
+    cmdbuf: &CommandBuffer<A>,
+    queue_id: QueueId,
+    snatch_guard: &SnatchGuard,
+) -> Result<(), QueueSubmitError> {
+    if cmdbuf.device.as_info().id() != queue_id {
+        return Err(DeviceError::WrongDevice.into());
+    }
+    if !cmdbuf.is_finished() {
+        return Err(QueueSubmitError::UnfinishedCommandBuffer(
+            cmdbuf.as_info().id(),
+        ));
+    }
+
+    let cmd_buf_data = cmdbuf.data.lock();
+    let cmd_buf_trackers = &cmd_buf_data.as_ref().unwrap().trackers;
+    for buffer in cmd_buf_trackers.buffers.used_resources() {
+        let id = buffer.info.id();
+        if buffer.raw.get(snatch_guard).is_none() {
+            return Err(QueueSubmitError::DestroyedBuffer(id));
+        }
+        // A mapping of a unique buffer is dropped on submission instead.
+        if !buffer.is_unique() && !matches!(*buffer.map_state.lock(), BufferMapState::Idle) {
+            return Err(QueueSubmitError::BufferStillMapped(id));
+        }
+    }
+    for texture in cmd_buf_trackers.textures.used_resources() {
+        if texture.inner.get(snatch_guard).is_none() {
+            return Err(QueueSubmitError::DestroyedTexture(texture.info.id()));
+        }
+    }
+    Ok(())
 }
 
 //TODO: move out common parts of write_xxx.
@@ -412,18 +572,44 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             });
         }
 
+        let buffer = hub
+            .buffers
+            .get(buffer_id)
+            .map_err(|_| TransferError::InvalidBuffer(buffer_id))?;
+        if buffer.device.as_info().id() != device.as_info().id() {
+            return Err(DeviceError::WrongDevice.into());
+        }
+        if buffer.is_destroyed(&device.snatchable_lock.read()) {
+            return Err(TransferError::DestroyedBuffer(buffer_id).into());
+        }
+        self.queue_validate_write_buffer_impl(&buffer, buffer_id, buffer_offset, data_size)?;
+
+        // An empty write is a no-op, but it must still be valid.
         if data_size == 0 {
             log::trace!("Ignoring write_buffer of size 0");
             return Ok(());
         }
 
+        let mut pending_writes = device.pending_writes.lock();
+        let pending_writes = pending_writes.as_mut().unwrap();
+
+        if pending_writes.coalesce_writes {
+            pending_writes.coalesced_writes.push(CoalescedWrite {
+                buffer_id,
+                buffer,
+                buffer_offset,
+                data_offset: pending_writes.coalesced_data.len() as wgt::BufferAddress,
+                size: data_size,
+            });
+            pending_writes.coalesced_data.extend_from_slice(data);
+            return Ok(());
+        }
+
         // Platform validation requires that the staging buffer always be
         // freed, even if an error occurs. All paths from here must call
         // `device.pending_writes.consume`.
         let (staging_buffer, staging_buffer_ptr) =
             prepare_staging_buffer(device, data_size, device.instance_flags)?;
-        let mut pending_writes = device.pending_writes.lock();
-        let pending_writes = pending_writes.as_mut().unwrap();
 
         let stage_fid = hub.staging_buffers.request();
         let staging_buffer = stage_fid.init(staging_buffer);
@@ -449,6 +635,96 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         result
     }
 
+    /// Enable or disable coalescing of [`Global::queue_write_buffer`] calls.
+    ///
+    /// While enabled, the data of every `queue_write_buffer` is validated and
+    /// kept on the CPU, and all writes made before the next
+    /// [`Global::queue_submit`] share a single staging buffer which is
+    /// uploaded at submit time. This saves staging allocations for many small
+    /// writes, but a write is no longer recorded until the queue is
+    /// submitted.
+    ///
+    /// Disabling coalescing records any deferred writes immediately.
+    pub fn queue_set_write_coalescing<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+        enabled: bool,
+    ) -> Result<(), QueueWriteError> {
+        api_log!("Queue::set_write_coalescing {queue_id:?} {enabled}");
+
+        let hub = A::hub(self);
+
+        let queue = hub
+            .queues
+            .get(queue_id)
+            .map_err(|_| DeviceError::InvalidQueueId)?;
+
+        let device = queue.device.as_ref().unwrap();
+
+        let mut pending_writes = device.pending_writes.lock();
+        let pending_writes = pending_writes.as_mut().unwrap();
+        pending_writes.coalesce_writes = enabled;
+        if enabled {
+            Ok(())
+        } else {
+            self.queue_flush_coalesced_writes(device, pending_writes)
+        }
+    }
+
+    /// Record all writes deferred by write coalescing into `pending_writes`,
+    /// using a single staging buffer.
+    fn queue_flush_coalesced_writes<A: HalApi>(
+        &self,
+        device: &Arc<Device<A>>,
+        pending_writes: &mut PendingWrites<A>,
+    ) -> Result<(), QueueWriteError> {
+        if pending_writes.coalesced_writes.is_empty() {
+            return Ok(());
+        }
+        profiling::scope!("Queue::flush_coalesced_writes");
+
+        let hub = A::hub(self);
+        let writes = mem::take(&mut pending_writes.coalesced_writes);
+        let data = mem::take(&mut pending_writes.coalesced_data);
+
+        // Platform validation requires that the staging buffer always be
+        // freed, even if an error occurs. All paths from here must call
+        // `device.pending_writes.consume`.
+        let (staging_buffer, staging_buffer_ptr) = prepare_staging_buffer(
+            device,
+            data.len() as wgt::BufferAddress,
+            device.instance_flags,
+        )?;
+
+        let stage_fid = hub.staging_buffers.request();
+        let staging_buffer = stage_fid.init(staging_buffer);
+
+        if let Err(flush_error) = unsafe {
+            profiling::scope!("copy");
+            ptr::copy_nonoverlapping(data.as_ptr(), staging_buffer_ptr, data.len());
+            staging_buffer.flush(device.raw())
+        } {
+            pending_writes.consume(staging_buffer);
+            return Err(flush_error.into());
+        }
+
+        let result = writes.iter().try_for_each(|write| {
+            self.queue_write_staging_buffer_region(
+                device,
+                pending_writes,
+                &staging_buffer,
+                write.data_offset,
+                &write.buffer,
+                write.buffer_id,
+                write.buffer_offset,
+                write.size,
+            )
+        });
+
+        pending_writes.consume(staging_buffer);
+        result
+    }
+
     pub fn queue_create_staging_buffer<A: HalApi>(
         &self,
         queue_id: QueueId,
@@ -469,8 +745,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             prepare_staging_buffer(device, buffer_size.get(), device.instance_flags)?;
 
         let fid = hub.staging_buffers.prepare::<G>(id_in);
-        let (id, _) = fid.assign(staging_buffer);
-        resource_log!("Queue::create_staging_buffer {id:?}");
+        let (id, staging_buffer) = fid.assign(staging_buffer);
+        staging_buffer.log_event(
+            device,
+            ResourceLogEvent::Created,
+            format_args!("Queue::create_staging_buffer {id:?}"),
+        );
 
         Ok((id, staging_buffer_ptr))
     }
@@ -511,6 +791,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Err(flush_error.into());
         }
 
+        // Deferred writes were made first, so they must be recorded first.
+        if let Err(error) = self.queue_flush_coalesced_writes(device, pending_writes) {
+            pending_writes.consume(staging_buffer);
+            return Err(error);
+        }
+
         let result = self.queue_write_staging_buffer_impl(
             device,
             pending_writes,
@@ -584,11 +870,37 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> Result<(), QueueWriteError> {
         let hub = A::hub(self);
 
+        let dst = hub
+            .buffers
+            .get(buffer_id)
+            .map_err(|_| TransferError::InvalidBuffer(buffer_id))?;
+
+        self.queue_write_staging_buffer_region(
+            device,
+            pending_writes,
+            staging_buffer,
+            0,
+            &dst,
+            buffer_id,
+            buffer_offset,
+            staging_buffer.size,
+        )
+    }
+
+    /// Record a copy of `size` bytes at `src_offset` in `staging_buffer` to
+    /// `buffer_offset` in `dst`.
+    fn queue_write_staging_buffer_region<A: HalApi>(
+        &self,
+        device: &Device<A>,
+        pending_writes: &mut PendingWrites<A>,
+        staging_buffer: &StagingBuffer<A>,
+        src_offset: wgt::BufferAddress,
+        dst: &Arc<Buffer<A>>,
+        buffer_id: id::BufferId,
+        buffer_offset: wgt::BufferAddress,
+        size: wgt::BufferAddress,
+    ) -> Result<(), QueueWriteError> {
         let (dst, transition) = {
-            let buffer_guard = hub.buffers.read();
-            let dst = buffer_guard
-                .get(buffer_id)
-                .map_err(|_| TransferError::InvalidBuffer(buffer_id))?;
             let mut trackers = device.trackers.lock();
             trackers
                 .buffers
@@ -599,20 +911,19 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let dst_raw = dst
             .raw
             .get(&snatch_guard)
-            .ok_or(TransferError::InvalidBuffer(buffer_id))?;
+            .ok_or(TransferError::DestroyedBuffer(buffer_id))?;
 
         if dst.device.as_info().id() != device.as_info().id() {
             return Err(DeviceError::WrongDevice.into());
         }
 
-        let src_buffer_size = staging_buffer.size;
-        self.queue_validate_write_buffer_impl(&dst, buffer_id, buffer_offset, src_buffer_size)?;
+        self.queue_validate_write_buffer_impl(&dst, buffer_id, buffer_offset, size)?;
 
         dst.info
             .use_at(device.active_submission_index.load(Ordering::Relaxed) + 1);
 
-        let region = wgt::BufferSize::new(src_buffer_size).map(|size| hal::BufferCopy {
-            src_offset: 0,
+        let region = wgt::BufferSize::new(size).map(|size| hal::BufferCopy {
+            src_offset,
             dst_offset: buffer_offset,
             size,
         });
@@ -631,7 +942,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 region.into_iter(),
             );
         }
-        let dst = hub.buffers.get(buffer_id).unwrap();
         pending_writes.dst_buffers.insert(buffer_id, dst.clone());
 
         // Ensure the overwritten bytes are marked as initialized so
@@ -639,23 +949,26 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         {
             dst.initialization_status
                 .write()
-                .drain(buffer_offset..(buffer_offset + src_buffer_size));
+                .drain(buffer_offset..(buffer_offset + size));
         }
 
         Ok(())
     }
 
-    pub fn queue_write_texture<A: HalApi>(
+    /// Check that a [`Global::queue_write_texture`] with the given
+    /// `destination`, `data_layout` and `size` would be valid, without
+    /// needing the data itself.
+    ///
+    /// This applies the same rules as the real write, except that the size of
+    /// the data is not known yet, so it can't be checked against the layout.
+    pub fn queue_validate_write_texture<A: HalApi>(
         &self,
         queue_id: QueueId,
         destination: &ImageCopyTexture,
-        data: &[u8],
         data_layout: &wgt::ImageDataLayout,
         size: &wgt::Extent3d,
     ) -> Result<(), QueueWriteError> {
-        profiling::scope!("Queue::write_texture");
-        api_log!("Queue::write_texture {:?} {size:?}", destination.texture);
-
+        profiling::scope!("Queue::validate_write_texture");
         let hub = A::hub(self);
 
         let queue = hub
@@ -665,27 +978,42 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let device = queue.device.as_ref().unwrap();
 
-        #[cfg(feature = "trace")]
-        if let Some(ref mut trace) = *device.trace.lock() {
-            let data_path = trace.make_binary("bin", data);
-            trace.add(Action::WriteTexture {
-                to: *destination,
-                data: data_path,
-                layout: *data_layout,
-                size: *size,
-            });
-        }
-
-        if size.width == 0 || size.height == 0 || size.depth_or_array_layers == 0 {
-            log::trace!("Ignoring write_texture of size 0");
-            return Ok(());
-        }
-
         let dst = hub
             .textures
             .get(destination.texture)
             .map_err(|_| TransferError::InvalidTexture(destination.texture))?;
 
+        self.queue_validate_write_texture_impl(
+            queue_id,
+            device,
+            &dst,
+            destination,
+            wgt::BufferAddress::MAX,
+            data_layout,
+            size,
+        )?;
+
+        Ok(())
+    }
+
+    fn queue_validate_write_texture_impl<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+        device: &Device<A>,
+        dst: &Arc<Texture<A>>,
+        destination: &ImageCopyTexture,
+        data_size: wgt::BufferAddress,
+        data_layout: &wgt::ImageDataLayout,
+        size: &wgt::Extent3d,
+    ) -> Result<
+        (
+            hal::CopyExtent,
+            u32,
+            track::TextureSelector,
+            hal::TextureCopyBase,
+        ),
+        QueueWriteError,
+    > {
         if dst.device.as_info().id() != queue_id {
             return Err(DeviceError::WrongDevice.into());
         }
@@ -701,7 +1029,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (hal_copy_size, array_layer_count) =
             validate_texture_copy_range(destination, &dst.desc, CopySide::Destination, size)?;
 
-        let (selector, dst_base) = extract_texture_selector(destination, size, &dst)?;
+        let (selector, dst_base) = extract_texture_selector(destination, size, dst)?;
 
         if !dst_base.aspect.is_one() {
             return Err(TransferError::CopyAspectNotOne.into());
@@ -721,7 +1049,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             data_layout,
             dst.desc.format,
             destination.aspect,
-            data.len() as wgt::BufferAddress,
+            data_size,
             CopySide::Source,
             size,
             false,
@@ -733,6 +1061,62 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .map_err(TransferError::from)?;
         }
 
+        Ok((hal_copy_size, array_layer_count, selector, dst_base))
+    }
+
+    pub fn queue_write_texture<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+        destination: &ImageCopyTexture,
+        data: &[u8],
+        data_layout: &wgt::ImageDataLayout,
+        size: &wgt::Extent3d,
+    ) -> Result<(), QueueWriteError> {
+        profiling::scope!("Queue::write_texture");
+        api_log!("Queue::write_texture {:?} {size:?}", destination.texture);
+
+        let hub = A::hub(self);
+
+        let queue = hub
+            .queues
+            .get(queue_id)
+            .map_err(|_| DeviceError::InvalidQueueId)?;
+
+        let device = queue.device.as_ref().unwrap();
+
+        #[cfg(feature = "trace")]
+        if let Some(ref mut trace) = *device.trace.lock() {
+            let data_path = trace.make_binary("bin", data);
+            trace.add(Action::WriteTexture {
+                to: *destination,
+                data: data_path,
+                layout: *data_layout,
+                size: *size,
+            });
+        }
+
+        let dst = hub
+            .textures
+            .get(destination.texture)
+            .map_err(|_| TransferError::InvalidTexture(destination.texture))?;
+
+        let (hal_copy_size, array_layer_count, selector, dst_base) = self
+            .queue_validate_write_texture_impl(
+                queue_id,
+                device,
+                &dst,
+                destination,
+                data.len() as wgt::BufferAddress,
+                data_layout,
+                size,
+            )?;
+
+        // A write with an empty extent is a no-op, but it must still be valid.
+        if size.width == 0 || size.height == 0 || size.depth_or_array_layers == 0 {
+            log::trace!("Ignoring write_texture of size 0");
+            return Ok(());
+        }
+
         let (block_width, block_height) = dst.desc.format.block_dimensions();
         let width_blocks = size.width / block_width;
         let height_blocks = size.height / block_height;
@@ -911,6 +1295,84 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 
+    /// Write a whole mip chain to `texture`, starting at mip level 0.
+    ///
+    /// `data` holds each level one after the other, tightly packed, with
+    /// level 0 having the size `full_size`. `mip_level_count` levels are
+    /// written, and `data` must be exactly as long as they need. Levels of
+    /// block-compressed formats are padded to whole blocks.
+    ///
+    /// Every level is validated before any of them is written.
+    pub fn queue_write_texture_mips<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+        texture: id::TextureId,
+        data: &[u8],
+        mip_level_count: u32,
+        full_size: &wgt::Extent3d,
+    ) -> Result<(), QueueWriteError> {
+        profiling::scope!("Queue::write_texture_mips");
+        api_log!("Queue::write_texture_mips {texture:?} {full_size:?} x{mip_level_count}");
+
+        let hub = A::hub(self);
+        let dst = hub
+            .textures
+            .get(texture)
+            .map_err(|_| TransferError::InvalidTexture(texture))?;
+
+        if mip_level_count > dst.desc.mip_level_count {
+            return Err(TransferError::InvalidMipLevel {
+                requested: mip_level_count - 1,
+                count: dst.desc.mip_level_count,
+            }
+            .into());
+        }
+
+        let format = dst.desc.format;
+        let levels = mip_chain_layout(format, dst.desc.dimension, full_size, mip_level_count)
+            .ok_or(QueueWriteError::MipChainUnsupportedFormat(format))?;
+
+        let expected = levels.last().map_or(0, |&(offset, ref layout, ref size)| {
+            offset + mip_level_bytes(layout, size)
+        });
+        if expected != data.len() as u64 {
+            return Err(QueueWriteError::MipChainSizeMismatch {
+                expected,
+                actual: data.len() as u64,
+            });
+        }
+
+        let destination = |mip_level| ImageCopyTexture {
+            texture,
+            mip_level: mip_level as u32,
+            origin: wgt::Origin3d::ZERO,
+            aspect: wgt::TextureAspect::All,
+        };
+        for (mip_level, &(_, _, ref size)) in levels.iter().enumerate() {
+            validate_texture_copy_range(
+                &destination(mip_level),
+                &dst.desc,
+                CopySide::Destination,
+                size,
+            )?;
+        }
+        drop(dst);
+
+        for (mip_level, (offset, layout, size)) in levels.into_iter().enumerate() {
+            let destination = destination(mip_level);
+            let end = offset + mip_level_bytes(&layout, &size);
+            self.queue_write_texture::<A>(
+                queue_id,
+                &destination,
+                &data[offset as usize..end as usize],
+                &layout,
+                &size,
+            )?;
+        }
+
+        Ok(())
+    }
+
     #[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
     pub fn queue_copy_external_image_to_texture<A: HalApi>(
         &self,
@@ -930,11 +1392,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let device = queue.device.as_ref().unwrap();
 
-        if size.width == 0 || size.height == 0 || size.depth_or_array_layers == 0 {
-            log::trace!("Ignoring write_texture of size 0");
-            return Ok(());
-        }
-
         let mut needs_flag = false;
         needs_flag |= matches!(source.source, wgt::ExternalImageSource::OffscreenCanvas(_));
         needs_flag |= source.origin != wgt::Origin2d::ZERO;
@@ -1005,7 +1462,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
             .into());
         }
-        if size.depth_or_array_layers != 1 {
+        if size.depth_or_array_layers > 1 {
             return Err(TransferError::TextureOverrun {
                 start_offset: 0,
                 end_offset: size.depth_or_array_layers,
@@ -1028,6 +1485,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (selector, dst_base) =
             extract_texture_selector(&destination.to_untagged(), &size, &dst)?;
 
+        // A copy with an empty extent is a no-op, but it must still be valid.
+        if size.width == 0 || size.height == 0 || size.depth_or_array_layers == 0 {
+            log::trace!("Ignoring copy_external_image_to_texture of size 0");
+            return Ok(());
+        }
+
         let mut pending_writes = device.pending_writes.lock();
         let encoder = pending_writes.as_mut().unwrap().activate();
 
@@ -1108,6 +1571,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 
+    /// Submit the command buffers `command_buffer_ids` to `queue_id`.
+    ///
+    /// Every command buffer is validated first. If one of them is invalid,
+    /// unfinished, or uses a destroyed or mapped resource, the error is
+    /// returned as [`QueueSubmitError::CommandBuffer`] with its index and
+    /// nothing is submitted. The command buffers are then left untouched,
+    /// and it's up to the caller to drop them. Invalid and unfinished command
+    /// buffers used to be skipped, with the rest still being submitted.
     pub fn queue_submit<A: HalApi>(
         &self,
         queue_id: QueueId,
@@ -1126,8 +1597,39 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
             let device = queue.device.as_ref().unwrap();
 
+            // Deferred writes precede the command buffers being submitted, so
+            // they have to be recorded before their usage is merged into the
+            // device trackers.
+            {
+                let mut pending_writes = device.pending_writes.lock();
+                let pending_writes = pending_writes.as_mut().unwrap();
+                self.queue_flush_coalesced_writes(device, pending_writes)?;
+            }
+
             let mut fence = device.fence.write();
             let fence = fence.as_mut().unwrap();
+
+            // Held from validation until every command buffer is consumed, so
+            // that nothing validated can be destroyed in between.
+            let snatch_guard = device.snatchable_lock.read();
+            let mut command_buffer_guard = hub.command_buffers.write();
+
+            // Validate every command buffer before any of them is consumed, and
+            // before a submission index is taken, so that a failed submission
+            // submits nothing and leaves the command buffers with the caller.
+            for (index, &cmb_id) in command_buffer_ids.iter().enumerate() {
+                let cmdbuf = match command_buffer_guard.try_get(cmb_id) {
+                    Ok(Some(cmdbuf)) if !command_buffer_ids[..index].contains(&cmb_id) => cmdbuf,
+                    _ => {
+                        return Err(
+                            QueueSubmitError::InvalidCommandBuffer(cmb_id).in_command_buffer(index)
+                        )
+                    }
+                };
+                validate_command_buffer(cmdbuf, queue_id, &snatch_guard)
+                    .map_err(|err| err.in_command_buffer(index))?;
+            }
+
             let submit_index = device
                 .active_submission_index
                 .fetch_add(1, Ordering::Relaxed)
@@ -1141,11 +1643,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
             let mut used_surface_textures = track::TextureUsageScope::new();
 
-            let snatch_guard = device.snatchable_lock.read();
-
             {
-                let mut command_buffer_guard = hub.command_buffers.write();
-
                 if !command_buffer_ids.is_empty() {
                     profiling::scope!("prepare");
 
@@ -1159,7 +1657,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     }
 
                     // finish all the command buffers first
-                    for &cmb_id in command_buffer_ids {
+                    for (index, &cmb_id) in command_buffer_ids.iter().enumerate() {
                         // we reset the used surface textures every time we use
                         // it, so make sure to set_size on it.
                         used_surface_textures.set_size(hub.textures.read().len());
@@ -1167,13 +1665,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         #[allow(unused_mut)]
                         let mut cmdbuf = match command_buffer_guard.replace_with_error(cmb_id) {
                             Ok(cmdbuf) => cmdbuf,
-                            Err(_) => continue,
+                            Err(_) => {
+                                return Err(QueueSubmitError::InvalidCommandBuffer(cmb_id)
+                                    .in_command_buffer(index))
+                            }
                         };
 
-                        if cmdbuf.device.as_info().id() != queue_id {
-                            return Err(DeviceError::WrongDevice.into());
-                        }
-
                         #[cfg(feature = "trace")]
                         if let Some(ref mut trace) = *device.trace.lock() {
                             trace.add(Action::Submit(
@@ -1188,16 +1685,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                     .unwrap(),
                             ));
                         }
-                        if !cmdbuf.is_finished() {
-                            if let Some(cmdbuf) = Arc::into_inner(cmdbuf) {
-                                device.destroy_command_buffer(cmdbuf);
-                            } else {
-                                panic!(
-                                    "Command buffer cannot be destroyed because is still in use"
-                                );
-                            }
-                            continue;
-                        }
 
                         // optimize the tracked states
                         // cmdbuf.trackers.optimize();
@@ -1211,7 +1698,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                 let raw_buf = match buffer.raw.get(&snatch_guard) {
                                     Some(raw) => raw,
                                     None => {
-                                        return Err(QueueSubmitError::DestroyedBuffer(id));
+                                        return Err(QueueSubmitError::DestroyedBuffer(id)
+                                            .in_command_buffer(index));
                                     }
                                 };
                                 buffer.info.use_at(submit_index);
@@ -1230,7 +1718,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                 } else {
                                     match *buffer.map_state.lock() {
                                         BufferMapState::Idle => (),
-                                        _ => return Err(QueueSubmitError::BufferStillMapped(id)),
+                                        _ => {
+                                            return Err(QueueSubmitError::BufferStillMapped(id)
+                                                .in_command_buffer(index))
+                                        }
                                     }
                                 }
                             }
@@ -1238,7 +1729,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                 let id = texture.info.id();
                                 let should_extend = match texture.inner.get(&snatch_guard) {
                                     None => {
-                                        return Err(QueueSubmitError::DestroyedTexture(id));
+                                        return Err(QueueSubmitError::DestroyedTexture(id)
+                                            .in_command_buffer(index));
                                     }
                                     Some(TextureInner::Native { .. }) => false,
                                     Some(TextureInner::Surface {
@@ -1376,10 +1868,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         let mut trackers = device.trackers.lock();
                         baked
                             .initialize_buffer_memory(&mut *trackers)
-                            .map_err(|err| QueueSubmitError::DestroyedBuffer(err.0))?;
+                            .map_err(|err| {
+                                QueueSubmitError::DestroyedBuffer(err.0).in_command_buffer(index)
+                            })?;
                         baked
                             .initialize_texture_memory(&mut *trackers, device)
-                            .map_err(|err| QueueSubmitError::DestroyedTexture(err.0))?;
+                            .map_err(|err| {
+                                QueueSubmitError::DestroyedTexture(err.0).in_command_buffer(index)
+                            })?;
                         //Note: stateless trackers are not merged:
                         // device already knows these resources exist.
                         CommandBuffer::insert_barriers_from_tracker(
@@ -1431,6 +1927,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
                     log::trace!("Device after submission {}", submit_index);
                 }
+                drop(command_buffer_guard);
             }
 
             let mut pending_writes = device.pending_writes.lock();
@@ -1528,7 +2025,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(closures) => closures,
                 Err(WaitIdleError::Device(err)) => return Err(QueueSubmitError::Queue(err)),
                 Err(WaitIdleError::StuckGpu) => return Err(QueueSubmitError::StuckGpu),
-                Err(WaitIdleError::WrongSubmissionIndex(..)) => unreachable!(),
+                Err(WaitIdleError::WrongSubmissionIndex(..) | WaitIdleError::Timeout) => {
+                    unreachable!()
+                }
             };
 
             // pending_write_resources has been drained, so it's empty, but we
@@ -1559,6 +2058,42 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
     }
 
+    /// The index of the last successful submission to `queue_id`.
+    ///
+    /// Waiting on it waits for everything submitted so far, which is what a
+    /// failed [`Global::queue_submit`] should hand back instead of a
+    /// submission of its own.
+    pub fn queue_get_last_submission_index<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+    ) -> Result<WrappedSubmissionIndex, InvalidQueue> {
+        let hub = A::hub(self);
+        let queue = hub.queues.get(queue_id).map_err(|_| InvalidQueue)?;
+        let device = queue.device.as_ref().unwrap();
+        Ok(WrappedSubmissionIndex {
+            queue_id,
+            index: device.active_submission_index.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Convert the raw timestamp ticks in `raw`, as written by
+    /// `command_encoder_resolve_query_set`, to nanoseconds in `out` using the
+    /// timestamp period of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `raw` and `out` have different lengths.
+    pub fn queue_timestamps_to_nanos<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+        raw: &[u64],
+        out: &mut [f64],
+    ) -> Result<(), InvalidQueue> {
+        let period = self.queue_get_timestamp_period::<A>(queue_id)?;
+        timestamps_to_nanos(period, raw, out);
+        Ok(())
+    }
+
     pub fn queue_on_submitted_work_done<A: HalApi>(
         &self,
         queue_id: QueueId,
@@ -1579,4 +2114,211 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
         Ok(())
     }
+
+    /// Like [`Global::queue_on_submitted_work_done`], but returns a future
+    /// instead of taking a closure.
+    ///
+    /// The future does not drive the device. It only completes once the
+    /// device has been polled past the work submitted so far, for example
+    /// with [`Global::device_poll`]. It resolves to [`InvalidQueue`] right
+    /// away if the queue is invalid, or if the device goes away first.
+    pub fn queue_on_submitted_work_done_future<A: HalApi>(
+        &self,
+        queue_id: QueueId,
+    ) -> SubmittedWorkDoneFuture {
+        let (closure, future) = SubmittedWorkDoneFuture::new();
+        // An invalid queue drops the closure, which fails the future.
+        let _ = self.queue_on_submitted_work_done::<A>(queue_id, closure);
+        future
+    }
+}
+
+/// The offset, data layout and physical size of each level of a tightly
+/// packed mip chain, or `None` if `format` has no single block size.
+fn mip_chain_layout(
+    format: wgt::TextureFormat,
+    dimension: wgt::TextureDimension,
+    full_size: &wgt::Extent3d,
+    mip_level_count: u32,
+) -> Option<Vec<(u64, wgt::ImageDataLayout, wgt::Extent3d)>> {
+    let block_size = format.block_copy_size(None)?;
+    let (block_width, block_height) = format.block_dimensions();
+
+    let mut offset = 0;
+    let levels = (0..mip_level_count)
+        .map(|level| {
+            let size = full_size.mip_level_size(level, dimension);
+            let physical = size.physical_size(format);
+            let layout = wgt::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(physical.width / block_width * block_size),
+                rows_per_image: Some(physical.height / block_height),
+            };
+            let level_offset = offset;
+            offset += mip_level_bytes(&layout, &physical);
+            (level_offset, layout, physical)
+        })
+        .collect();
+    Some(levels)
+}
+
+/// The number of bytes a level laid out by [`mip_chain_layout`] takes.
+fn mip_level_bytes(layout: &wgt::ImageDataLayout, size: &wgt::Extent3d) -> u64 {
+    layout.bytes_per_row.unwrap_or(0) as u64
+        * layout.rows_per_image.unwrap_or(0) as u64
+        * size.depth_or_array_layers as u64
+}
+
+fn timestamps_to_nanos(period: f32, raw: &[u64], out: &mut [f64]) {
+    assert_eq!(
+        raw.len(),
+        out.len(),
+        "raw timestamps and output must have the same length"
+    );
+    let period = period as f64;
+    for (out, &raw) in out.iter_mut().zip(raw) {
+        *out = raw as f64 * period;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    use super::SubmittedWorkDoneFuture;
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn submitted_work_done_future_resolves_once() {
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let (closure, mut future) = SubmittedWorkDoneFuture::new();
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+
+        // This is what polling the device past the submission ends up doing.
+        closure.call();
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            Pin::new(&mut future).poll(&mut cx),
+            Poll::Ready(Ok(()))
+        ));
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+    }
+
+    #[test]
+    fn submitted_work_done_future_fails_on_dropped_closure() {
+        let (closure, mut future) = SubmittedWorkDoneFuture::new();
+        drop(closure);
+
+        let waker = Waker::from(Arc::new(CountingWaker::default()));
+        let mut cx = Context::from_waker(&waker);
+        assert!(matches!(
+            Pin::new(&mut future).poll(&mut cx),
+            Poll::Ready(Err(super::InvalidQueue))
+        ));
+    }
+
+    #[test]
+    fn timestamps_to_nanos() {
+        let period = 83.333;
+        let raw = [0, 1, 12, 1_000_000_007];
+        let mut out = [0.0; 4];
+        super::timestamps_to_nanos(period, &raw, &mut out);
+
+        for (&raw, &nanos) in raw.iter().zip(&out) {
+            assert_eq!(nanos, raw as f64 * period as f64);
+        }
+    }
+
+    #[test]
+    fn mip_chain_layout() {
+        let full_size = wgt::Extent3d {
+            width: 16,
+            height: 8,
+            depth_or_array_layers: 1,
+        };
+        let levels = super::mip_chain_layout(
+            wgt::TextureFormat::Rgba8Unorm,
+            wgt::TextureDimension::D2,
+            &full_size,
+            4,
+        )
+        .unwrap();
+
+        // Levels are 16x8, 8x4, 4x2 and 2x1 texels of 4 bytes each.
+        let offsets = levels
+            .iter()
+            .map(|&(offset, ..)| offset)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, [0, 512, 640, 672]);
+
+        let (_, layout, size) = levels[2];
+        assert_eq!(layout.bytes_per_row, Some(16));
+        assert_eq!(layout.rows_per_image, Some(2));
+        assert_eq!(
+            size,
+            wgt::Extent3d {
+                width: 4,
+                height: 2,
+                depth_or_array_layers: 1,
+            }
+        );
+        assert_eq!(super::mip_level_bytes(&layout, &size), 32);
+
+        // Levels smaller than a block still take up a whole one.
+        let levels = super::mip_chain_layout(
+            wgt::TextureFormat::Bc1RgbaUnorm,
+            wgt::TextureDimension::D2,
+            &wgt::Extent3d {
+                width: 8,
+                height: 8,
+                depth_or_array_layers: 1,
+            },
+            4,
+        )
+        .unwrap();
+        let offsets = levels
+            .iter()
+            .map(|&(offset, ..)| offset)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, [0, 32, 40, 48]);
+        let (_, layout, size) = levels[3];
+        assert_eq!(layout.bytes_per_row, Some(8));
+        assert_eq!(layout.rows_per_image, Some(1));
+        assert_eq!(
+            size,
+            wgt::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            }
+        );
+
+        // Combined depth-stencil formats can't be written as a whole.
+        assert!(super::mip_chain_layout(
+            wgt::TextureFormat::Depth24PlusStencil8,
+            wgt::TextureDimension::D2,
+            &full_size,
+            1,
+        )
+        .is_none());
+    }
 }
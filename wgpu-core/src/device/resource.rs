@@ -6,8 +6,8 @@ use crate::{
     device::life::{LifetimeTracker, WaitIdleError},
     device::queue::PendingWrites,
     device::{
-        bgl, AttachmentData, CommandAllocator, DeviceLostInvocation, MissingDownlevelFlags,
-        MissingFeatures, RenderPassContext, CLEANUP_WAIT_MS,
+        bgl, AttachmentData, CommandAllocator, DeviceLostInvocation, DeviceLostReasonFilter,
+        MissingDownlevelFlags, MissingFeatures, RenderPassContext, CLEANUP_WAIT_MS,
     },
     hal_api::HalApi,
     hal_label,
@@ -20,13 +20,12 @@ use crate::{
     instance::Adapter,
     pipeline,
     pool::ResourcePool,
-    registry::Registry,
+    registry::{Registry, ResourceLogEvent, ResourceLogger},
     resource::ResourceInfo,
     resource::{
         self, Buffer, QuerySet, Resource, ResourceType, Sampler, Texture, TextureView,
         TextureViewNotRenderableReason,
     },
-    resource_log,
     snatch::{SnatchGuard, SnatchLock, Snatchable},
     storage::Storage,
     track::{BindGroupStates, TextureSelector, Tracker},
@@ -111,6 +110,18 @@ pub struct Device<A: HalApi> {
     /// using ref-counted references for internal access.
     pub(crate) valid: AtomicBool,
 
+    /// Filter consulted before losing the device in `device_mark_lost`, see
+    /// [`Global::device_set_lost_reason_filter`](crate::global::Global::device_set_lost_reason_filter).
+    pub(crate) lost_reason_filter: Mutex<Option<DeviceLostReasonFilter>>,
+
+    /// Whether [`Device::maintain`] may have something to do.
+    ///
+    /// This is set whenever the life tracker is locked, since that is how
+    /// all work that needs maintaining gets queued, and cleared by `maintain`
+    /// once the life tracker is idle. `Maintain::Poll` skips maintenance
+    /// while it's clear.
+    pub(crate) has_pending_work: AtomicBool,
+
     /// All live resources allocated with this [`Device`].
     ///
     /// Has to be locked temporarily only (locked last)
@@ -129,6 +140,8 @@ pub struct Device<A: HalApi> {
     pub(crate) downlevel: wgt::DownlevelCapabilities,
     pub(crate) instance_flags: wgt::InstanceFlags,
     pub(crate) pending_writes: Mutex<Option<PendingWrites<A>>>,
+    /// The resource logger of the `Global` owning this device.
+    pub(crate) resource_logger: Arc<ResourceLogger>,
     #[cfg(feature = "trace")]
     pub(crate) trace: Mutex<Option<trace::Trace>>,
 }
@@ -146,7 +159,11 @@ impl<A: HalApi> std::fmt::Debug for Device<A> {
 
 impl<A: HalApi> Drop for Device<A> {
     fn drop(&mut self) {
-        resource_log!("Destroy raw Device {:?}", self.info.label());
+        self.log_event(
+            &*self,
+            ResourceLogEvent::Destroyed,
+            format_args!("Destroy raw Device {:?}", self.info.label()),
+        );
         let raw = self.raw.take().unwrap();
         let pending_writes = self.pending_writes.lock().take().unwrap();
         pending_writes.dispose(&raw);
@@ -261,10 +278,13 @@ impl<A: HalApi> Device<A> {
             fence: RwLock::new(Some(fence)),
             snatchable_lock: unsafe { SnatchLock::new() },
             valid: AtomicBool::new(true),
+            lost_reason_filter: Mutex::new(None),
+            has_pending_work: AtomicBool::new(true),
             trackers: Mutex::new(Tracker::new()),
             life_tracker: Mutex::new(life::LifetimeTracker::new()),
             temp_suspected: Mutex::new(Some(life::ResourceMaps::new())),
             bgl_pool: ResourcePool::new(),
+            resource_logger: Arc::default(),
             #[cfg(feature = "trace")]
             trace: Mutex::new(trace_path.and_then(|path| match trace::Trace::new(path) {
                 Ok(mut trace) => {
@@ -297,7 +317,11 @@ impl<A: HalApi> Device<A> {
     }
 
     pub(crate) fn lock_life<'a>(&'a self) -> MutexGuard<'a, LifetimeTracker<A>> {
-        self.life_tracker.lock()
+        let life_tracker = self.life_tracker.lock();
+        // Set while holding the lock, so that `maintain` can't clear it
+        // before the caller's work is queued.
+        self.has_pending_work.store(true, Ordering::Release);
+        life_tracker
     }
 
     /// Check this device for completed commands.
@@ -393,6 +417,10 @@ impl<A: HalApi> Device<A> {
             }
         }
 
+        if self.is_valid() && life_tracker.is_idle() {
+            self.has_pending_work.store(false, Ordering::Release);
+        }
+
         let closures = UserClosures {
             mappings: mapping_closures,
             submissions: submission_closures,
@@ -1556,6 +1584,17 @@ impl<A: HalApi> Device<A> {
             No,
         }
 
+        let flags = entry_map.flags();
+        if flags.contains_invalid_bits() {
+            return Err(binding_model::CreateBindGroupLayoutError::InvalidFlags(
+                flags,
+            ));
+        }
+        self.require_features(flags.required_features())
+            .map_err(|error| {
+                binding_model::CreateBindGroupLayoutError::MissingFeatures(flags, error)
+            })?;
+
         for entry in entry_map.values() {
             use wgt::BindingType as Bt;
 
@@ -1677,6 +1716,19 @@ impl<A: HalApi> Device<A> {
                 );
             }
 
+            if let Bt::Buffer {
+                has_dynamic_offset: true,
+                ..
+            } = entry.ty
+            {
+                if flags.contains(wgt::BindGroupLayoutFlags::UPDATE_AFTER_BIND) {
+                    return Err(binding_model::CreateBindGroupLayoutError::Entry {
+                        binding: entry.binding,
+                        error: BindGroupLayoutEntryError::DynamicOffsetWithUpdateAfterBind,
+                    });
+                }
+            }
+
             if entry.visibility.contains(wgt::ShaderStages::VERTEX) {
                 if writable_storage == WritableStorage::Yes {
                     required_features |= wgt::Features::VERTEX_WRITABLE_STORAGE;
@@ -1709,7 +1761,7 @@ impl<A: HalApi> Device<A> {
                 })?;
         }
 
-        let bgl_flags = conv::bind_group_layout_flags(self.features);
+        let bgl_flags = conv::bind_group_layout_flags(self.features, flags);
 
         let hal_bindings = entry_map.values().copied().collect::<Vec<_>>();
         let label = label.to_hal(self.instance_flags);
@@ -1975,6 +2027,23 @@ impl<A: HalApi> Device<A> {
                 .entries
                 .get(binding)
                 .ok_or(Error::MissingBindingDeclaration(binding))?;
+            // Resources whose creation failed make the bind group invalid
+            // too, so point at the binding rather than the resource.
+            let invalid = match entry.resource {
+                Br::Buffer(ref bb) => buffer_guard.get(bb.buffer_id).is_err(),
+                Br::BufferArray(ref bindings_array) => bindings_array
+                    .iter()
+                    .any(|bb| buffer_guard.get(bb.buffer_id).is_err()),
+                Br::Sampler(id) => sampler_guard.get(id).is_err(),
+                Br::SamplerArray(ref ids) => ids.iter().any(|&id| sampler_guard.get(id).is_err()),
+                Br::TextureView(id) => texture_view_guard.get(id).is_err(),
+                Br::TextureViewArray(ref ids) => {
+                    ids.iter().any(|&id| texture_view_guard.get(id).is_err())
+                }
+            };
+            if invalid {
+                return Err(Error::InvalidResource { binding });
+            }
             let (res_index, count) = match entry.resource {
                 Br::Buffer(ref bb) => {
                     let bb = Self::create_buffer_binding(
@@ -2235,7 +2304,11 @@ impl<A: HalApi> Device<A> {
             .aspects()
             .contains(hal::FormatAspects::DEPTH | hal::FormatAspects::STENCIL)
         {
-            return Err(Error::DepthStencilAspect);
+            return Err(Error::InvalidAspectForFormat {
+                binding,
+                format: view.desc.format,
+                aspect: view.desc.range.aspect,
+            });
         }
         match decl.ty {
             wgt::BindingType::Texture {
@@ -2562,6 +2635,19 @@ impl<A: HalApi> Device<A> {
             let stage = wgt::ShaderStages::COMPUTE;
 
             if let Some(ref interface) = shader_module.interface {
+                if let Some((dimension, requested, limit)) = interface
+                    .compute_workgroup_size(&desc.stage.entry_point)
+                    .and_then(|size| pipeline::WorkgroupSizeDimension::check(size, &self.limits))
+                {
+                    return Err(
+                        pipeline::CreateComputePipelineError::WorkgroupSizeLimitExceeded {
+                            dimension,
+                            requested,
+                            limit,
+                        },
+                    );
+                }
+
                 let _ = interface.check_stage(
                     &mut binding_layout_source,
                     &mut shader_binding_sizes,
@@ -3246,6 +3332,18 @@ impl<A: HalApi> Device<A> {
         }
     }
 
+    /// Check whether the GPU is done with the submission `submission_index`,
+    /// without waiting for it.
+    pub(crate) fn is_submission_done(
+        &self,
+        submission_index: SubmissionIndex,
+    ) -> Result<bool, DeviceError> {
+        let guard = self.fence.read();
+        let fence = guard.as_ref().unwrap();
+        let last_done_index = unsafe { self.raw.as_ref().unwrap().get_fence_value(fence)? };
+        Ok(last_done_index >= submission_index)
+    }
+
     pub(crate) fn wait_for_submit(
         &self,
         submission_index: SubmissionIndex,
@@ -3317,20 +3415,28 @@ impl<A: HalApi> Device<A> {
         })
     }
 
-    pub(crate) fn lose(&self, message: &str) {
+    pub(crate) fn lose(&self, reason: DeviceLostReason, message: &str) {
         // Follow the steps at https://gpuweb.github.io/gpuweb/#lose-the-device.
 
         // Mark the device explicitly as invalid. This is checked in various
         // places to prevent new work from being submitted.
-        self.valid.store(false, Ordering::Release);
+        let was_valid = self.valid.swap(false, Ordering::AcqRel);
 
         // 1) Resolve the GPUDevice device.lost promise.
+        //
+        // If the device was already invalid but the closure is still around,
+        // it was destroyed and `maintain` has not gotten to the closure yet.
+        // The first reason wins, so report it as destroyed.
         let mut life_lock = self.lock_life();
         let closure = life_lock.device_lost_closure.take();
         if let Some(device_lost_closure) = closure {
             // It's important to not hold the lock while calling the closure.
             drop(life_lock);
-            device_lost_closure.call(DeviceLostReason::Unknown, message.to_string());
+            if was_valid {
+                device_lost_closure.call(reason, message.to_string());
+            } else {
+                device_lost_closure.call(DeviceLostReason::Destroyed, String::new());
+            }
             life_lock = self.lock_life();
         }
 
@@ -3348,19 +3454,6 @@ impl<A: HalApi> Device<A> {
 }
 
 impl<A: HalApi> Device<A> {
-    pub(crate) fn destroy_command_buffer(&self, mut cmd_buf: command::CommandBuffer<A>) {
-        let mut baked = cmd_buf.extract_baked_commands();
-        unsafe {
-            baked.encoder.reset_all(baked.list.into_iter());
-        }
-        unsafe {
-            self.raw
-                .as_ref()
-                .unwrap()
-                .destroy_command_encoder(baked.encoder);
-        }
-    }
-
     /// Wait for idle and remove resources that we can, before we die.
     pub(crate) fn prepare_to_die(&self) {
         self.pending_writes.lock().as_mut().unwrap().deactivate();
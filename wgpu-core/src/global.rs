@@ -1,14 +1,23 @@
 use std::{marker::PhantomData, sync::Arc};
 
-use wgt::Backend;
+use parking_lot::Mutex;
 
+use thiserror::Error;
+use wgt::{Backend, Backends};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::device::poll_thread::PollThread;
 use crate::{
+    api_log,
     hal_api::HalApi,
     hub::{HubReport, Hubs},
-    id::SurfaceId,
+    id::{DeviceId, SurfaceId},
     identity::GlobalIdentityHandlerFactory,
     instance::{Instance, Surface},
-    registry::{Registry, RegistryReport},
+    registry::{
+        LifetimeHook, Registry, RegistryReport, ResourceLifetimeHook, ResourceLogger,
+        ResourceLoggerFn,
+    },
     resource_log,
     storage::Element,
 };
@@ -43,26 +52,125 @@ impl GlobalReport {
             _ => panic!("HubReport is not supported on this backend"),
         }
     }
+
+    /// Check that no registry in this report holds any resources.
+    ///
+    /// Registries named in `allowed` (for example `"surfaces"` or
+    /// `"adapters"`) are skipped, which lets callers keep some resources alive
+    /// intentionally. Surfaces are reported under [`Backend::Empty`].
+    pub fn check_leaks(&self, allowed: &[&str]) -> Result<(), LeakReport> {
+        let mut leaks = Vec::new();
+        let mut check = |backend: Backend, name: &'static str, report: &RegistryReport| {
+            if !report.is_empty() && !allowed.contains(&name) {
+                let count = report.num_allocated.max(report.num_kept_from_user);
+                leaks.push((backend, name, count));
+            }
+        };
+
+        check(Backend::Empty, "surfaces", &self.surfaces);
+        #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
+        if let Some(ref hub) = self.vulkan {
+            for (name, report) in hub.registries() {
+                check(Backend::Vulkan, name, report);
+            }
+        }
+        #[cfg(all(feature = "metal", any(target_os = "macos", target_os = "ios")))]
+        if let Some(ref hub) = self.metal {
+            for (name, report) in hub.registries() {
+                check(Backend::Metal, name, report);
+            }
+        }
+        #[cfg(all(feature = "dx12", windows))]
+        if let Some(ref hub) = self.dx12 {
+            for (name, report) in hub.registries() {
+                check(Backend::Dx12, name, report);
+            }
+        }
+        #[cfg(feature = "gles")]
+        if let Some(ref hub) = self.gl {
+            for (name, report) in hub.registries() {
+                check(Backend::Gl, name, report);
+            }
+        }
+
+        if leaks.is_empty() {
+            Ok(())
+        } else {
+            Err(LeakReport { leaks })
+        }
+    }
+}
+
+/// The registries found to still hold resources by [`Global::assert_no_leaks`].
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("Leaked resources (backend, registry, count): {leaks:?}")]
+pub struct LeakReport {
+    /// A `(backend, registry_name, count)` entry for every non-empty registry.
+    pub leaks: Vec<(Backend, &'static str, usize)>,
 }
 
 pub struct Global<G: GlobalIdentityHandlerFactory> {
     pub instance: Instance,
     pub surfaces: Registry<SurfaceId, Surface>,
     pub(crate) hubs: Hubs,
+    lifetime_hook: Arc<LifetimeHook>,
+    pub(crate) resource_logger: Arc<ResourceLogger>,
+    instance_desc: wgt::InstanceDescriptor,
+    backend_priority: Mutex<Vec<Backend>>,
+    /// The threads started with [`Global::start_poll_thread`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) poll_threads: Mutex<Vec<(DeviceId, PollThread)>>,
     _phantom: PhantomData<G>,
 }
 
+/// The order in which backends are considered when no priority has been set,
+/// or for the backends a priority doesn't list.
+const DEFAULT_BACKEND_ORDER: [Backend; 6] = [
+    Backend::Vulkan,
+    Backend::Metal,
+    Backend::Dx12,
+    Backend::Gl,
+    Backend::BrowserWebGpu,
+    Backend::Empty,
+];
+
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
     pub fn new(name: &str, factory: G, instance_desc: wgt::InstanceDescriptor) -> Self {
         profiling::scope!("Global::new");
+        let lifetime_hook = Arc::new(LifetimeHook::default());
         Self {
-            instance: Instance::new(name, instance_desc),
-            surfaces: Registry::without_backend(&factory),
-            hubs: Hubs::new(&factory),
+            instance: Instance::new(name, instance_desc.clone()),
+            surfaces: Registry::without_backend(&factory, &lifetime_hook),
+            hubs: Hubs::new(&factory, &lifetime_hook),
+            lifetime_hook,
+            resource_logger: Arc::default(),
+            instance_desc,
+            backend_priority: Mutex::new(DEFAULT_BACKEND_ORDER.to_vec()),
+            #[cfg(not(target_arch = "wasm32"))]
+            poll_threads: Mutex::new(Vec::new()),
             _phantom: PhantomData,
         }
     }
 
+    /// Like [`Global::new`], but only initializes the backends which are both
+    /// requested by `instance_desc` and contained in `backends`.
+    ///
+    /// The instance fields of all other backends are left as `None`, so they
+    /// are not reported by [`Global::generate_report`] or
+    /// [`Global::initialized_backends`].
+    pub fn new_with_backends(
+        name: &str,
+        factory: G,
+        instance_desc: wgt::InstanceDescriptor,
+        backends: Backends,
+    ) -> Self {
+        let instance_desc = wgt::InstanceDescriptor {
+            backends: instance_desc.backends & backends,
+            ..instance_desc
+        };
+        Self::new(name, factory, instance_desc)
+    }
+
     /// # Safety
     ///
     /// Refer to the creation of wgpu-hal Instance for every backend.
@@ -72,12 +180,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         hal_instance: A::Instance,
     ) -> Self {
         profiling::scope!("Global::new");
-        Self {
-            instance: A::create_instance_from_hal(name, hal_instance),
-            surfaces: Registry::without_backend(&factory),
-            hubs: Hubs::new(&factory),
-            _phantom: PhantomData,
-        }
+        unsafe { Self::from_instance(factory, A::create_instance_from_hal(name, hal_instance)) }
     }
 
     /// # Safety
@@ -92,11 +195,63 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     /// - The raw handles obtained from the Instance must not be manually destroyed
     pub unsafe fn from_instance(factory: G, instance: Instance) -> Self {
         profiling::scope!("Global::new");
-        Self {
+        let lifetime_hook = Arc::new(LifetimeHook::default());
+        let instance_desc = wgt::InstanceDescriptor {
+            flags: instance.flags,
+            ..Default::default()
+        };
+        let mut global = Self {
             instance,
-            surfaces: Registry::without_backend(&factory),
-            hubs: Hubs::new(&factory),
+            surfaces: Registry::without_backend(&factory, &lifetime_hook),
+            hubs: Hubs::new(&factory, &lifetime_hook),
+            lifetime_hook,
+            resource_logger: Arc::default(),
+            instance_desc,
+            backend_priority: Mutex::new(DEFAULT_BACKEND_ORDER.to_vec()),
+            #[cfg(not(target_arch = "wasm32"))]
+            poll_threads: Mutex::new(Vec::new()),
             _phantom: PhantomData,
+        };
+        // There is no descriptor to go by, so describe what is actually there.
+        global.instance_desc.backends = global.initialized_backends();
+        global
+    }
+
+    /// The descriptor this instance was created with.
+    ///
+    /// For a `Global` created from an existing [`Instance`], `backends` lists
+    /// the backends present in it and the remaining fields are their defaults,
+    /// apart from `flags`.
+    pub fn instance_descriptor(&self) -> &wgt::InstanceDescriptor {
+        &self.instance_desc
+    }
+
+    /// Set the order in which backends are preferred.
+    ///
+    /// This orders the adapters returned by [`Global::enumerate_adapters`],
+    /// and decides between equally suitable adapters of different backends in
+    /// [`Global::request_adapter`]. Backends not in `order` come after the
+    /// listed ones, in the default order of Vulkan, Metal, Dx12 and then GL.
+    pub fn set_backend_priority(&self, order: &[Backend]) {
+        api_log!("Global::set_backend_priority {order:?}");
+
+        let mut priority = Vec::with_capacity(DEFAULT_BACKEND_ORDER.len());
+        for &backend in order.iter().chain(DEFAULT_BACKEND_ORDER.iter()) {
+            if !priority.contains(&backend) {
+                priority.push(backend);
+            }
+        }
+        *self.backend_priority.lock() = priority;
+    }
+
+    /// The position of `backend` in the backend priority, lower is preferred.
+    pub(crate) fn backend_rank(&self) -> impl Fn(Backend) -> usize {
+        let priority = self.backend_priority.lock().clone();
+        move |backend| {
+            priority
+                .iter()
+                .position(|&b| b == backend)
+                .unwrap_or(priority.len())
         }
     }
 
@@ -107,33 +262,145 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         hub.clear(&surfaces_locked, false);
     }
 
+    /// Release every resource owned by `device_id` and mark the device as
+    /// lost, without touching other devices on the same backend.
+    ///
+    /// This is useful for multi-device applications which lose one GPU and
+    /// need to tear down what was created on it. The device-lost closure, if
+    /// any, is called before any locks are acquired.
+    pub fn clear_device_resources<A: HalApi>(&self, device_id: DeviceId) {
+        api_log!("Global::clear_device_resources {device_id:?}");
+
+        let hub = A::hub(self);
+        if let Ok(device) = hub.devices.get(device_id) {
+            device.lose(
+                wgt::DeviceLostReason::Unknown,
+                "Device resources were cleared.",
+            );
+        }
+
+        let surfaces_locked = self.surfaces.read();
+        hub.clear_device(device_id, &surfaces_locked);
+    }
+
+    /// Install a hook which is called whenever a resource is registered under a
+    /// new id, or its id is released by the user.
+    ///
+    /// This is meant for tracking down resource leaks and is disabled by
+    /// default. Installing a new hook replaces the previous one.
+    ///
+    /// The hook may be called from any thread, and must not create or drop
+    /// resources on this `Global`, nor replace the hook.
+    pub fn set_resource_lifetime_hook(&self, hook: ResourceLifetimeHook) {
+        self.lifetime_hook.set(Some(hook));
+    }
+
+    /// Install a logger which receives a [`ResourceLogRecord`] wherever
+    /// resources are logged, such as when they are created or their raw
+    /// resource is destroyed.
+    ///
+    /// While a logger is installed, these events are no longer logged through
+    /// the `log` crate. Installing a new logger replaces the previous one.
+    ///
+    /// The logger may be called from any thread, and while internal locks are
+    /// held. It must not call into this `Global`.
+    pub fn set_resource_logger(&self, logger: ResourceLoggerFn) {
+        self.resource_logger.set(logger);
+    }
+
+    /// Check that every registry of every initialized backend is empty.
+    ///
+    /// This is meant to be called at test teardown. See
+    /// [`GlobalReport::check_leaks`] for the meaning of `allowed`.
+    pub fn assert_no_leaks(&self, allowed: &[&str]) -> Result<(), LeakReport> {
+        self.generate_report().check_leaks(allowed)
+    }
+
+    /// Return the set of backends whose instance was successfully initialized.
+    pub fn initialized_backends(&self) -> Backends {
+        #[allow(unused_mut)]
+        let mut backends = Backends::empty();
+        #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
+        backends.set(Backends::VULKAN, self.instance.vulkan.is_some());
+        #[cfg(all(feature = "metal", any(target_os = "macos", target_os = "ios")))]
+        backends.set(Backends::METAL, self.instance.metal.is_some());
+        #[cfg(all(feature = "dx12", windows))]
+        backends.set(Backends::DX12, self.instance.dx12.is_some());
+        #[cfg(feature = "gles")]
+        backends.set(Backends::GL, self.instance.gl.is_some());
+        backends
+    }
+
     pub fn generate_report(&self) -> GlobalReport {
         GlobalReport {
             surfaces: self.surfaces.generate_report(),
             #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
-            vulkan: if self.instance.vulkan.is_some() {
+            vulkan: self.generate_report_for_backend(Backend::Vulkan),
+            #[cfg(all(feature = "metal", any(target_os = "macos", target_os = "ios")))]
+            metal: self.generate_report_for_backend(Backend::Metal),
+            #[cfg(all(feature = "dx12", windows))]
+            dx12: self.generate_report_for_backend(Backend::Dx12),
+            #[cfg(feature = "gles")]
+            gl: self.generate_report_for_backend(Backend::Gl),
+        }
+    }
+
+    /// Like [`Global::generate_report`], but without locking any registry.
+    ///
+    /// The counts are read from atomics with relaxed ordering, so while other
+    /// threads create or drop resources they may be slightly stale, and the
+    /// counts of different registries, or even of a single one, may not add
+    /// up. This is meant for monitoring, such as an on-screen overlay, where
+    /// [`Global::generate_report`] would contend with the render thread.
+    pub fn generate_report_lossy(&self) -> GlobalReport {
+        GlobalReport {
+            surfaces: self.surfaces.generate_report_lossy(),
+            #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
+            vulkan: self
+                .instance
+                .vulkan
+                .as_ref()
+                .map(|_| self.hubs.vulkan.generate_report_lossy()),
+            #[cfg(all(feature = "metal", any(target_os = "macos", target_os = "ios")))]
+            metal: self
+                .instance
+                .metal
+                .as_ref()
+                .map(|_| self.hubs.metal.generate_report_lossy()),
+            #[cfg(all(feature = "dx12", windows))]
+            dx12: self
+                .instance
+                .dx12
+                .as_ref()
+                .map(|_| self.hubs.dx12.generate_report_lossy()),
+            #[cfg(feature = "gles")]
+            gl: self
+                .instance
+                .gl
+                .as_ref()
+                .map(|_| self.hubs.gl.generate_report_lossy()),
+        }
+    }
+
+    /// Generate the report of a single backend's hub.
+    ///
+    /// Returns `None` if the backend is not compiled in or its instance was
+    /// not initialized.
+    pub fn generate_report_for_backend(&self, backend: Backend) -> Option<HubReport> {
+        match backend {
+            #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
+            Backend::Vulkan if self.instance.vulkan.is_some() => {
                 Some(self.hubs.vulkan.generate_report())
-            } else {
-                None
-            },
+            }
             #[cfg(all(feature = "metal", any(target_os = "macos", target_os = "ios")))]
-            metal: if self.instance.metal.is_some() {
+            Backend::Metal if self.instance.metal.is_some() => {
                 Some(self.hubs.metal.generate_report())
-            } else {
-                None
-            },
+            }
             #[cfg(all(feature = "dx12", windows))]
-            dx12: if self.instance.dx12.is_some() {
-                Some(self.hubs.dx12.generate_report())
-            } else {
-                None
-            },
+            Backend::Dx12 if self.instance.dx12.is_some() => Some(self.hubs.dx12.generate_report()),
             #[cfg(feature = "gles")]
-            gl: if self.instance.gl.is_some() {
-                Some(self.hubs.gl.generate_report())
-            } else {
-                None
-            },
+            Backend::Gl if self.instance.gl.is_some() => Some(self.hubs.gl.generate_report()),
+            _ => None,
         }
     }
 }
@@ -142,6 +409,13 @@ impl<G: GlobalIdentityHandlerFactory> Drop for Global<G> {
     fn drop(&mut self) {
         profiling::scope!("Global::drop");
         resource_log!("Global::drop");
+
+        // Nothing may poll the devices while they're destroyed.
+        #[cfg(not(target_arch = "wasm32"))]
+        for (_, thread) in self.poll_threads.get_mut().drain(..) {
+            thread.stop();
+        }
+
         let mut surfaces_locked = self.surfaces.write();
 
         // destroy hubs before the instance gets dropped
@@ -165,10 +439,14 @@ impl<G: GlobalIdentityHandlerFactory> Drop for Global<G> {
         // destroy surfaces
         for element in surfaces_locked.map.drain(..) {
             if let Element::Occupied(arc_surface, _) = element {
+                let id = arc_surface.info.id();
                 if let Some(surface) = Arc::into_inner(arc_surface) {
                     self.instance.destroy_surface(surface);
                 } else {
-                    panic!("Surface cannot be destroyed because is still in use");
+                    // Something outside of the `Global` still holds the
+                    // surface, so it can't be destroyed. Leaking it is
+                    // better than aborting.
+                    log::warn!("Surface {id:?} is still in use and will be leaked");
                 }
             }
         }
@@ -189,3 +467,193 @@ fn _test_send_sync(global: &Global<crate::identity::IdentityManagerFactory>) {
     fn test_internal<T: Send + Sync>(_: T) {}
     test_internal(global)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Global;
+    use crate::identity::IdentityManagerFactory;
+
+    #[test]
+    fn new_with_backends() {
+        let global = Global::new_with_backends(
+            "test",
+            IdentityManagerFactory,
+            wgt::InstanceDescriptor::default(),
+            wgt::Backends::GL,
+        );
+
+        // GL may still fail to initialize, but nothing else may be.
+        assert!(wgt::Backends::GL.contains(global.initialized_backends()));
+
+        #[allow(unused_variables)]
+        let report = global.generate_report();
+        #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
+        assert!(report.vulkan.is_none());
+        #[cfg(all(feature = "metal", any(target_os = "macos", target_os = "ios")))]
+        assert!(report.metal.is_none());
+        #[cfg(all(feature = "dx12", windows))]
+        assert!(report.dx12.is_none());
+    }
+
+    #[test]
+    fn drop_with_surface_in_use() {
+        use crate::{
+            any_surface::AnySurface,
+            instance::{HalSurface, Surface},
+            resource::ResourceInfo,
+        };
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+
+        let global = Global::new(
+            "test",
+            IdentityManagerFactory,
+            wgt::InstanceDescriptor {
+                backends: wgt::Backends::empty(),
+                ..Default::default()
+            },
+        );
+
+        let surface = Surface {
+            presentation: Mutex::new(None),
+            capabilities: Default::default(),
+            info: ResourceInfo::new("<Surface>"),
+            raw: AnySurface::new(HalSurface::<hal::api::Empty> {
+                raw: Arc::new(hal::empty::Context),
+            }),
+        };
+        let (id, _) = global
+            .surfaces
+            .prepare::<IdentityManagerFactory>(())
+            .assign(surface);
+        assert_eq!(global.surface_strong_count(id), 1);
+
+        let held = global.surfaces.get(id).unwrap();
+        assert_eq!(global.surface_strong_count(id), 2);
+
+        // The surface is leaked rather than destroyed.
+        drop(global);
+        assert_eq!(Arc::strong_count(&held), 1);
+    }
+
+    #[test]
+    fn surface_strong_count_after_drop() {
+        use crate::{
+            any_surface::AnySurface,
+            instance::{HalSurface, Surface},
+            resource::ResourceInfo,
+        };
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+
+        let global = Global::new(
+            "test",
+            IdentityManagerFactory,
+            wgt::InstanceDescriptor {
+                backends: wgt::Backends::empty(),
+                ..Default::default()
+            },
+        );
+
+        let surface = Surface {
+            presentation: Mutex::new(None),
+            capabilities: Default::default(),
+            info: ResourceInfo::new("<Surface>"),
+            raw: AnySurface::new(HalSurface::<hal::api::Empty> {
+                raw: Arc::new(hal::empty::Context),
+            }),
+        };
+        let (id, _) = global
+            .surfaces
+            .prepare::<IdentityManagerFactory>(())
+            .assign(surface);
+        assert_eq!(global.surface_strong_count(id), 1);
+
+        // An id which has been dropped has no references left.
+        drop(global.surfaces.unregister(id));
+        assert_eq!(global.surface_strong_count(id), 0);
+    }
+
+    #[test]
+    fn backend_priority() {
+        use wgt::Backend;
+
+        let global = Global::new(
+            "test",
+            IdentityManagerFactory,
+            wgt::InstanceDescriptor {
+                backends: wgt::Backends::empty(),
+                ..Default::default()
+            },
+        );
+
+        let rank = global.backend_rank();
+        assert!(rank(Backend::Vulkan) < rank(Backend::Gl));
+
+        global.set_backend_priority(&[Backend::Gl, Backend::Dx12]);
+        let rank = global.backend_rank();
+        let mut backends = [Backend::Vulkan, Backend::Metal, Backend::Dx12, Backend::Gl];
+        backends.sort_by_key(|&backend| rank(backend));
+        assert_eq!(
+            backends,
+            [Backend::Gl, Backend::Dx12, Backend::Vulkan, Backend::Metal]
+        );
+    }
+
+    #[test]
+    fn instance_descriptor() {
+        let flags = wgt::InstanceFlags::DEBUG | wgt::InstanceFlags::VALIDATION;
+        let global = Global::new(
+            "test",
+            IdentityManagerFactory,
+            wgt::InstanceDescriptor {
+                backends: wgt::Backends::GL,
+                flags,
+                gles_minor_version: wgt::Gles3MinorVersion::Version1,
+                ..Default::default()
+            },
+        );
+
+        let desc = global.instance_descriptor();
+        assert_eq!(desc.backends, wgt::Backends::GL);
+        assert_eq!(desc.flags, flags);
+        assert_eq!(desc.gles_minor_version, wgt::Gles3MinorVersion::Version1);
+    }
+
+    #[test]
+    fn generate_report_for_backend() {
+        let global = Global::new(
+            "test",
+            IdentityManagerFactory,
+            wgt::InstanceDescriptor::default(),
+        );
+
+        #[allow(unused_variables)]
+        let report = global.generate_report();
+        #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
+        assert_eq!(
+            global.generate_report_for_backend(wgt::Backend::Vulkan),
+            report.vulkan
+        );
+        #[cfg(all(feature = "metal", any(target_os = "macos", target_os = "ios")))]
+        assert_eq!(
+            global.generate_report_for_backend(wgt::Backend::Metal),
+            report.metal
+        );
+        #[cfg(all(feature = "dx12", windows))]
+        assert_eq!(
+            global.generate_report_for_backend(wgt::Backend::Dx12),
+            report.dx12
+        );
+        #[cfg(feature = "gles")]
+        assert_eq!(
+            global.generate_report_for_backend(wgt::Backend::Gl),
+            report.gl
+        );
+
+        assert_eq!(
+            global.generate_report_for_backend(wgt::Backend::Empty),
+            None
+        );
+    }
+}
@@ -118,12 +118,14 @@ use crate::{
     id,
     identity::GlobalIdentityHandlerFactory,
     instance::{Adapter, HalSurface, Surface},
-    pipeline::{ComputePipeline, RenderPipeline, ShaderModule},
-    registry::{Registry, RegistryReport},
-    resource::{Buffer, QuerySet, Sampler, StagingBuffer, Texture, TextureView},
+    pipeline::{ComputePipeline, RenderPipeline, ShaderCompilationMessage, ShaderModule},
+    registry::{LifetimeHook, Registry, RegistryReport},
+    resource::{Buffer, QuerySet, Resource, Sampler, StagingBuffer, Texture, TextureView},
     storage::{Element, Storage},
+    FastHashMap,
 };
-use std::fmt::Debug;
+use parking_lot::Mutex;
+use std::{fmt::Debug, sync::Arc};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct HubReport {
@@ -149,6 +151,28 @@ impl HubReport {
     pub fn is_empty(&self) -> bool {
         self.adapters.is_empty()
     }
+
+    /// The report of every registry in this hub, along with its name.
+    pub fn registries(&self) -> [(&'static str, &RegistryReport); 16] {
+        [
+            ("adapters", &self.adapters),
+            ("devices", &self.devices),
+            ("queues", &self.queues),
+            ("pipeline_layouts", &self.pipeline_layouts),
+            ("shader_modules", &self.shader_modules),
+            ("bind_group_layouts", &self.bind_group_layouts),
+            ("bind_groups", &self.bind_groups),
+            ("command_buffers", &self.command_buffers),
+            ("render_bundles", &self.render_bundles),
+            ("render_pipelines", &self.render_pipelines),
+            ("compute_pipelines", &self.compute_pipelines),
+            ("query_sets", &self.query_sets),
+            ("buffers", &self.buffers),
+            ("textures", &self.textures),
+            ("texture_views", &self.texture_views),
+            ("samplers", &self.samplers),
+        ]
+    }
 }
 
 #[allow(rustdoc::private_intra_doc_links)]
@@ -182,6 +206,11 @@ pub struct Hub<A: HalApi> {
     pub queues: Registry<id::QueueId, Queue<A>>,
     pub pipeline_layouts: Registry<id::PipelineLayoutId, PipelineLayout<A>>,
     pub shader_modules: Registry<id::ShaderModuleId, ShaderModule<A>>,
+    /// The compilation error of each shader module id in `shader_modules`
+    /// which failed to compile, since error ids have no `ShaderModule` to
+    /// keep it in.
+    pub(crate) shader_module_errors:
+        Mutex<FastHashMap<id::ShaderModuleId, ShaderCompilationMessage>>,
     pub bind_group_layouts: Registry<id::BindGroupLayoutId, BindGroupLayout<A>>,
     pub bind_groups: Registry<id::BindGroupId, BindGroup<A>>,
     pub command_buffers: Registry<id::CommandBufferId, CommandBuffer<A>>,
@@ -197,25 +226,29 @@ pub struct Hub<A: HalApi> {
 }
 
 impl<A: HalApi> Hub<A> {
-    fn new<F: GlobalIdentityHandlerFactory>(factory: &F) -> Self {
+    fn new<F: GlobalIdentityHandlerFactory>(
+        factory: &F,
+        lifetime_hook: &Arc<LifetimeHook>,
+    ) -> Self {
         Self {
-            adapters: Registry::new(A::VARIANT, factory),
-            devices: Registry::new(A::VARIANT, factory),
-            queues: Registry::new(A::VARIANT, factory),
-            pipeline_layouts: Registry::new(A::VARIANT, factory),
-            shader_modules: Registry::new(A::VARIANT, factory),
-            bind_group_layouts: Registry::new(A::VARIANT, factory),
-            bind_groups: Registry::new(A::VARIANT, factory),
-            command_buffers: Registry::new(A::VARIANT, factory),
-            render_bundles: Registry::new(A::VARIANT, factory),
-            render_pipelines: Registry::new(A::VARIANT, factory),
-            compute_pipelines: Registry::new(A::VARIANT, factory),
-            query_sets: Registry::new(A::VARIANT, factory),
-            buffers: Registry::new(A::VARIANT, factory),
-            staging_buffers: Registry::new(A::VARIANT, factory),
-            textures: Registry::new(A::VARIANT, factory),
-            texture_views: Registry::new(A::VARIANT, factory),
-            samplers: Registry::new(A::VARIANT, factory),
+            adapters: Registry::new(A::VARIANT, factory, lifetime_hook),
+            devices: Registry::new(A::VARIANT, factory, lifetime_hook),
+            queues: Registry::new(A::VARIANT, factory, lifetime_hook),
+            pipeline_layouts: Registry::new(A::VARIANT, factory, lifetime_hook),
+            shader_modules: Registry::new(A::VARIANT, factory, lifetime_hook),
+            shader_module_errors: Mutex::default(),
+            bind_group_layouts: Registry::new(A::VARIANT, factory, lifetime_hook),
+            bind_groups: Registry::new(A::VARIANT, factory, lifetime_hook),
+            command_buffers: Registry::new(A::VARIANT, factory, lifetime_hook),
+            render_bundles: Registry::new(A::VARIANT, factory, lifetime_hook),
+            render_pipelines: Registry::new(A::VARIANT, factory, lifetime_hook),
+            compute_pipelines: Registry::new(A::VARIANT, factory, lifetime_hook),
+            query_sets: Registry::new(A::VARIANT, factory, lifetime_hook),
+            buffers: Registry::new(A::VARIANT, factory, lifetime_hook),
+            staging_buffers: Registry::new(A::VARIANT, factory, lifetime_hook),
+            textures: Registry::new(A::VARIANT, factory, lifetime_hook),
+            texture_views: Registry::new(A::VARIANT, factory, lifetime_hook),
+            samplers: Registry::new(A::VARIANT, factory, lifetime_hook),
         }
     }
 
@@ -236,18 +269,19 @@ impl<A: HalApi> Hub<A> {
             }
         }
 
-        self.command_buffers.write().map.clear();
-        self.samplers.write().map.clear();
-        self.texture_views.write().map.clear();
-        self.textures.write().map.clear();
-        self.buffers.write().map.clear();
-        self.bind_groups.write().map.clear();
-        self.shader_modules.write().map.clear();
-        self.bind_group_layouts.write().map.clear();
-        self.pipeline_layouts.write().map.clear();
-        self.compute_pipelines.write().map.clear();
-        self.render_pipelines.write().map.clear();
-        self.query_sets.write().map.clear();
+        self.command_buffers.write().clear();
+        self.samplers.write().clear();
+        self.texture_views.write().clear();
+        self.textures.write().clear();
+        self.buffers.write().clear();
+        self.bind_groups.write().clear();
+        self.shader_modules.write().clear();
+        self.shader_module_errors.lock().clear();
+        self.bind_group_layouts.write().clear();
+        self.pipeline_layouts.write().clear();
+        self.compute_pipelines.write().clear();
+        self.render_pipelines.write().clear();
+        self.query_sets.write().clear();
 
         for element in surface_guard.map.iter() {
             if let Element::Occupied(ref surface, _epoch) = *element {
@@ -263,15 +297,99 @@ impl<A: HalApi> Hub<A> {
             }
         }
 
-        self.queues.write().map.clear();
-        devices.map.clear();
+        self.queues.write().clear();
+        devices.clear();
 
         if with_adapters {
             drop(devices);
-            self.adapters.write().map.clear();
+            self.adapters.write().clear();
         }
     }
 
+    /// Release every resource owned by the device `device_id`, and the device
+    /// itself, leaving the resources of other devices in this hub untouched.
+    ///
+    /// The ids of the released resources stay registered as errors, so the
+    /// user can still drop them, and using them fails instead of panicking.
+    ///
+    /// Locks are acquired in the same order as [`Hub::clear`].
+    pub(crate) fn clear_device(
+        &self,
+        device_id: id::DeviceId,
+        surface_guard: &Storage<Surface, id::SurfaceId>,
+    ) {
+        use hal::Surface;
+
+        let mut devices = self.devices.write();
+        match devices.try_get(device_id) {
+            Ok(Some(device)) => device.prepare_to_die(),
+            _ => return,
+        }
+
+        let owned = |device: &Device<A>| device.as_info().id() == device_id;
+
+        self.command_buffers
+            .write()
+            .retain(|cmd_buf| !owned(&cmd_buf.device));
+        self.samplers
+            .write()
+            .retain(|sampler| !owned(&sampler.device));
+        self.texture_views
+            .write()
+            .retain(|view| !owned(&view.device));
+        self.textures
+            .write()
+            .retain(|texture| !owned(&texture.device));
+        self.buffers.write().retain(|buffer| !owned(&buffer.device));
+        self.bind_groups
+            .write()
+            .retain(|bind_group| !owned(&bind_group.device));
+        self.shader_modules
+            .write()
+            .retain(|module| !owned(&module.device));
+        self.bind_group_layouts
+            .write()
+            .retain(|layout| !owned(&layout.device));
+        self.pipeline_layouts
+            .write()
+            .retain(|layout| !owned(&layout.device));
+        self.compute_pipelines
+            .write()
+            .retain(|pipeline| !owned(&pipeline.device));
+        self.render_pipelines
+            .write()
+            .retain(|pipeline| !owned(&pipeline.device));
+        self.query_sets
+            .write()
+            .retain(|query_set| !owned(&query_set.device));
+        self.render_bundles
+            .write()
+            .retain(|bundle| !owned(&bundle.device));
+
+        for element in surface_guard.map.iter() {
+            if let Element::Occupied(ref surface, _epoch) = *element {
+                let mut presentation = surface.presentation.lock();
+                let configured_here = presentation
+                    .as_ref()
+                    .and_then(|present| present.device.downcast_ref::<A>())
+                    .map_or(false, owned);
+                if configured_here {
+                    let present = presentation.take().unwrap();
+                    let device = present.device.downcast_ref::<A>().unwrap();
+                    let suf = A::get_surface(surface);
+                    unsafe {
+                        suf.unwrap().raw.unconfigure(device.raw());
+                    }
+                }
+            }
+        }
+
+        self.queues
+            .write()
+            .retain(|queue| !queue.device.as_deref().map_or(false, owned));
+        let _ = devices.replace_with_error(device_id);
+    }
+
     pub(crate) fn surface_unconfigure(&self, device: &Device<A>, surface: &HalSurface<A>) {
         unsafe {
             use hal::Surface;
@@ -299,6 +417,32 @@ impl<A: HalApi> Hub<A> {
             samplers: self.samplers.generate_report(),
         }
     }
+
+    /// Like [`Hub::generate_report`], but without locking any registry.
+    ///
+    /// See [`Global::generate_report_lossy`] for the caveats.
+    ///
+    /// [`Global::generate_report_lossy`]: crate::global::Global::generate_report_lossy
+    pub fn generate_report_lossy(&self) -> HubReport {
+        HubReport {
+            adapters: self.adapters.generate_report_lossy(),
+            devices: self.devices.generate_report_lossy(),
+            queues: self.queues.generate_report_lossy(),
+            pipeline_layouts: self.pipeline_layouts.generate_report_lossy(),
+            shader_modules: self.shader_modules.generate_report_lossy(),
+            bind_group_layouts: self.bind_group_layouts.generate_report_lossy(),
+            bind_groups: self.bind_groups.generate_report_lossy(),
+            command_buffers: self.command_buffers.generate_report_lossy(),
+            render_bundles: self.render_bundles.generate_report_lossy(),
+            render_pipelines: self.render_pipelines.generate_report_lossy(),
+            compute_pipelines: self.compute_pipelines.generate_report_lossy(),
+            query_sets: self.query_sets.generate_report_lossy(),
+            buffers: self.buffers.generate_report_lossy(),
+            textures: self.textures.generate_report_lossy(),
+            texture_views: self.texture_views.generate_report_lossy(),
+            samplers: self.samplers.generate_report_lossy(),
+        }
+    }
 }
 
 pub struct Hubs {
@@ -320,23 +464,26 @@ pub struct Hubs {
 }
 
 impl Hubs {
-    pub(crate) fn new<F: GlobalIdentityHandlerFactory>(factory: &F) -> Self {
+    pub(crate) fn new<F: GlobalIdentityHandlerFactory>(
+        factory: &F,
+        lifetime_hook: &Arc<LifetimeHook>,
+    ) -> Self {
         Self {
             #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
-            vulkan: Hub::new(factory),
+            vulkan: Hub::new(factory, lifetime_hook),
             #[cfg(all(feature = "metal", any(target_os = "macos", target_os = "ios")))]
-            metal: Hub::new(factory),
+            metal: Hub::new(factory, lifetime_hook),
             #[cfg(all(feature = "dx12", windows))]
-            dx12: Hub::new(factory),
+            dx12: Hub::new(factory, lifetime_hook),
             #[cfg(feature = "gles")]
-            gl: Hub::new(factory),
+            gl: Hub::new(factory, lifetime_hook),
             #[cfg(all(
                 not(all(feature = "vulkan", not(target_arch = "wasm32"))),
                 not(all(feature = "metal", any(target_os = "macos", target_os = "ios"))),
                 not(all(feature = "dx12", windows)),
                 not(feature = "gles"),
             ))]
-            empty: Hub::new(factory),
+            empty: Hub::new(factory, lifetime_hook),
         }
     }
 }
@@ -5,7 +5,14 @@ use crate::{
     id::{self},
     Epoch, FastHashMap, Index,
 };
-use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 /// A simple structure to allocate [`Id`] identifiers.
 ///
@@ -88,18 +95,33 @@ impl IdentityValues {
 #[derive(Debug)]
 pub struct IdentityManager<I: id::TypedId> {
     pub(super) values: Mutex<IdentityValues>,
+    /// A copy of `values.count()`, which can be read without locking.
+    count: AtomicUsize,
     _phantom: PhantomData<I>,
 }
 
 impl<I: id::TypedId> IdentityManager<I> {
     pub fn process(&self, backend: Backend) -> I {
-        self.values.lock().alloc(backend)
+        let id = self.values.lock().alloc(backend);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        id
     }
     pub fn mark_as_used(&self, id: I) -> I {
-        self.values.lock().mark_as_used(id)
+        let id = self.values.lock().mark_as_used(id);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        id
     }
     pub fn free(&self, id: I) {
-        self.values.lock().release(id)
+        self.values.lock().release(id);
+        self.count.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// The number of allocated ids, read without locking.
+    ///
+    /// This may be momentarily out of date with respect to concurrent
+    /// allocations.
+    pub(crate) fn count_relaxed(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
     }
 }
 
@@ -107,6 +129,7 @@ impl<I: id::TypedId> IdentityManager<I> {
     pub fn new() -> Self {
         Self {
             values: Mutex::new(IdentityValues::default()),
+            count: AtomicUsize::new(0),
             _phantom: PhantomData,
         }
     }
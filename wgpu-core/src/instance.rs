@@ -9,14 +9,15 @@ use crate::{
     id::{AdapterId, DeviceId, QueueId, SurfaceId},
     identity::{GlobalIdentityHandlerFactory, Input},
     present::Presentation,
+    registry::ResourceLogEvent,
     resource::{Resource, ResourceInfo, ResourceType},
-    resource_log, LabelHelpers, DOWNLEVEL_WARNING_MESSAGE,
+    FastHashMap, LabelHelpers, DOWNLEVEL_WARNING_MESSAGE,
 };
 
 use parking_lot::Mutex;
 use wgt::{Backend, Backends, PowerPreference};
 
-use hal::{Adapter as _, Instance as _, OpenDevice};
+use hal::{Adapter as _, Device as _, Instance as _, OpenDevice};
 use thiserror::Error;
 
 pub type RequestAdapterOptions = wgt::RequestAdapterOptions<SurfaceId>;
@@ -119,17 +120,10 @@ impl Instance {
 
     pub(crate) fn destroy_surface(&self, surface: Surface) {
         fn destroy<A: HalApi>(_: A, instance: &Option<A::Instance>, surface: AnySurface) {
-            unsafe {
-                if let Some(surface) = surface.take::<A>() {
-                    if let Some(suf) = Arc::into_inner(surface) {
-                        if let Some(raw) = Arc::into_inner(suf.raw) {
-                            instance.as_ref().unwrap().destroy_surface(raw);
-                        } else {
-                            panic!("Surface cannot be destroyed because is still in use");
-                        }
-                    } else {
-                        panic!("Surface cannot be destroyed because is still in use");
-                    }
+            if let Some(surface) = surface.take::<A>() {
+                match Arc::into_inner(surface).and_then(|suf| Arc::into_inner(suf.raw)) {
+                    Some(raw) => unsafe { instance.as_ref().unwrap().destroy_surface(raw) },
+                    None => log::warn!("Surface is still in use and will be leaked"),
                 }
             }
         }
@@ -151,6 +145,38 @@ pub struct Surface {
     pub(crate) presentation: Mutex<Option<Presentation>>,
     pub(crate) info: ResourceInfo<SurfaceId>,
     pub(crate) raw: AnySurface,
+    pub(crate) capabilities: SurfaceCapabilitiesCache,
+}
+
+/// The capabilities of a surface, as reported for each adapter.
+///
+/// These don't change for the lifetime of the surface, apart from when it is
+/// configured onto another device.
+#[derive(Debug, Default)]
+pub(crate) struct SurfaceCapabilitiesCache {
+    by_adapter: Mutex<FastHashMap<AdapterId, wgt::SurfaceCapabilities>>,
+}
+
+impl SurfaceCapabilitiesCache {
+    /// Return the capabilities cached for `adapter_id`, calling `query` to
+    /// find them if there are none. Errors are not cached.
+    pub(crate) fn get_or_query<E>(
+        &self,
+        adapter_id: AdapterId,
+        query: impl FnOnce() -> Result<wgt::SurfaceCapabilities, E>,
+    ) -> Result<wgt::SurfaceCapabilities, E> {
+        let mut by_adapter = self.by_adapter.lock();
+        if let Some(caps) = by_adapter.get(&adapter_id) {
+            return Ok(caps.clone());
+        }
+        let caps = query()?;
+        by_adapter.insert(adapter_id, caps.clone());
+        Ok(caps)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.by_adapter.lock().clear();
+    }
 }
 
 impl Resource<SurfaceId> for Surface {
@@ -164,8 +190,8 @@ impl Resource<SurfaceId> for Surface {
         &mut self.info
     }
 
-    fn label(&self) -> String {
-        String::from("<Surface>")
+    fn label_into(&self, out: &mut String) {
+        out.push_str("<Surface>");
     }
 }
 
@@ -458,6 +484,20 @@ impl<I: Copy> AdapterInputs<'_, I> {
 #[error("Adapter is invalid")]
 pub struct InvalidAdapter;
 
+/// A presentation timestamp paired with the CPU clock sampled alongside it.
+///
+/// See [`Global::adapter_get_timestamp_calibration`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, Debug)]
+pub struct TimestampCalibration {
+    /// The adapter's presentation timestamp. This is
+    /// [`wgt::PresentationTimestamp::INVALID_TIMESTAMP`] on backends which
+    /// cannot provide one.
+    pub gpu: wgt::PresentationTimestamp,
+    /// The CPU clock at the time `gpu` was sampled.
+    pub cpu: std::time::Instant,
+}
+
 #[derive(Clone, Debug, Error)]
 #[non_exhaustive]
 pub enum RequestAdapterError {
@@ -522,6 +562,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let surface = Surface {
             presentation: Mutex::new(None),
+            capabilities: Default::default(),
             info: ResourceInfo::new("<Surface>"),
             raw: hal_surface,
         };
@@ -543,6 +584,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let surface = Surface {
             presentation: Mutex::new(None),
+            capabilities: Default::default(),
             info: ResourceInfo::new("<Surface>"),
             raw: {
                 let hal_surface: HalSurface<hal::api::Metal> = self
@@ -578,6 +620,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let surface = Surface {
             presentation: Mutex::new(None),
+            capabilities: Default::default(),
             info: ResourceInfo::new("<Surface>"),
             raw: {
                 let hal_surface: HalSurface<hal::api::Dx12> = self
@@ -609,6 +652,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let surface = Surface {
             presentation: Mutex::new(None),
+            capabilities: Default::default(),
             info: ResourceInfo::new("<Surface>"),
             raw: {
                 let hal_surface: HalSurface<hal::api::Dx12> = self
@@ -642,6 +686,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let surface = Surface {
             presentation: Mutex::new(None),
+            capabilities: Default::default(),
             info: ResourceInfo::new("<Surface>"),
             raw: {
                 let hal_surface: HalSurface<hal::api::Dx12> = self
@@ -662,6 +707,20 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         id
     }
 
+    /// The number of strong references to the surface `id`, or zero if `id`
+    /// is invalid or has been dropped.
+    ///
+    /// The `Global` holds one of these itself. Any more at the time the
+    /// `Global` is dropped mean the surface is leaked rather than destroyed.
+    pub fn surface_strong_count(&self, id: SurfaceId) -> usize {
+        self.surfaces
+            .read()
+            .try_get(id)
+            .ok()
+            .flatten()
+            .map_or(0, |surface| Arc::strong_count(surface))
+    }
+
     pub fn surface_drop(&self, id: SurfaceId) {
         profiling::scope!("Surface::drop");
 
@@ -752,6 +811,50 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         #[cfg(feature = "gles")]
         self.enumerate(hal::api::Gles, &self.instance.gl, &inputs, &mut adapters);
 
+        let rank = self.backend_rank();
+        adapters.sort_by_key(|id| rank(id.backend()));
+        adapters
+    }
+
+    fn registered_adapters<A: HalApi>(
+        &self,
+        instance: &Option<A::Instance>,
+        backends: Backends,
+        list: &mut Vec<AdapterId>,
+    ) {
+        if instance.is_none() || !backends.contains(A::VARIANT.into()) {
+            return;
+        }
+
+        let hub = HalApi::hub(self);
+        list.extend(hub.adapters.read().iter(A::VARIANT).map(|(id, _)| id));
+    }
+
+    /// Return the ids of every adapter currently registered on the requested
+    /// and initialized `backends`.
+    ///
+    /// Unlike [`Global::enumerate_adapters`], this does not ask the backends
+    /// for new adapters. It only lists the ones that have already been
+    /// enumerated or requested and not yet dropped.
+    pub fn instance_enumerate_adapters(&self, backends: Backends) -> Vec<AdapterId> {
+        profiling::scope!("Instance::instance_enumerate_adapters");
+        api_log!("Instance::instance_enumerate_adapters {backends:?}");
+
+        let mut adapters = Vec::new();
+
+        #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
+        self.registered_adapters::<hal::api::Vulkan>(
+            &self.instance.vulkan,
+            backends,
+            &mut adapters,
+        );
+        #[cfg(all(feature = "metal", any(target_os = "macos", target_os = "ios")))]
+        self.registered_adapters::<hal::api::Metal>(&self.instance.metal, backends, &mut adapters);
+        #[cfg(all(feature = "dx12", windows))]
+        self.registered_adapters::<hal::api::Dx12>(&self.instance.dx12, backends, &mut adapters);
+        #[cfg(feature = "gles")]
+        self.registered_adapters::<hal::api::Gles>(&self.instance.gl, backends, &mut adapters);
+
         adapters
     }
 
@@ -792,7 +895,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             inputs: &AdapterInputs<I>,
             compatible_surface: Option<&Surface>,
             force_software: bool,
-            device_types: &mut Vec<wgt::DeviceType>,
+            device_types: &mut Vec<(Backend, wgt::DeviceType)>,
         ) -> (Option<I>, Vec<hal::ExposedAdapter<A>>) {
             let id = inputs.find(A::VARIANT);
             match instance {
@@ -813,7 +916,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                     .is_some()
                         });
                     }
-                    device_types.extend(adapters.iter().map(|ad| ad.info.device_type));
+                    device_types
+                        .extend(adapters.iter().map(|ad| (A::VARIANT, ad.info.device_type)));
                     (id, adapters)
                 }
                 _ => (id, Vec::new()),
@@ -872,10 +976,17 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Err(RequestAdapterError::NotFound);
         }
 
+        // Consider the adapters in backend priority order, the indices found
+        // below are positions in `by_priority`.
+        let rank = self.backend_rank();
+        let mut by_priority = (0..device_types.len()).collect::<Vec<_>>();
+        by_priority.sort_by_key(|&i| rank(device_types[i].0));
+
         let (mut integrated, mut discrete, mut virt, mut cpu, mut other) =
             (None, None, None, None, None);
 
-        for (i, ty) in device_types.into_iter().enumerate() {
+        for (i, &index) in by_priority.iter().enumerate() {
+            let ty = device_types[index].1;
             match ty {
                 wgt::DeviceType::IntegratedGpu => {
                     integrated = integrated.or(Some(i));
@@ -918,7 +1029,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
         };
 
-        let mut selected = preferred_gpu.unwrap_or(0);
+        let mut selected = by_priority[preferred_gpu.unwrap_or(0)];
         #[cfg(all(feature = "vulkan", not(target_arch = "wasm32")))]
         if let Some(id) = self.select(&mut selected, id_vulkan, adapters_vk) {
             return Ok(id);
@@ -965,7 +1076,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Backend::Gl => fid.assign(Adapter::new(hal_adapter)),
                 _ => unreachable!(),
             };
-        resource_log!("Created Adapter {:?}", id);
+        self.resource_logger.log_event(
+            A::VARIANT,
+            Adapter::<A>::TYPE,
+            Some(id),
+            ResourceLogEvent::Created,
+            format_args!("Created Adapter {:?}", id),
+        );
         id
     }
 
@@ -1041,21 +1158,50 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(unsafe { adapter.raw.adapter.get_presentation_timestamp() })
     }
 
+    /// Sample the adapter's presentation timestamp together with the CPU
+    /// clock, so that the two timelines can be aligned.
+    ///
+    /// The CPU clock is read immediately before and after the presentation
+    /// timestamp, and the midpoint of the two is reported. If a device open
+    /// on the adapter supports calibrated timestamps (such as
+    /// `VK_EXT_calibrated_timestamps`), the presentation timestamp is sampled
+    /// through it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn adapter_get_timestamp_calibration<A: HalApi>(
+        &self,
+        adapter_id: AdapterId,
+    ) -> Result<TimestampCalibration, InvalidAdapter> {
+        let hub = A::hub(self);
+
+        let adapter = hub.adapters.get(adapter_id).map_err(|_| InvalidAdapter)?;
+        let device = hub
+            .devices
+            .read()
+            .iter(A::VARIANT)
+            .find(|(_, device)| Arc::ptr_eq(&device.adapter, &adapter) && device.is_valid())
+            .map(|(_, device)| device.clone());
+
+        let before = std::time::Instant::now();
+        let gpu = device
+            .and_then(|device| unsafe { device.raw().get_calibrated_presentation_timestamp() })
+            .unwrap_or_else(|| unsafe { adapter.raw.adapter.get_presentation_timestamp() });
+        let after = std::time::Instant::now();
+
+        Ok(TimestampCalibration {
+            gpu,
+            cpu: before + (after - before) / 2,
+        })
+    }
+
     pub fn adapter_drop<A: HalApi>(&self, adapter_id: AdapterId) {
         profiling::scope!("Adapter::drop");
         api_log!("Adapter::drop {adapter_id:?}");
 
+        // Devices hold a reference to their adapter, so this only releases
+        // the adapter if no device created from it is alive. Otherwise the
+        // last of those devices releases it, along with its id.
         let hub = A::hub(self);
-        let mut adapters_locked = hub.adapters.write();
-
-        let free = match adapters_locked.get(adapter_id) {
-            Ok(adapter) => Arc::strong_count(adapter) == 1,
-            Err(_) => true,
-        };
-        if free {
-            hub.adapters
-                .unregister_locked(adapter_id, &mut *adapters_locked);
-        }
+        hub.adapters.unregister(adapter_id);
     }
 }
 
@@ -1080,19 +1226,28 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(adapter) => adapter,
                 Err(_) => break RequestDeviceError::InvalidAdapter,
             };
-            let (device, mut queue) =
+            let (mut device, mut queue) =
                 match adapter.create_device_and_queue(desc, self.instance.flags, trace_path) {
                     Ok((device, queue)) => (device, queue),
                     Err(e) => break e,
                 };
+            device.resource_logger = self.resource_logger.clone();
             let (device_id, _) = device_fid.assign(device);
-            resource_log!("Created Device {:?}", device_id);
 
             let device = hub.devices.get(device_id).unwrap();
+            device.log_event(
+                &device,
+                ResourceLogEvent::Created,
+                format_args!("Created Device {:?}", device_id),
+            );
             queue.device = Some(device.clone());
 
-            let (queue_id, _) = queue_fid.assign(queue);
-            resource_log!("Created Queue {:?}", queue_id);
+            let (queue_id, queue) = queue_fid.assign(queue);
+            queue.log_event(
+                &device,
+                ResourceLogEvent::Created,
+                format_args!("Created Queue {:?}", queue_id),
+            );
 
             device.queue_id.write().replace(queue_id);
 
@@ -1128,7 +1283,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(adapter) => adapter,
                 Err(_) => break RequestDeviceError::InvalidAdapter,
             };
-            let (device, mut queue) = match adapter.create_device_and_queue_from_hal(
+            let (mut device, mut queue) = match adapter.create_device_and_queue_from_hal(
                 hal_device,
                 desc,
                 self.instance.flags,
@@ -1137,14 +1292,23 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(e) => break e,
             };
+            device.resource_logger = self.resource_logger.clone();
             let (device_id, _) = devices_fid.assign(device);
-            resource_log!("Created Device {:?}", device_id);
 
             let device = hub.devices.get(device_id).unwrap();
+            device.log_event(
+                &device,
+                ResourceLogEvent::Created,
+                format_args!("Created Device {:?}", device_id),
+            );
             queue.device = Some(device.clone());
 
-            let (queue_id, _) = queues_fid.assign(queue);
-            resource_log!("Created Queue {:?}", queue_id);
+            let (queue_id, queue) = queues_fid.assign(queue);
+            queue.log_event(
+                &device,
+                ResourceLogEvent::Created,
+                format_args!("Created Queue {:?}", queue_id),
+            );
 
             device.queue_id.write().replace(queue_id);
 
@@ -1191,3 +1355,77 @@ pub fn parse_backends_from_comma_list(string: &str) -> Backends {
 
     backends
 }
+
+#[cfg(test)]
+mod test {
+    use super::{AdapterInputs, SurfaceCapabilitiesCache};
+    use crate::{
+        global::Global,
+        id::{AdapterId, TypedId},
+        identity::IdentityManagerFactory,
+    };
+
+    #[test]
+    fn surface_capabilities_cache() {
+        let cache = SurfaceCapabilitiesCache::default();
+        let first = AdapterId::zip(0, 1, wgt::Backend::Vulkan);
+        let second = AdapterId::zip(1, 1, wgt::Backend::Vulkan);
+
+        let queries = std::cell::Cell::new(0);
+        let query = |id| {
+            cache.get_or_query::<()>(id, || {
+                queries.set(queries.get() + 1);
+                Ok(wgt::SurfaceCapabilities {
+                    formats: vec![wgt::TextureFormat::Bgra8Unorm],
+                    present_modes: vec![wgt::PresentMode::Fifo],
+                    alpha_modes: vec![wgt::CompositeAlphaMode::Opaque],
+                    usages: wgt::TextureUsages::RENDER_ATTACHMENT,
+                })
+            })
+        };
+
+        for _ in 0..3 {
+            assert!(query(first).is_ok());
+            assert!(query(second).is_ok());
+        }
+        assert_eq!(queries.get(), 2);
+
+        // Errors are not cached.
+        let third = AdapterId::zip(2, 1, wgt::Backend::Vulkan);
+        assert!(cache.get_or_query(third, || Err(())).is_err());
+        assert!(cache.get_or_query(third, || Err(())).is_err());
+
+        cache.clear();
+        let mut requeried = false;
+        let caps = cache.get_or_query::<()>(first, || {
+            requeried = true;
+            Err(())
+        });
+        assert!(caps.is_err());
+        assert!(requeried);
+    }
+
+    #[test]
+    fn enumerate_adapters_without_backends() {
+        // With no initialized backend there is nothing to walk, which keeps
+        // this test independent of the adapters available on the machine.
+        let global = Global::new(
+            "test",
+            IdentityManagerFactory,
+            wgt::InstanceDescriptor {
+                backends: wgt::Backends::empty(),
+                ..Default::default()
+            },
+        );
+
+        let enumerated =
+            global.enumerate_adapters(AdapterInputs::Mask(wgt::Backends::all(), |_| ()));
+        assert!(enumerated.is_empty());
+        assert_eq!(
+            global
+                .instance_enumerate_adapters(wgt::Backends::all())
+                .len(),
+            enumerated.len()
+        );
+    }
+}
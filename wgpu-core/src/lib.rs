@@ -90,6 +90,8 @@ pub mod id;
 pub mod identity;
 mod init_tracker;
 pub mod instance;
+#[cfg(feature = "lock-ordering")]
+mod lock_order;
 pub mod pipeline;
 mod pool;
 pub mod present;
@@ -0,0 +1,98 @@
+//! Run-time checks of the order in which registry locks are acquired.
+//!
+//! Enabled by the `lock-ordering` feature. Every [`Registry`] has a
+//! [`LockRank`], and a thread acquiring a registry lock while it holds the
+//! lock of a registry with a higher rank panics, naming both locks. Locks of
+//! the same rank may be acquired in any order.
+//!
+//! [`Registry`]: crate::registry::Registry
+
+use std::{
+    cell::RefCell,
+    ops::{Deref, DerefMut},
+};
+
+/// The position of a registry lock in the acquisition order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum LockRank {
+    /// The `surfaces` registry of a `Global`.
+    Surfaces,
+    /// The registries of a backend's `Hub`.
+    Hub,
+}
+
+thread_local! {
+    static HELD: RefCell<Vec<(LockRank, &'static str)>> = RefCell::new(Vec::new());
+}
+
+/// Records that the current thread holds a lock until it is dropped.
+#[derive(Debug)]
+pub(crate) struct LockToken {
+    rank: LockRank,
+    kind: &'static str,
+}
+
+impl LockToken {
+    /// Record the acquisition of the `kind` lock, which has `rank`.
+    ///
+    /// Panics if the current thread holds a lock ranked after `rank`.
+    pub(crate) fn acquire(rank: LockRank, kind: &'static str) -> Self {
+        HELD.with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some(&(held_rank, held_kind)) = held.iter().find(|&&(r, _)| r > rank) {
+                // Release the borrow, the panic may drop guards which use it.
+                drop(held);
+                panic!(
+                    "Lock ordering violation: acquiring the {kind} lock ({rank:?}) \
+                     while holding the {held_kind} lock ({held_rank:?})"
+                );
+            }
+            held.push((rank, kind));
+        });
+        Self { rank, kind }
+    }
+}
+
+impl Drop for LockToken {
+    fn drop(&mut self) {
+        HELD.with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some(index) = held
+                .iter()
+                .rposition(|&(rank, kind)| rank == self.rank && kind == self.kind)
+            {
+                held.remove(index);
+            }
+        });
+    }
+}
+
+/// A lock guard along with the [`LockToken`] recording it.
+#[derive(Debug)]
+pub(crate) struct Tracked<G> {
+    guard: G,
+    _token: LockToken,
+}
+
+impl<G> Tracked<G> {
+    pub(crate) fn new(token: LockToken, guard: G) -> Self {
+        Self {
+            guard,
+            _token: token,
+        }
+    }
+}
+
+impl<G: Deref> Deref for Tracked<G> {
+    type Target = G::Target;
+
+    fn deref(&self) -> &G::Target {
+        &self.guard
+    }
+}
+
+impl<G: DerefMut> DerefMut for Tracked<G> {
+    fn deref_mut(&mut self) -> &mut G::Target {
+        &mut self.guard
+    }
+}
@@ -6,8 +6,9 @@ use crate::{
     device::{Device, DeviceError, MissingDownlevelFlags, MissingFeatures, RenderPassContext},
     hal_api::HalApi,
     id::{ComputePipelineId, PipelineLayoutId, RenderPipelineId, ShaderModuleId},
+    registry::ResourceLogEvent,
     resource::{Resource, ResourceInfo, ResourceType},
-    resource_log, validation, Label,
+    validation, Label,
 };
 use arrayvec::ArrayVec;
 use std::{borrow::Cow, error::Error, fmt, marker::PhantomData, num::NonZeroU32, sync::Arc};
@@ -54,7 +55,11 @@ pub struct ShaderModule<A: HalApi> {
 impl<A: HalApi> Drop for ShaderModule<A> {
     fn drop(&mut self) {
         if let Some(raw) = self.raw.take() {
-            resource_log!("Destroy raw ShaderModule {:?}", self.info.label());
+            self.log_event(
+                &self.device,
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw ShaderModule {:?}", self.info.label()),
+            );
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
                 t.add(trace::Action::DestroyShaderModule(self.info.id()));
@@ -78,8 +83,8 @@ impl<A: HalApi> Resource<ShaderModuleId> for ShaderModule<A> {
         &mut self.info
     }
 
-    fn label(&self) -> String {
-        self.label.clone()
+    fn label_into(&self, out: &mut String) {
+        out.push_str(&self.label);
     }
 }
 
@@ -178,6 +183,39 @@ impl CreateShaderModuleError {
             _ => None,
         }
     }
+
+    /// This error as a [`ShaderCompilationMessage`], located in the WGSL
+    /// source it was reported for.
+    pub fn compilation_message(&self) -> ShaderCompilationMessage {
+        let source = match *self {
+            #[cfg(feature = "wgsl")]
+            CreateShaderModuleError::Parsing(ref err) => err.source.as_str(),
+            CreateShaderModuleError::Validation(ref err) => err.source.as_str(),
+            _ => "",
+        };
+        // Modules given as naga IR have no source for their spans to point
+        // into.
+        let location = if source.is_empty() {
+            None
+        } else {
+            self.location(source)
+        };
+        ShaderCompilationMessage {
+            message: self.to_string(),
+            location,
+        }
+    }
+}
+
+/// A message produced when compiling a shader module, as returned by
+/// [`Global::shader_module_get_compilation_info`].
+///
+/// [`Global::shader_module_get_compilation_info`]: crate::global::Global::shader_module_get_compilation_info
+#[derive(Clone, Debug)]
+pub struct ShaderCompilationMessage {
+    pub message: String,
+    /// Where in the WGSL source the message applies, if it's known.
+    pub location: Option<naga::SourceLocation>,
 }
 
 /// Describes a programmable pipeline stage.
@@ -231,12 +269,71 @@ pub enum CreateComputePipelineError {
     Implicit(#[from] ImplicitLayoutError),
     #[error("Error matching shader requirements against the pipeline")]
     Stage(#[from] validation::StageError),
+    #[error("Workgroup size {dimension} of {requested} exceeds the device limit of {limit}")]
+    WorkgroupSizeLimitExceeded {
+        dimension: WorkgroupSizeDimension,
+        requested: u32,
+        limit: u32,
+    },
     #[error("Internal error: {0}")]
     Internal(String),
     #[error(transparent)]
     MissingDownlevelFlags(#[from] MissingDownlevelFlags),
 }
 
+/// The workgroup size limit named by
+/// [`CreateComputePipelineError::WorkgroupSizeLimitExceeded`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WorkgroupSizeDimension {
+    /// `max_compute_workgroup_size_x`.
+    X,
+    /// `max_compute_workgroup_size_y`.
+    Y,
+    /// `max_compute_workgroup_size_z`.
+    Z,
+    /// `max_compute_invocations_per_workgroup`.
+    Invocations,
+}
+
+impl fmt::Display for WorkgroupSizeDimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            Self::X => "x",
+            Self::Y => "y",
+            Self::Z => "z",
+            Self::Invocations => "total invocations",
+        })
+    }
+}
+
+impl WorkgroupSizeDimension {
+    /// Check `size` against the workgroup size limits in `limits`, returning
+    /// the first limit it exceeds along with the requested value.
+    pub(crate) fn check(size: [u32; 3], limits: &wgt::Limits) -> Option<(Self, u32, u32)> {
+        let per_dimension = [
+            (Self::X, size[0], limits.max_compute_workgroup_size_x),
+            (Self::Y, size[1], limits.max_compute_workgroup_size_y),
+            (Self::Z, size[2], limits.max_compute_workgroup_size_z),
+        ];
+        if let Some(&exceeded) = per_dimension
+            .iter()
+            .find(|&&(_, requested, limit)| requested > limit)
+        {
+            return Some(exceeded);
+        }
+        let total = size.iter().map(|&s| s as u64).product::<u64>();
+        let limit = limits.max_compute_invocations_per_workgroup;
+        (total > limit as u64).then(|| {
+            (
+                Self::Invocations,
+                total.try_into().unwrap_or(u32::MAX),
+                limit,
+            )
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct ComputePipeline<A: HalApi> {
     pub(crate) raw: Option<A::ComputePipeline>,
@@ -250,7 +347,11 @@ pub struct ComputePipeline<A: HalApi> {
 impl<A: HalApi> Drop for ComputePipeline<A> {
     fn drop(&mut self) {
         if let Some(raw) = self.raw.take() {
-            resource_log!("Destroy raw ComputePipeline {:?}", self.info.label());
+            self.log_event(
+                &self.device,
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw ComputePipeline {:?}", self.info.label()),
+            );
 
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
@@ -498,7 +599,11 @@ pub struct RenderPipeline<A: HalApi> {
 impl<A: HalApi> Drop for RenderPipeline<A> {
     fn drop(&mut self) {
         if let Some(raw) = self.raw.take() {
-            resource_log!("Destroy raw RenderPipeline {:?}", self.info.label());
+            self.log_event(
+                &self.device,
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw RenderPipeline {:?}", self.info.label()),
+            );
 
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
@@ -111,7 +111,7 @@ impl From<WaitIdleError> for ConfigureSurfaceError {
     fn from(e: WaitIdleError) -> Self {
         match e {
             WaitIdleError::Device(d) => ConfigureSurfaceError::Device(d),
-            WaitIdleError::WrongSubmissionIndex(..) => unreachable!(),
+            WaitIdleError::WrongSubmissionIndex(..) | WaitIdleError::Timeout => unreachable!(),
             WaitIdleError::StuckGpu => ConfigureSurfaceError::StuckGpu,
         }
     }
@@ -1,13 +1,16 @@
-use std::sync::Arc;
+use std::{fmt, num::NonZeroU64, sync::Arc};
 
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use wgt::Backend;
 
+#[cfg(feature = "lock-ordering")]
+use crate::lock_order;
 use crate::{
     id,
     identity::{IdentityHandlerFactory, IdentityManager},
-    resource::Resource,
-    storage::{Element, InvalidId, Storage},
+    resource::{Resource, ResourceType},
+    resource_log,
+    storage::{Element, ElementCounts, InvalidId, Storage},
 };
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
@@ -25,6 +28,150 @@ impl RegistryReport {
     }
 }
 
+/// Whether a [`ResourceEvent`] reports a resource being created or dropped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResourceLifetime {
+    /// The resource was registered under a new id.
+    Created,
+    /// The user released the id of the resource.
+    ///
+    /// The resource itself may still be kept alive internally, for example if
+    /// it's used by a pending submission.
+    Dropped,
+}
+
+/// A resource lifetime event, passed to the hook installed with
+/// [`Global::set_resource_lifetime_hook`].
+///
+/// [`Global::set_resource_lifetime_hook`]: crate::global::Global::set_resource_lifetime_hook
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ResourceEvent {
+    /// The backend the resource belongs to.
+    pub backend: Backend,
+    /// The resource type, such as `"Buffer"`.
+    pub kind: ResourceType,
+    /// The raw value of the resource id.
+    pub id: NonZeroU64,
+    pub lifetime: ResourceLifetime,
+}
+
+pub type ResourceLifetimeHook = Box<dyn Fn(ResourceEvent) + Send + Sync>;
+
+/// The slot holding the resource lifetime hook, shared by every registry of a
+/// [`Global`](crate::global::Global).
+#[derive(Default)]
+pub(crate) struct LifetimeHook {
+    hook: RwLock<Option<ResourceLifetimeHook>>,
+}
+
+impl LifetimeHook {
+    pub(crate) fn set(&self, hook: Option<ResourceLifetimeHook>) {
+        *self.hook.write() = hook;
+    }
+
+    fn notify<I: id::TypedId>(&self, kind: ResourceType, id: I, lifetime: ResourceLifetime) {
+        if let Some(ref hook) = *self.hook.read() {
+            hook(ResourceEvent {
+                backend: id.unzip().2,
+                kind,
+                id: id.into_raw(),
+                lifetime,
+            });
+        }
+    }
+}
+
+impl fmt::Debug for LifetimeHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LifetimeHook")
+            .field("is_set", &self.hook.read().is_some())
+            .finish()
+    }
+}
+
+/// What a [`ResourceLogRecord`] reports about a resource.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResourceLogEvent {
+    /// The resource was created.
+    Created,
+    /// The raw resource was destroyed.
+    Destroyed,
+}
+
+/// A resource log record, passed to the logger installed with
+/// [`Global::set_resource_logger`] in place of the text `resource_log!`
+/// would log.
+///
+/// [`Global::set_resource_logger`]: crate::global::Global::set_resource_logger
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ResourceLogRecord {
+    /// The backend the resource belongs to.
+    pub backend: Backend,
+    /// The resource type, such as `"Buffer"`.
+    pub kind: ResourceType,
+    /// The raw value of the resource id, if it was given one.
+    pub id: Option<NonZeroU64>,
+    /// What happened to the resource.
+    pub event: ResourceLogEvent,
+}
+
+impl ResourceLogRecord {
+    pub(crate) fn new<I: id::TypedId>(
+        backend: Backend,
+        kind: ResourceType,
+        id: Option<I>,
+        event: ResourceLogEvent,
+    ) -> Self {
+        Self {
+            backend,
+            kind,
+            id: id.map(I::into_raw),
+            event,
+        }
+    }
+}
+
+pub type ResourceLoggerFn = Box<dyn Fn(ResourceLogRecord) + Send + Sync>;
+
+/// The slot holding the resource logger of a
+/// [`Global`](crate::global::Global), shared with its devices.
+#[derive(Default)]
+pub(crate) struct ResourceLogger {
+    logger: RwLock<Option<ResourceLoggerFn>>,
+}
+
+impl ResourceLogger {
+    pub(crate) fn set(&self, logger: ResourceLoggerFn) {
+        *self.logger.write() = Some(logger);
+    }
+
+    /// Report `event` for the resource `id` of type `kind` to the logger, or
+    /// log `message` with `resource_log!` if none is set.
+    ///
+    /// The record is only built if a logger is set.
+    pub(crate) fn log_event<I: id::TypedId>(
+        &self,
+        backend: Backend,
+        kind: ResourceType,
+        id: Option<I>,
+        event: ResourceLogEvent,
+        message: fmt::Arguments<'_>,
+    ) {
+        match *self.logger.read() {
+            Some(ref logger) => logger(ResourceLogRecord::new(backend, kind, id, event)),
+            None => resource_log!("{}", message),
+        }
+    }
+}
+
+impl fmt::Debug for ResourceLogger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceLogger")
+            .field("is_set", &self.logger.read().is_some())
+            .finish()
+    }
+}
+
 /// Registry is the primary holder of each resource type
 /// Every resource is now arcanized so the last arc released
 /// will in the end free the memory and release the inner raw resource
@@ -40,20 +187,80 @@ impl RegistryReport {
 pub struct Registry<I: id::TypedId, T: Resource<I>> {
     identity: Arc<IdentityManager<I>>,
     storage: RwLock<Storage<T, I>>,
+    counts: Arc<ElementCounts>,
     backend: Backend,
+    lifetime_hook: Arc<LifetimeHook>,
+    #[cfg(feature = "lock-ordering")]
+    lock_rank: lock_order::LockRank,
 }
 
+#[cfg(feature = "lock-ordering")]
+pub(crate) type StorageReadGuard<'a, T, I> =
+    lock_order::Tracked<RwLockReadGuard<'a, Storage<T, I>>>;
+#[cfg(not(feature = "lock-ordering"))]
+pub(crate) type StorageReadGuard<'a, T, I> = RwLockReadGuard<'a, Storage<T, I>>;
+
+#[cfg(feature = "lock-ordering")]
+pub(crate) type StorageWriteGuard<'a, T, I> =
+    lock_order::Tracked<RwLockWriteGuard<'a, Storage<T, I>>>;
+#[cfg(not(feature = "lock-ordering"))]
+pub(crate) type StorageWriteGuard<'a, T, I> = RwLockWriteGuard<'a, Storage<T, I>>;
+
 impl<I: id::TypedId, T: Resource<I>> Registry<I, T> {
-    pub(crate) fn new<F: IdentityHandlerFactory<I>>(backend: Backend, factory: &F) -> Self {
+    pub(crate) fn new<F: IdentityHandlerFactory<I>>(
+        backend: Backend,
+        factory: &F,
+        lifetime_hook: &Arc<LifetimeHook>,
+    ) -> Self {
+        let storage = Storage::new();
         Self {
             identity: factory.spawn(),
-            storage: RwLock::new(Storage::new()),
+            counts: storage.counts().clone(),
+            storage: RwLock::new(storage),
             backend,
+            lifetime_hook: lifetime_hook.clone(),
+            #[cfg(feature = "lock-ordering")]
+            lock_rank: lock_order::LockRank::Hub,
         }
     }
 
-    pub(crate) fn without_backend<F: IdentityHandlerFactory<I>>(factory: &F) -> Self {
-        Self::new(Backend::Empty, factory)
+    /// Create a registry which isn't part of a backend's hub.
+    ///
+    /// With the `lock-ordering` feature, its lock must be acquired before
+    /// any hub lock.
+    pub(crate) fn without_backend<F: IdentityHandlerFactory<I>>(
+        factory: &F,
+        lifetime_hook: &Arc<LifetimeHook>,
+    ) -> Self {
+        #[allow(unused_mut)]
+        let mut registry = Self::new(Backend::Empty, factory, lifetime_hook);
+        #[cfg(feature = "lock-ordering")]
+        {
+            registry.lock_rank = lock_order::LockRank::Surfaces;
+        }
+        registry
+    }
+}
+
+#[cfg(not(feature = "lock-ordering"))]
+impl<I: id::TypedId, T: Resource<I>> Registry<I, T> {
+    pub(crate) fn read<'a>(&'a self) -> StorageReadGuard<'a, T, I> {
+        self.storage.read()
+    }
+    pub(crate) fn write<'a>(&'a self) -> StorageWriteGuard<'a, T, I> {
+        self.storage.write()
+    }
+}
+
+#[cfg(feature = "lock-ordering")]
+impl<I: id::TypedId, T: Resource<I>> Registry<I, T> {
+    pub(crate) fn read<'a>(&'a self) -> StorageReadGuard<'a, T, I> {
+        let token = lock_order::LockToken::acquire(self.lock_rank, T::TYPE);
+        lock_order::Tracked::new(token, self.storage.read())
+    }
+    pub(crate) fn write<'a>(&'a self) -> StorageWriteGuard<'a, T, I> {
+        let token = lock_order::LockToken::acquire(self.lock_rank, T::TYPE);
+        lock_order::Tracked::new(token, self.storage.write())
     }
 }
 
@@ -61,7 +268,7 @@ impl<I: id::TypedId, T: Resource<I>> Registry<I, T> {
 pub(crate) struct FutureId<'a, I: id::TypedId, T: Resource<I>> {
     id: I,
     identity: Arc<IdentityManager<I>>,
-    data: &'a RwLock<Storage<T, I>>,
+    registry: &'a Registry<I, T>,
 }
 
 impl<I: id::TypedId + Copy, T: Resource<I>> FutureId<'_, I, T> {
@@ -83,9 +290,12 @@ impl<I: id::TypedId + Copy, T: Resource<I>> FutureId<'_, I, T> {
     ///
     /// Registers it with the registry, and fills out the resource info.
     pub fn assign(self, value: T) -> (I, Arc<T>) {
-        let mut data = self.data.write();
-        data.insert(self.id, self.init(value));
-        (self.id, data.get(self.id).unwrap().clone())
+        let value = self.init(value);
+        self.registry.write().insert(self.id, value.clone());
+        self.registry
+            .lifetime_hook
+            .notify(T::TYPE, self.id, ResourceLifetime::Created);
+        (self.id, value)
     }
 
     /// Assign an existing resource to a new ID.
@@ -95,14 +305,19 @@ impl<I: id::TypedId + Copy, T: Resource<I>> FutureId<'_, I, T> {
     /// This _will_ leak the ID, and it will not be recycled again.
     /// See https://github.com/gfx-rs/wgpu/issues/4912.
     pub fn assign_existing(self, value: &Arc<T>) -> I {
-        let mut data = self.data.write();
-        debug_assert!(!data.contains(self.id));
-        data.insert(self.id, value.clone());
+        {
+            let mut data = self.registry.write();
+            debug_assert!(!data.contains(self.id));
+            data.insert(self.id, value.clone());
+        }
+        self.registry
+            .lifetime_hook
+            .notify(T::TYPE, self.id, ResourceLifetime::Created);
         self.id
     }
 
     pub fn assign_error(self, label: &str) -> I {
-        self.data.write().insert_error(self.id, label);
+        self.registry.write().insert_error(self.id, label);
         self.id
     }
 }
@@ -119,14 +334,14 @@ impl<I: id::TypedId, T: Resource<I>> Registry<I, T> {
                 self.identity.mark_as_used(F::input_to_id(id_in))
             },
             identity: self.identity.clone(),
-            data: &self.storage,
+            registry: self,
         }
     }
     pub(crate) fn request(&self) -> FutureId<I, T> {
         FutureId {
             id: self.identity.process(self.backend),
             identity: self.identity.clone(),
-            data: &self.storage,
+            registry: self,
         }
     }
     pub(crate) fn try_get(&self, id: I) -> Result<Option<Arc<T>>, InvalidId> {
@@ -135,54 +350,69 @@ impl<I: id::TypedId, T: Resource<I>> Registry<I, T> {
     pub(crate) fn get(&self, id: I) -> Result<Arc<T>, InvalidId> {
         self.read().get_owned(id)
     }
-    pub(crate) fn read<'a>(&'a self) -> RwLockReadGuard<'a, Storage<T, I>> {
-        self.storage.read()
-    }
-    pub(crate) fn write<'a>(&'a self) -> RwLockWriteGuard<'a, Storage<T, I>> {
-        self.storage.write()
-    }
+    /// Like [`Registry::unregister`], for callers already holding the storage
+    /// lock.
+    ///
+    /// Note that the lifetime hook is called while the lock is held.
     pub fn unregister_locked(&self, id: I, storage: &mut Storage<T, I>) -> Option<Arc<T>> {
-        storage.remove(id)
+        let value = storage.remove(id);
+        self.lifetime_hook
+            .notify(T::TYPE, id, ResourceLifetime::Dropped);
+        value
     }
     pub fn force_replace(&self, id: I, mut value: T) {
-        let mut storage = self.storage.write();
+        let mut storage = self.write();
         value.as_info_mut().set_id(id, &self.identity);
         storage.force_replace(id, value)
     }
     pub fn force_replace_with_error(&self, id: I, label: &str) {
-        let mut storage = self.storage.write();
+        let mut storage = self.write();
         storage.remove(id);
         storage.insert_error(id, label);
     }
     pub(crate) fn unregister(&self, id: I) -> Option<Arc<T>> {
-        let value = self.storage.write().remove(id);
+        let value = self.write().remove(id);
+        self.lifetime_hook
+            .notify(T::TYPE, id, ResourceLifetime::Dropped);
         //Returning None is legal if it's an error ID
         value
     }
 
     pub fn label_for_resource(&self, id: I) -> String {
-        let guard = self.storage.read();
+        let mut label = String::new();
+        self.label_for_resource_into(id, &mut label);
+        label
+    }
+
+    /// Like [`Registry::label_for_resource`], but clears `out` and writes the
+    /// label into it instead of allocating a new `String`.
+    pub fn label_for_resource_into(&self, id: I, out: &mut String) {
+        use std::fmt::Write as _;
+
+        out.clear();
+        let guard = self.read();
 
         let type_name = guard.kind();
         match guard.get(id) {
             Ok(res) => {
-                let label = res.label();
-                if label.is_empty() {
-                    format!("<{}-{:?}>", type_name, id.unzip())
-                } else {
-                    label
+                res.label_into(out);
+                if out.is_empty() {
+                    let _ = write!(out, "<{}-{:?}>", type_name, id.unzip());
                 }
             }
-            Err(_) => format!(
-                "<Invalid-{} label={}>",
-                type_name,
-                guard.label_for_invalid_id(id)
-            ),
+            Err(_) => {
+                let _ = write!(
+                    out,
+                    "<Invalid-{} label={}>",
+                    type_name,
+                    guard.label_for_invalid_id(id)
+                );
+            }
         }
     }
 
     pub(crate) fn generate_report(&self) -> RegistryReport {
-        let storage = self.storage.read();
+        let storage = self.read();
         let mut report = RegistryReport {
             element_size: std::mem::size_of::<T>(),
             ..Default::default()
@@ -197,4 +427,200 @@ impl<I: id::TypedId, T: Resource<I>> Registry<I, T> {
         }
         report
     }
+
+    /// Like [`Registry::generate_report`], but reads the counts without
+    /// locking the registry, so they may be momentarily inconsistent.
+    pub(crate) fn generate_report_lossy(&self) -> RegistryReport {
+        let (len, occupied, error) = self.counts.load();
+        RegistryReport {
+            num_allocated: self.identity.count_relaxed(),
+            num_kept_from_user: occupied,
+            num_released_from_user: len.saturating_sub(occupied + error),
+            num_error: error,
+            element_size: std::mem::size_of::<T>(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{LifetimeHook, Registry, ResourceEvent, ResourceLifetime};
+    use crate::{
+        id::{self, TypedId},
+        identity::IdentityManagerFactory,
+        resource::{Resource, ResourceInfo, ResourceType},
+    };
+
+    struct TestData {
+        info: ResourceInfo<id::BufferId>,
+    }
+
+    impl Resource<id::BufferId> for TestData {
+        const TYPE: ResourceType = "Test data";
+
+        fn as_info(&self) -> &ResourceInfo<id::BufferId> {
+            &self.info
+        }
+
+        fn as_info_mut(&mut self) -> &mut ResourceInfo<id::BufferId> {
+            &mut self.info
+        }
+    }
+
+    #[test]
+    fn label_for_resource_into() {
+        let registry = Registry::without_backend(&IdentityManagerFactory, &Default::default());
+        let (labeled, _) = registry
+            .prepare::<IdentityManagerFactory>(())
+            .assign(TestData {
+                info: ResourceInfo::new("labeled"),
+            });
+        let (unlabeled, _) = registry
+            .prepare::<IdentityManagerFactory>(())
+            .assign(TestData {
+                info: ResourceInfo::new(""),
+            });
+        let invalid = registry
+            .prepare::<IdentityManagerFactory>(())
+            .assign_error("invalid");
+
+        let mut out = String::from("stale contents");
+        for id in [labeled, unlabeled, invalid] {
+            registry.label_for_resource_into(id, &mut out);
+            assert_eq!(out, registry.label_for_resource(id));
+        }
+        assert_eq!(out, "<Invalid-Test data label=invalid>");
+    }
+
+    #[test]
+    fn generate_report_lossy() {
+        let registry = Arc::new(Registry::without_backend(
+            &IdentityManagerFactory,
+            &Default::default(),
+        ));
+        let (_kept, _) = registry
+            .prepare::<IdentityManagerFactory>(())
+            .assign(TestData {
+                info: ResourceInfo::new(""),
+            });
+        let (released, _) = registry
+            .prepare::<IdentityManagerFactory>(())
+            .assign(TestData {
+                info: ResourceInfo::new(""),
+            });
+        registry.unregister(released);
+        registry
+            .prepare::<IdentityManagerFactory>(())
+            .assign_error("invalid");
+
+        let report = registry.generate_report_lossy();
+        assert_eq!(report, registry.generate_report());
+        assert_eq!(report.num_kept_from_user, 1);
+        assert_eq!(report.num_released_from_user, 1);
+        assert_eq!(report.num_error, 1);
+
+        // The report doesn't wait for a concurrent creation holding the lock.
+        let storage = registry.write();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let reader = registry.clone();
+        std::thread::spawn(move || sender.send(reader.generate_report_lossy()));
+        let concurrent = receiver
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("generate_report_lossy blocked on the registry lock");
+        assert_eq!(concurrent, report);
+        drop(storage);
+    }
+
+    #[test]
+    fn lifetime_hook() {
+        let hook = Arc::new(LifetimeHook::default());
+        let registry = Registry::without_backend(&IdentityManagerFactory, &hook);
+
+        // Nothing is recorded until a hook is installed.
+        let (ignored, _) = registry
+            .prepare::<IdentityManagerFactory>(())
+            .assign(TestData {
+                info: ResourceInfo::new(""),
+            });
+        registry.unregister(ignored);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        hook.set(Some(Box::new(move |event| {
+            sink.lock().unwrap().push(event)
+        })));
+
+        let (id, _) = registry
+            .prepare::<IdentityManagerFactory>(())
+            .assign(TestData {
+                info: ResourceInfo::new(""),
+            });
+        registry.unregister(id);
+
+        let event = |lifetime| ResourceEvent {
+            backend: wgt::Backend::Empty,
+            kind: "Test data",
+            id: id.into_raw(),
+            lifetime,
+        };
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                event(ResourceLifetime::Created),
+                event(ResourceLifetime::Dropped)
+            ]
+        );
+    }
+
+    #[cfg(feature = "lock-ordering")]
+    #[test]
+    fn lock_ordering_in_order() {
+        let hook = Arc::new(LifetimeHook::default());
+        let surfaces =
+            Registry::<id::BufferId, TestData>::without_backend(&IdentityManagerFactory, &hook);
+        let buffers = Registry::<id::BufferId, TestData>::new(
+            wgt::Backend::Vulkan,
+            &IdentityManagerFactory,
+            &hook,
+        );
+        let textures = Registry::<id::BufferId, TestData>::new(
+            wgt::Backend::Vulkan,
+            &IdentityManagerFactory,
+            &hook,
+        );
+
+        let surfaces_guard = surfaces.read();
+        // Hub locks may be taken in any order among themselves.
+        let textures_guard = textures.write();
+        let buffers_guard = buffers.read();
+        drop(textures_guard);
+        drop(buffers_guard);
+        drop(surfaces_guard);
+
+        // With the hub locks released, the surfaces may be locked again.
+        let _buffers_guard = buffers.read();
+        drop(_buffers_guard);
+        let _surfaces_guard = surfaces.write();
+    }
+
+    #[cfg(feature = "lock-ordering")]
+    #[test]
+    #[should_panic(
+        expected = "Lock ordering violation: acquiring the Test data lock (Surfaces) while holding the Test data lock (Hub)"
+    )]
+    fn lock_ordering_violation() {
+        let hook = Arc::new(LifetimeHook::default());
+        let surfaces =
+            Registry::<id::BufferId, TestData>::without_backend(&IdentityManagerFactory, &hook);
+        let hub = Registry::<id::BufferId, TestData>::new(
+            wgt::Backend::Vulkan,
+            &IdentityManagerFactory,
+            &hook,
+        );
+
+        let _hub_guard = hub.read();
+        let _surfaces_guard = surfaces.read();
+    }
 }
@@ -13,7 +13,8 @@ use crate::{
     },
     identity::{GlobalIdentityHandlerFactory, IdentityManager},
     init_tracker::{BufferInitTracker, TextureInitTracker},
-    resource, resource_log,
+    registry::ResourceLogEvent,
+    resource,
     snatch::{ExclusiveSnatchGuard, SnatchGuard, Snatchable},
     track::TextureSelector,
     validation::MissingBufferUsageError,
@@ -28,14 +29,17 @@ use wgt::WasmNotSendSync;
 
 use std::{
     borrow::Borrow,
-    fmt::Debug,
+    fmt::{self, Debug},
+    future::Future,
     iter, mem,
     ops::Range,
+    pin::Pin,
     ptr::NonNull,
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
+    task::{Context, Poll, Waker},
 };
 
 /// Information about the wgpu-core resource.
@@ -113,6 +117,11 @@ impl<Id: TypedId> ResourceInfo<Id> {
         self.id.unwrap()
     }
 
+    /// The id of the resource, or `None` if it hasn't been registered.
+    pub(crate) fn try_id(&self) -> Option<Id> {
+        self.id
+    }
+
     pub(crate) fn set_id(&mut self, id: Id, identity: &Arc<IdentityManager<Id>>) {
         self.id = Some(id);
         self.identity = Some(identity.clone());
@@ -137,7 +146,14 @@ pub trait Resource<Id: TypedId>: 'static + WasmNotSendSync {
     fn as_info(&self) -> &ResourceInfo<Id>;
     fn as_info_mut(&mut self) -> &mut ResourceInfo<Id>;
     fn label(&self) -> String {
-        self.as_info().label.clone()
+        let mut label = String::new();
+        self.label_into(&mut label);
+        label
+    }
+    /// Append the label of this resource to `out` without allocating a new
+    /// `String`.
+    fn label_into(&self, out: &mut String) {
+        out.push_str(&self.as_info().label);
     }
     fn ref_count(self: &Arc<Self>) -> usize {
         Arc::strong_count(self)
@@ -148,6 +164,22 @@ pub trait Resource<Id: TypedId>: 'static + WasmNotSendSync {
     fn is_equal(&self, other: &Self) -> bool {
         self.as_info().id().unzip() == other.as_info().id().unzip()
     }
+    /// Report `event` for this resource to the resource logger of `device`,
+    /// or log `message` with `resource_log!` if none is set.
+    fn log_event<A: HalApi>(
+        &self,
+        device: &Device<A>,
+        event: ResourceLogEvent,
+        message: fmt::Arguments<'_>,
+    ) {
+        device.resource_logger.log_event(
+            A::VARIANT,
+            Self::TYPE,
+            self.as_info().try_id(),
+            event,
+            message,
+        );
+    }
 }
 
 /// The status code provided to the buffer mapping callback.
@@ -338,6 +370,77 @@ pub struct BufferMapOperation {
     pub callback: Option<BufferMapCallback>,
 }
 
+#[derive(Debug, Default)]
+struct BufferMapFutureState {
+    result: Option<BufferAccessResult>,
+    waker: Option<Waker>,
+}
+
+/// Completes the [`BufferMapFuture`] it was created with, or aborts it if
+/// dropped without a result.
+struct BufferMapFutureSender {
+    state: Option<Arc<Mutex<BufferMapFutureState>>>,
+}
+
+impl BufferMapFutureSender {
+    fn send(&mut self, result: BufferAccessResult) {
+        let Some(state) = self.state.take() else {
+            return;
+        };
+        let waker = {
+            let mut state = state.lock();
+            state.result = Some(result);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for BufferMapFutureSender {
+    fn drop(&mut self) {
+        self.send(Err(BufferAccessError::MapAborted));
+    }
+}
+
+/// A future resolving to the result of a buffer mapping.
+///
+/// Returned by [`Global::buffer_map_async_future`]. It does not drive the
+/// device on its own: it only completes once the mapping callback has been
+/// called, which requires the device to be polled.
+#[derive(Debug)]
+pub struct BufferMapFuture {
+    state: Arc<Mutex<BufferMapFutureState>>,
+}
+
+impl BufferMapFuture {
+    /// Create a future together with the callback which completes it.
+    pub(crate) fn new() -> (BufferMapCallback, Self) {
+        let state = Arc::new(Mutex::new(BufferMapFutureState::default()));
+        let mut sender = BufferMapFutureSender {
+            state: Some(state.clone()),
+        };
+        let callback = BufferMapCallback::from_rust(Box::new(move |result| sender.send(result)));
+        (callback, Self { state })
+    }
+}
+
+impl Future for BufferMapFuture {
+    type Output = BufferAccessResult;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, Error)]
 #[non_exhaustive]
 pub enum BufferAccessError {
@@ -357,6 +460,8 @@ pub enum BufferAccessError {
     MissingBufferUsage(#[from] MissingBufferUsageError),
     #[error("Buffer is not mapped")]
     NotMapped,
+    #[error("Buffer is mapped for reading, and its mapped range can't be written to")]
+    MappedForReadOnly,
     #[error(
         "Buffer map range must start aligned to `MAP_ALIGNMENT` and end to `COPY_BUFFER_ALIGNMENT`"
     )]
@@ -398,6 +503,20 @@ pub(crate) struct BufferPendingMapping<A: HalApi> {
 
 pub type BufferDescriptor<'a> = wgt::BufferDescriptor<Label<'a>>;
 
+/// Whether the GPU was done with a buffer when it was dropped, see
+/// [`Global::buffer_drop_tracked`](crate::global::Global::buffer_drop_tracked).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferDropResult {
+    /// No submission uses the buffer anymore, so its memory is released on
+    /// the next device maintenance.
+    Freed,
+    /// The buffer is still used by a submission, or by writes that haven't
+    /// been submitted yet, and is released once those are done.
+    Deferred,
+    /// The id wasn't a valid buffer, so nothing was dropped.
+    Invalid,
+}
+
 #[derive(Debug)]
 pub struct Buffer<A: HalApi> {
     pub(crate) raw: Snatchable<A::Buffer>,
@@ -413,7 +532,11 @@ pub struct Buffer<A: HalApi> {
 impl<A: HalApi> Drop for Buffer<A> {
     fn drop(&mut self) {
         if let Some(raw) = self.raw.take() {
-            resource_log!("Destroy raw Buffer (dropped) {:?}", self.info.label());
+            self.log_event(
+                &self.device,
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw Buffer (dropped) {:?}", self.info.label()),
+            );
 
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
@@ -649,7 +772,13 @@ impl<A: HalApi> DestroyedBuffer<A> {
 impl<A: HalApi> Drop for DestroyedBuffer<A> {
     fn drop(&mut self) {
         if let Some(raw) = self.raw.take() {
-            resource_log!("Destroy raw Buffer (destroyed) {:?}", self.label());
+            self.device.resource_logger.log_event(
+                A::VARIANT,
+                Buffer::<A>::TYPE,
+                Some(self.id),
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw Buffer (destroyed) {:?}", self.label()),
+            );
 
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
@@ -695,7 +824,11 @@ pub struct StagingBuffer<A: HalApi> {
 impl<A: HalApi> Drop for StagingBuffer<A> {
     fn drop(&mut self) {
         if let Some(raw) = self.raw.lock().take() {
-            resource_log!("Destroy raw StagingBuffer {:?}", self.info.label());
+            self.log_event(
+                &self.device,
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw StagingBuffer {:?}", self.info.label()),
+            );
             unsafe {
                 use hal::Device;
                 self.device.raw().destroy_buffer(raw);
@@ -715,13 +848,59 @@ impl<A: HalApi> Resource<StagingBufferId> for StagingBuffer<A> {
         &mut self.info
     }
 
-    fn label(&self) -> String {
-        String::from("<StagingBuffer>")
+    fn label_into(&self, out: &mut String) {
+        out.push_str("<StagingBuffer>");
     }
 }
 
 pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>, Vec<wgt::TextureFormat>>;
 
+/// Estimate the number of bytes needed to hold a texture described by `desc`,
+/// with every mip level.
+///
+/// This is the tightly packed size of its texel blocks. Backends may need more
+/// for alignment and metadata. Formats with a depth aspect which doesn't have
+/// a defined size, like `Depth24Plus`, count as 4 bytes of depth.
+pub(crate) fn estimate_texture_allocation(desc: &TextureDescriptor) -> u64 {
+    let format = desc.format;
+    let (block_width, block_height) = format.block_dimensions();
+
+    let block_bytes = match format {
+        wgt::TextureFormat::NV12 => None,
+        _ => Some(format.block_copy_size(None).unwrap_or_else(|| {
+            let depth = format
+                .block_copy_size(Some(wgt::TextureAspect::DepthOnly))
+                .unwrap_or(4);
+            let stencil = format
+                .block_copy_size(Some(wgt::TextureAspect::StencilOnly))
+                .unwrap_or(0);
+            depth + stencil
+        }) as u64),
+    };
+
+    (0..desc.mip_level_count)
+        .map(|level| {
+            let size = desc.size.mip_level_size(level, desc.dimension);
+            let layers = size.depth_or_array_layers as u64 * desc.sample_count as u64;
+            let bytes = match block_bytes {
+                Some(block_bytes) => {
+                    let blocks_wide = ((size.width + block_width - 1) / block_width) as u64;
+                    let blocks_high = ((size.height + block_height - 1) / block_height) as u64;
+                    blocks_wide * blocks_high * block_bytes
+                }
+                // A full resolution luma plane, and a half resolution
+                // plane of two chroma bytes.
+                None => {
+                    let luma = size.width as u64 * size.height as u64;
+                    let chroma = ((size.width + 1) / 2) as u64 * ((size.height + 1) / 2) as u64;
+                    luma + chroma * 2
+                }
+            };
+            bytes * layers
+        })
+        .sum()
+}
+
 #[derive(Debug)]
 pub(crate) enum TextureInner<A: HalApi> {
     Native {
@@ -775,7 +954,11 @@ pub struct Texture<A: HalApi> {
 
 impl<A: HalApi> Drop for Texture<A> {
     fn drop(&mut self) {
-        resource_log!("Destroy raw Texture {:?}", self.info.label());
+        self.log_event(
+            &self.device,
+            ResourceLogEvent::Destroyed,
+            format_args!("Destroy raw Texture {:?}", self.info.label()),
+        );
         use hal::Device;
         let mut clear_mode = self.clear_mode.write();
         let clear_mode = &mut *clear_mode;
@@ -1019,7 +1202,13 @@ impl<A: HalApi> DestroyedTexture<A> {
 impl<A: HalApi> Drop for DestroyedTexture<A> {
     fn drop(&mut self) {
         if let Some(raw) = self.raw.take() {
-            resource_log!("Destroy raw Texture (destroyed) {:?}", self.label());
+            self.device.resource_logger.log_event(
+                A::VARIANT,
+                Texture::<A>::TYPE,
+                Some(self.id),
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw Texture (destroyed) {:?}", self.label()),
+            );
 
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
@@ -1217,7 +1406,11 @@ pub struct TextureView<A: HalApi> {
 impl<A: HalApi> Drop for TextureView<A> {
     fn drop(&mut self) {
         if let Some(raw) = self.raw.take() {
-            resource_log!("Destroy raw TextureView {:?}", self.info.label());
+            self.log_event(
+                &self.device,
+                ResourceLogEvent::Destroyed,
+                format_args!("Destroy raw TextureView {:?}", self.info.label()),
+            );
 
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
@@ -1344,7 +1537,11 @@ pub struct Sampler<A: HalApi> {
 
 impl<A: HalApi> Drop for Sampler<A> {
     fn drop(&mut self) {
-        resource_log!("Destroy raw Sampler {:?}", self.info.label());
+        self.log_event(
+            &self.device,
+            ResourceLogEvent::Destroyed,
+            format_args!("Destroy raw Sampler {:?}", self.info.label()),
+        );
         if let Some(raw) = self.raw.take() {
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
@@ -1436,6 +1633,10 @@ pub enum CreateQuerySetError {
 
 pub type QuerySetDescriptor<'a> = wgt::QuerySetDescriptor<Label<'a>>;
 
+#[derive(Clone, Debug, Error)]
+#[error("Query set is invalid")]
+pub struct InvalidQuerySet;
+
 #[derive(Debug)]
 pub struct QuerySet<A: HalApi> {
     pub(crate) raw: Option<A::QuerySet>,
@@ -1446,7 +1647,11 @@ pub struct QuerySet<A: HalApi> {
 
 impl<A: HalApi> Drop for QuerySet<A> {
     fn drop(&mut self) {
-        resource_log!("Destroy raw QuerySet {:?}", self.info.label());
+        self.log_event(
+            &self.device,
+            ResourceLogEvent::Destroyed,
+            format_args!("Destroy raw QuerySet {:?}", self.info.label()),
+        );
         if let Some(raw) = self.raw.take() {
             #[cfg(feature = "trace")]
             if let Some(t) = self.device.trace.lock().as_mut() {
@@ -1487,3 +1692,81 @@ pub enum DestroyError {
     #[error("Resource is already destroyed")]
     AlreadyDestroyed,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    use super::{BufferAccessError, BufferMapFuture};
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn buffer_map_future_resolves_from_callback() {
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let (callback, mut future) = BufferMapFuture::new();
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+
+        // This is what polling the device to completion ends up doing.
+        callback.call(Ok(()));
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            Pin::new(&mut future).poll(&mut cx),
+            Poll::Ready(Ok(()))
+        ));
+    }
+
+    #[test]
+    fn buffer_map_future_aborts_on_dropped_callback() {
+        let (callback, mut future) = BufferMapFuture::new();
+        drop(callback);
+
+        let waker = Waker::from(Arc::new(CountingWaker::default()));
+        let mut cx = Context::from_waker(&waker);
+        assert!(matches!(
+            Pin::new(&mut future).poll(&mut cx),
+            Poll::Ready(Err(BufferAccessError::MapAborted))
+        ));
+    }
+
+    #[test]
+    fn estimate_texture_allocation() {
+        let desc = super::TextureDescriptor {
+            label: None,
+            size: wgt::Extent3d {
+                width: 256,
+                height: 128,
+                depth_or_array_layers: 2,
+            },
+            mip_level_count: 9,
+            sample_count: 1,
+            dimension: wgt::TextureDimension::D2,
+            format: wgt::TextureFormat::Rgba8Unorm,
+            usage: wgt::TextureUsages::TEXTURE_BINDING,
+            view_formats: Vec::new(),
+        };
+
+        // 256x128, 128x64, 64x32, 32x16, 16x8, 8x4, 4x2, 2x1 and 1x1 texels
+        // of 4 bytes each, for both layers.
+        let texels = 32768 + 8192 + 2048 + 512 + 128 + 32 + 8 + 2 + 1;
+        assert_eq!(super::estimate_texture_allocation(&desc), texels * 4 * 2);
+    }
+}
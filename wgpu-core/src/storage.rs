@@ -1,4 +1,11 @@
-use std::{marker::PhantomData, ops, sync::Arc};
+use std::{
+    marker::PhantomData,
+    ops,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use wgt::Backend;
 
@@ -24,6 +31,49 @@ pub(crate) enum Element<T> {
 #[derive(Clone, Debug)]
 pub(crate) struct InvalidId;
 
+/// The number of entries of each kind in a [`Storage`].
+///
+/// This is shared with the [`Registry`] owning the storage, so that the
+/// counts can be read without locking the storage. They are updated with
+/// relaxed ordering, and may be momentarily inconsistent with each other.
+///
+/// [`Registry`]: crate::registry::Registry
+#[derive(Debug, Default)]
+pub(crate) struct ElementCounts {
+    len: AtomicUsize,
+    occupied: AtomicUsize,
+    error: AtomicUsize,
+}
+
+impl ElementCounts {
+    fn counter<T>(&self, element: &Element<T>) -> Option<&AtomicUsize> {
+        match *element {
+            Element::Vacant => None,
+            Element::Occupied(..) => Some(&self.occupied),
+            Element::Error(..) => Some(&self.error),
+        }
+    }
+
+    /// Account for `old` having been replaced by `new`.
+    fn replaced<T>(&self, old: &Element<T>, new: &Element<T>) {
+        if let Some(counter) = self.counter(old) {
+            counter.fetch_sub(1, Ordering::Relaxed);
+        }
+        if let Some(counter) = self.counter(new) {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The number of entries, and of those the occupied and error ones.
+    pub(crate) fn load(&self) -> (usize, usize, usize) {
+        (
+            self.len.load(Ordering::Relaxed),
+            self.occupied.load(Ordering::Relaxed),
+            self.error.load(Ordering::Relaxed),
+        )
+    }
+}
+
 /// A table of `T` values indexed by the id type `I`.
 ///
 /// The table is represented as a vector indexed by the ids' index
@@ -36,6 +86,7 @@ where
     I: id::TypedId,
 {
     pub(crate) map: Vec<Element<T>>,
+    counts: Arc<ElementCounts>,
     kind: &'static str,
     _phantom: PhantomData<I>,
 }
@@ -58,6 +109,7 @@ where
     pub(crate) fn new() -> Self {
         Self {
             map: Vec::new(),
+            counts: Arc::default(),
             kind: T::TYPE,
             _phantom: PhantomData,
         }
@@ -136,11 +188,24 @@ where
         }
     }
 
+    /// The element counts of this storage, kept up to date as it changes.
+    pub(crate) fn counts(&self) -> &Arc<ElementCounts> {
+        &self.counts
+    }
+
+    /// Replace the element at `index`, which must be in bounds, keeping the
+    /// counts up to date.
+    fn replace(&mut self, index: usize, element: Element<T>) -> Element<T> {
+        self.counts.replaced(&self.map[index], &element);
+        std::mem::replace(&mut self.map[index], element)
+    }
+
     fn insert_impl(&mut self, index: usize, epoch: Epoch, element: Element<T>) {
         if index >= self.map.len() {
             self.map.resize_with(index + 1, || Element::Vacant);
+            self.counts.len.store(self.map.len(), Ordering::Relaxed);
         }
-        match std::mem::replace(&mut self.map[index], element) {
+        match self.replace(index, element) {
             Element::Vacant => {}
             Element::Occupied(_, storage_epoch) => {
                 assert_ne!(
@@ -179,10 +244,7 @@ where
 
     pub(crate) fn replace_with_error(&mut self, id: I) -> Result<Arc<T>, InvalidId> {
         let (index, epoch, _) = id.unzip();
-        match std::mem::replace(
-            &mut self.map[index as usize],
-            Element::Error(epoch, String::new()),
-        ) {
+        match self.replace(index as usize, Element::Error(epoch, String::new())) {
             Element::Vacant => panic!("Cannot access vacant resource"),
             Element::Occupied(value, storage_epoch) => {
                 assert_eq!(epoch, storage_epoch);
@@ -195,13 +257,13 @@ where
     pub(crate) fn force_replace(&mut self, id: I, value: T) {
         log::trace!("User is replacing {}{:?}", T::TYPE, id);
         let (index, epoch, _) = id.unzip();
-        self.map[index as usize] = Element::Occupied(Arc::new(value), epoch);
+        self.replace(index as usize, Element::Occupied(Arc::new(value), epoch));
     }
 
     pub(crate) fn remove(&mut self, id: I) -> Option<Arc<T>> {
         log::trace!("User is removing {}{:?}", T::TYPE, id);
         let (index, epoch, _) = id.unzip();
-        match std::mem::replace(&mut self.map[index as usize], Element::Vacant) {
+        match self.replace(index as usize, Element::Vacant) {
             Element::Occupied(value, storage_epoch) => {
                 assert_eq!(epoch, storage_epoch);
                 Some(value)
@@ -211,6 +273,30 @@ where
         }
     }
 
+    /// Release every occupied entry for which `f` returns `false`.
+    ///
+    /// Released entries become errors rather than vacant, since the user
+    /// still holds their ids: using or dropping one of them then fails like
+    /// for any invalid id. Error entries are left untouched.
+    pub(crate) fn retain<F: FnMut(&Arc<T>) -> bool>(&mut self, mut f: F) {
+        for index in 0..self.map.len() {
+            let (epoch, label) = match self.map[index] {
+                Element::Occupied(ref value, epoch) if !f(value) => (epoch, value.label()),
+                _ => continue,
+            };
+            log::trace!("User is releasing {}", T::TYPE);
+            self.replace(index, Element::Error(epoch, label));
+        }
+    }
+
+    /// Remove every entry.
+    pub(crate) fn clear(&mut self) {
+        self.map.clear();
+        self.counts.len.store(0, Ordering::Relaxed);
+        self.counts.occupied.store(0, Ordering::Relaxed);
+        self.counts.error.store(0, Ordering::Relaxed);
+    }
+
     pub(crate) fn iter(&self, backend: Backend) -> impl Iterator<Item = (I, &Arc<T>)> {
         self.map
             .iter()
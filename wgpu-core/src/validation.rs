@@ -953,6 +953,13 @@ impl Interface {
         }
     }
 
+    /// The workgroup size of the compute entry point `entry_point_name`, if
+    /// the module has one.
+    pub(crate) fn compute_workgroup_size(&self, entry_point_name: &str) -> Option<[u32; 3]> {
+        let pair = (naga::ShaderStage::Compute, entry_point_name.to_string());
+        self.entry_points.get(&pair).map(|ep| ep.workgroup_size)
+    }
+
     pub fn check_stage(
         &self,
         layouts: &mut BindingLayoutSource<'_>,
@@ -936,6 +936,9 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         };
     }
 
+    // The `*_colored` markers deliberately use the default implementations,
+    // which drop the color: D3D12 only takes a color as part of a
+    // PIX-encoded event blob, which we don't produce.
     unsafe fn insert_debug_marker(&mut self, label: &str) {
         let (wide_label, size) = self.temp.prepare_marker(label);
         unsafe {
@@ -1572,6 +1572,10 @@ impl crate::Device<super::Api> for super::Device {
         }
     }
 
+    unsafe fn get_calibrated_presentation_timestamp(&self) -> Option<wgt::PresentationTimestamp> {
+        None
+    }
+
     unsafe fn get_acceleration_structure_build_sizes<'a>(
         &self,
         _desc: &crate::GetAccelerationStructureBuildSizesDescriptor<'a, super::Api>,
@@ -239,6 +239,9 @@ impl crate::Device<Api> for Context {
         false
     }
     unsafe fn stop_capture(&self) {}
+    unsafe fn get_calibrated_presentation_timestamp(&self) -> Option<wgt::PresentationTimestamp> {
+        None
+    }
     unsafe fn create_acceleration_structure(
         &self,
         desc: &crate::AccelerationStructureDescriptor,
@@ -1476,6 +1476,9 @@ impl crate::Device<super::Api> for super::Device {
                 .end_frame_capture(ptr::null_mut(), ptr::null_mut())
         }
     }
+    unsafe fn get_calibrated_presentation_timestamp(&self) -> Option<wgt::PresentationTimestamp> {
+        None
+    }
     unsafe fn create_acceleration_structure(
         &self,
         _desc: &crate::AccelerationStructureDescriptor,
@@ -389,6 +389,13 @@ pub trait Device<A: Api>: WasmNotSendSync {
     unsafe fn start_capture(&self) -> bool;
     unsafe fn stop_capture(&self);
 
+    /// Sample the presentation clock through the backend's calibrated
+    /// timestamp facility, which reads it together with the device clock.
+    ///
+    /// Returns `None` if the device has no such facility, in which case
+    /// [`Adapter::get_presentation_timestamp`] should be used instead.
+    unsafe fn get_calibrated_presentation_timestamp(&self) -> Option<wgt::PresentationTimestamp>;
+
     unsafe fn create_acceleration_structure(
         &self,
         desc: &AccelerationStructureDescriptor,
@@ -542,6 +549,23 @@ pub trait CommandEncoder<A: Api>: WasmNotSendSync + fmt::Debug {
     unsafe fn begin_debug_marker(&mut self, group_label: &str);
     unsafe fn end_debug_marker(&mut self);
 
+    /// Like [`CommandEncoder::insert_debug_marker`], with an RGBA `color` for
+    /// graphics debuggers.
+    ///
+    /// Backends which can't attach a color to markers ignore it.
+    unsafe fn insert_debug_marker_colored(&mut self, label: &str, color: [f32; 4]) {
+        let _ = color;
+        unsafe { self.insert_debug_marker(label) }
+    }
+    /// Like [`CommandEncoder::begin_debug_marker`], with an RGBA `color` for
+    /// graphics debuggers.
+    ///
+    /// Backends which can't attach a color to markers ignore it.
+    unsafe fn begin_debug_marker_colored(&mut self, group_label: &str, color: [f32; 4]) {
+        let _ = color;
+        unsafe { self.begin_debug_marker(group_label) }
+    }
+
     // queries
 
     /// # Safety:
@@ -675,6 +699,10 @@ bitflags!(
     pub struct BindGroupLayoutFlags: u32 {
         /// Allows for bind group binding arrays to be shorter than the array in the BGL.
         const PARTIALLY_BOUND = 1 << 0;
+        /// Allocates bind groups so their bindings may be updated after being bound.
+        ///
+        /// Must not be used with buffer bindings which have a dynamic offset.
+        const UPDATE_AFTER_BIND = 1 << 1;
     }
 );
 
@@ -1221,6 +1221,10 @@ impl crate::Device<super::Api> for super::Device {
         shared_capture_manager.stop_capture();
     }
 
+    unsafe fn get_calibrated_presentation_timestamp(&self) -> Option<wgt::PresentationTimestamp> {
+        None
+    }
+
     unsafe fn get_acceleration_structure_build_sizes(
         &self,
         _desc: &crate::GetAccelerationStructureBuildSizesDescriptor<super::Api>,
@@ -18,6 +18,7 @@ fn indexing_features() -> wgt::Features {
     wgt::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
         | wgt::Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING
         | wgt::Features::PARTIALLY_BOUND_BINDING_ARRAY
+        | wgt::Features::UPDATE_AFTER_BIND_BINDING_ARRAY
 }
 
 /// Aggregate of the `vk::PhysicalDevice*Features` structs used by `gfx`.
@@ -121,6 +122,8 @@ impl PhysicalDeviceFeatures {
         );
         let needs_partially_bound =
             requested_features.intersects(wgt::Features::PARTIALLY_BOUND_BINDING_ARRAY);
+        let needs_update_after_bind =
+            requested_features.intersects(wgt::Features::UPDATE_AFTER_BIND_BINDING_ARRAY);
 
         Self {
             // vk::PhysicalDeviceFeatures is a struct composed of Bool32's while
@@ -212,6 +215,14 @@ impl PhysicalDeviceFeatures {
                             needs_storage_buffer_non_uniform,
                         )
                         .descriptor_binding_partially_bound(needs_partially_bound)
+                        .descriptor_binding_sampled_image_update_after_bind(needs_update_after_bind)
+                        .descriptor_binding_storage_image_update_after_bind(needs_update_after_bind)
+                        .descriptor_binding_uniform_buffer_update_after_bind(
+                            needs_update_after_bind,
+                        )
+                        .descriptor_binding_storage_buffer_update_after_bind(
+                            needs_update_after_bind,
+                        )
                         .build(),
                 )
             } else {
@@ -519,6 +530,13 @@ impl PhysicalDeviceFeatures {
             if descriptor_indexing.descriptor_binding_partially_bound != 0 && !intel_windows {
                 features |= F::PARTIALLY_BOUND_BINDING_ARRAY;
             }
+            if descriptor_indexing.descriptor_binding_sampled_image_update_after_bind != 0
+                && descriptor_indexing.descriptor_binding_storage_image_update_after_bind != 0
+                && descriptor_indexing.descriptor_binding_uniform_buffer_update_after_bind != 0
+                && descriptor_indexing.descriptor_binding_storage_buffer_update_after_bind != 0
+            {
+                features |= F::UPDATE_AFTER_BIND_BINDING_ARRAY;
+            }
         }
 
         features.set(F::DEPTH_CLIP_CONTROL, self.core.depth_clamp != 0);
@@ -774,6 +792,13 @@ impl PhysicalDeviceCapabilities {
             extensions.push(vk::ExtRobustness2Fn::name());
         }
 
+        // Optional `VK_EXT_calibrated_timestamps`, only useful where the
+        // presentation clock is `CLOCK_MONOTONIC`
+        #[cfg(unix)]
+        if self.supports_extension(vk::ExtCalibratedTimestampsFn::name()) {
+            extensions.push(vk::ExtCalibratedTimestampsFn::name());
+        }
+
         // Require `VK_KHR_draw_indirect_count` if the associated feature was requested
         // Even though Vulkan 1.2 has promoted the extension to core, we must require the extension to avoid
         // large amounts of spaghetti involved with using PhysicalDeviceVulkan12Features.
@@ -1310,6 +1335,29 @@ impl super::Adapter {
         supported_extensions
     }
 
+    /// Query the time domains `VK_EXT_calibrated_timestamps` can sample.
+    unsafe fn calibrateable_time_domains(
+        &self,
+        fns: &vk::ExtCalibratedTimestampsFn,
+    ) -> Vec<vk::TimeDomainEXT> {
+        let get_time_domains = fns.get_physical_device_calibrateable_time_domains_ext;
+
+        let mut count = 0;
+        let result = unsafe { get_time_domains(self.raw, &mut count, std::ptr::null_mut()) };
+        if result != vk::Result::SUCCESS {
+            return Vec::new();
+        }
+
+        let mut time_domains = vec![vk::TimeDomainEXT::DEVICE; count as usize];
+        let result = unsafe { get_time_domains(self.raw, &mut count, time_domains.as_mut_ptr()) };
+        if result != vk::Result::SUCCESS {
+            return Vec::new();
+        }
+
+        time_domains.truncate(count as usize);
+        time_domains
+    }
+
     /// `features` must be the same features used to create `enabled_extensions`.
     pub fn physical_device_features(
         &self,
@@ -1392,6 +1440,26 @@ impl super::Adapter {
         } else {
             None
         };
+        let calibrated_timestamps_fn =
+            if enabled_extensions.contains(&vk::ExtCalibratedTimestampsFn::name()) {
+                let fns = vk::ExtCalibratedTimestampsFn::load(|name| unsafe {
+                    std::mem::transmute(
+                        self.instance
+                            .entry
+                            .get_instance_proc_addr(self.instance.raw.handle(), name.as_ptr()),
+                    )
+                });
+                let time_domains = unsafe { self.calibrateable_time_domains(&fns) };
+                if time_domains.contains(&vk::TimeDomainEXT::DEVICE)
+                    && time_domains.contains(&vk::TimeDomainEXT::CLOCK_MONOTONIC)
+                {
+                    Some(fns)
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
 
         let naga_options = {
             use naga::back::spv;
@@ -1512,6 +1580,7 @@ impl super::Adapter {
                 draw_indirect_count: indirect_count_fn,
                 timeline_semaphore: timeline_semaphore_fn,
                 ray_tracing: ray_tracing_fns,
+                calibrated_timestamps: calibrated_timestamps_fn,
             },
             vendor_id: self.phd_capabilities.properties.vendor_id,
             timestamp_period: self.phd_capabilities.properties.limits.timestamp_period,
@@ -852,16 +852,29 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
     }
 
     unsafe fn insert_debug_marker(&mut self, label: &str) {
+        // An all-zero color means "no color" to Vulkan.
+        unsafe { self.insert_debug_marker_colored(label, [0.0; 4]) }
+    }
+    unsafe fn begin_debug_marker(&mut self, group_label: &str) {
+        unsafe { self.begin_debug_marker_colored(group_label, [0.0; 4]) }
+    }
+    unsafe fn insert_debug_marker_colored(&mut self, label: &str, color: [f32; 4]) {
         if let Some(ext) = self.device.debug_messenger() {
             let cstr = self.temp.make_c_str(label);
-            let vk_label = vk::DebugUtilsLabelEXT::builder().label_name(cstr).build();
+            let vk_label = vk::DebugUtilsLabelEXT::builder()
+                .label_name(cstr)
+                .color(color)
+                .build();
             unsafe { ext.cmd_insert_debug_utils_label(self.active, &vk_label) };
         }
     }
-    unsafe fn begin_debug_marker(&mut self, group_label: &str) {
+    unsafe fn begin_debug_marker_colored(&mut self, group_label: &str, color: [f32; 4]) {
         if let Some(ext) = self.device.debug_messenger() {
             let cstr = self.temp.make_c_str(group_label);
-            let vk_label = vk::DebugUtilsLabelEXT::builder().label_name(cstr).build();
+            let vk_label = vk::DebugUtilsLabelEXT::builder()
+                .label_name(cstr)
+                .color(color)
+                .build();
             unsafe { ext.cmd_begin_debug_utils_label(self.active, &vk_label) };
         }
     }
@@ -1299,7 +1299,17 @@ impl crate::Device<super::Api> for super::Device {
             })
             .collect::<Vec<_>>();
 
-        let vk_info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(&vk_bindings);
+        let update_after_bind = desc
+            .flags
+            .contains(crate::BindGroupLayoutFlags::UPDATE_AFTER_BIND);
+
+        let vk_info = vk::DescriptorSetLayoutCreateInfo::builder()
+            .bindings(&vk_bindings)
+            .flags(if update_after_bind {
+                vk::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND_POOL
+            } else {
+                vk::DescriptorSetLayoutCreateFlags::empty()
+            });
 
         let binding_arrays = desc
             .entries
@@ -1315,7 +1325,7 @@ impl crate::Device<super::Api> for super::Device {
             .flags
             .contains(crate::BindGroupLayoutFlags::PARTIALLY_BOUND);
 
-        let vk_info = if partially_bound {
+        let vk_info = if partially_bound || update_after_bind {
             binding_flag_vec = desc
                 .entries
                 .iter()
@@ -1326,6 +1336,12 @@ impl crate::Device<super::Api> for super::Device {
                         flags |= vk::DescriptorBindingFlags::PARTIALLY_BOUND;
                     }
 
+                    // Dynamic offsets are rejected in such layouts, and the
+                    // feature doesn't cover acceleration structures.
+                    if update_after_bind && entry.ty != wgt::BindingType::AccelerationStructure {
+                        flags |= vk::DescriptorBindingFlags::UPDATE_AFTER_BIND;
+                    }
+
                     flags
                 })
                 .collect::<Vec<_>>();
@@ -1351,9 +1367,16 @@ impl crate::Device<super::Api> for super::Device {
             };
         }
 
+        let desc_flags = if update_after_bind {
+            gpu_descriptor::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND
+        } else {
+            gpu_descriptor::DescriptorSetLayoutCreateFlags::empty()
+        };
+
         Ok(super::BindGroupLayout {
             raw,
             desc_count,
+            desc_flags,
             types: types.into_boxed_slice(),
             binding_arrays,
         })
@@ -1439,7 +1462,7 @@ impl crate::Device<super::Api> for super::Device {
             self.desc_allocator.lock().allocate(
                 &*self.shared,
                 &desc.layout.raw,
-                gpu_descriptor::DescriptorSetLayoutCreateFlags::empty(),
+                desc.layout.desc_flags,
                 &desc.layout.desc_count,
                 1,
             )?
@@ -2094,6 +2117,41 @@ impl crate::Device<super::Api> for super::Device {
         }
     }
 
+    unsafe fn get_calibrated_presentation_timestamp(&self) -> Option<wgt::PresentationTimestamp> {
+        // The presentation clock is `CLOCK_MONOTONIC`, see
+        // `Adapter::get_presentation_timestamp`.
+        let calibrated_timestamps = self.shared.extension_fns.calibrated_timestamps.as_ref()?;
+
+        let infos = [
+            vk::CalibratedTimestampInfoEXT::builder()
+                .time_domain(vk::TimeDomainEXT::DEVICE)
+                .build(),
+            vk::CalibratedTimestampInfoEXT::builder()
+                .time_domain(vk::TimeDomainEXT::CLOCK_MONOTONIC)
+                .build(),
+        ];
+        let mut timestamps = [0u64; 2];
+        let mut max_deviation = 0u64;
+
+        let result = unsafe {
+            (calibrated_timestamps.get_calibrated_timestamps_ext)(
+                self.shared.raw.handle(),
+                infos.len() as u32,
+                infos.as_ptr(),
+                timestamps.as_mut_ptr(),
+                &mut max_deviation,
+            )
+        };
+
+        match result {
+            vk::Result::SUCCESS => Some(wgt::PresentationTimestamp(timestamps[1] as u128)),
+            other => {
+                log::warn!("vkGetCalibratedTimestampsEXT failed: {other:?}");
+                None
+            }
+        }
+    }
+
     unsafe fn get_acceleration_structure_build_sizes<'a>(
         &self,
         desc: &crate::GetAccelerationStructureBuildSizesDescriptor<'a, super::Api>,
@@ -220,6 +220,8 @@ struct DeviceExtensionFunctions {
     draw_indirect_count: Option<khr::DrawIndirectCount>,
     timeline_semaphore: Option<ExtensionFn<khr::TimelineSemaphore>>,
     ray_tracing: Option<RayTracingDeviceExtensionFunctions>,
+    /// Only loaded if `CLOCK_MONOTONIC` is a calibrateable time domain.
+    calibrated_timestamps: Option<vk::ExtCalibratedTimestampsFn>,
 }
 
 struct RayTracingDeviceExtensionFunctions {
@@ -434,6 +436,8 @@ pub struct Sampler {
 pub struct BindGroupLayout {
     raw: vk::DescriptorSetLayout,
     desc_count: gpu_descriptor::DescriptorTotalCount,
+    /// Flags to allocate descriptor sets of this layout with.
+    desc_flags: gpu_descriptor::DescriptorSetLayoutCreateFlags,
     types: Box<[(vk::DescriptorType, u32)]>,
     /// Map of binding index to size,
     binding_arrays: Vec<(u32, NonZeroU32)>,
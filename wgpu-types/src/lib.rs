@@ -787,8 +787,14 @@ bitflags::bitflags! {
         ///
         /// This is a native-only feature.
         const RAY_TRACING_ACCELERATION_STRUCTURE = 1 << 56;
-
-        // 57 available
+        /// Allows the user to create bind group layouts with
+        /// [`BindGroupLayoutFlags::UPDATE_AFTER_BIND`].
+        ///
+        /// Supported platforms:
+        /// - Vulkan 1.2+ (or VK_EXT_descriptor_indexing)'s descriptorBinding*UpdateAfterBind features for sampled images, storage images, uniform buffers and storage buffers
+        ///
+        /// This is a native only feature.
+        const UPDATE_AFTER_BIND_BINDING_ARRAY = 1 << 57;
 
         // Shader:
 
@@ -5054,7 +5060,7 @@ bitflags::bitflags! {
 impl_bitflags!(TextureUsages);
 
 /// Defines the capabilities of a given surface and adapter.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct SurfaceCapabilities {
     /// List of supported formats to use with the given adapter. The first format in the vector is preferred.
     ///
@@ -6309,6 +6315,57 @@ pub struct BindGroupLayoutEntry {
     pub count: Option<NonZeroU32>,
 }
 
+bitflags::bitflags! {
+    /// Flags changing how the bindings of a bind group layout may be used.
+    ///
+    /// These are native only.
+    #[repr(transparent)]
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct BindGroupLayoutFlags: u32 {
+        /// Bind groups may contain binding arrays with fewer elements than
+        /// the layout's `count`, leaving the rest unbound.
+        ///
+        /// Requires [`Features::PARTIALLY_BOUND_BINDING_ARRAY`], with which
+        /// every layout behaves as if it had this flag.
+        const PARTIALLY_BOUND = 1 << 0;
+        /// The descriptors of bind groups using this layout are allocated so
+        /// they may be updated after being bound, which lifts some backend
+        /// limits on their number. Bindings with a dynamic offset aren't
+        /// allowed in such a layout.
+        ///
+        /// Requires [`Features::UPDATE_AFTER_BIND_BINDING_ARRAY`].
+        const UPDATE_AFTER_BIND = 1 << 1;
+    }
+}
+
+impl_bitflags!(BindGroupLayoutFlags);
+
+impl BindGroupLayoutFlags {
+    /// Returns the features required to create a layout with these flags.
+    pub fn required_features(&self) -> Features {
+        let mut features = Features::empty();
+        if self.contains(Self::PARTIALLY_BOUND) {
+            features |= Features::PARTIALLY_BOUND_BINDING_ARRAY;
+        }
+        if self.contains(Self::UPDATE_AFTER_BIND) {
+            features |= Features::UPDATE_AFTER_BIND_BINDING_ARRAY;
+        }
+        features
+    }
+}
+
+#[test]
+fn test_bind_group_layout_flags_required_features() {
+    assert_eq!(
+        BindGroupLayoutFlags::empty().required_features(),
+        Features::empty()
+    );
+    assert_eq!(
+        BindGroupLayoutFlags::all().required_features(),
+        Features::PARTIALLY_BOUND_BINDING_ARRAY | Features::UPDATE_AFTER_BIND_BINDING_ARRAY
+    );
+}
+
 /// View of a buffer which can be used to copy to/from a texture.
 ///
 /// Corresponds to [WebGPU `GPUImageCopyBuffer`](
@@ -6898,7 +6955,7 @@ pub enum Gles3MinorVersion {
 }
 
 /// Options for creating an instance.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct InstanceDescriptor {
     /// Which `Backends` to enable.
     pub backends: Backends,
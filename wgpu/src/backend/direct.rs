@@ -232,6 +232,22 @@ impl Context {
         self.0.generate_report()
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn device_start_poll_thread(&self, device: &Device, interval: std::time::Duration) {
+        let global = &self.0;
+        if let Err(cause) =
+            wgc::gfx_select!(device.id => global.start_poll_thread(device.id, interval))
+        {
+            self.handle_error_nolabel(&device.error_sink, cause, "Device::start_poll_thread");
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn device_stop_poll_thread(&self, device: &Device) {
+        let global = &self.0;
+        wgc::gfx_select!(device.id => global.stop_poll_thread(device.id))
+    }
+
     fn handle_error(
         &self,
         sink_mutex: &Mutex<ErrorSinkRaw>,
@@ -942,6 +958,7 @@ impl crate::Context for Context {
         let descriptor = wgc::binding_model::BindGroupLayoutDescriptor {
             label: desc.label.map(Borrowed),
             entries: Borrowed(desc.entries),
+            flags: wgt::BindGroupLayoutFlags::empty(),
         };
         let (id, error) = wgc::gfx_select!(
             device => global.device_create_bind_group_layout(*device, &descriptor, ())
@@ -1435,10 +1452,13 @@ impl crate::Context for Context {
         _device_data: &Self::DeviceData,
         message: &str,
     ) {
-        // We do not provide a reason to device_lose, because all reasons other than
-        // destroyed (which this is not) are "unknown".
+        // All reasons other than destroyed (which this is not) are "unknown".
         let global = &self.0;
-        wgc::gfx_select!(device => global.device_mark_lost(*device, message));
+        wgc::gfx_select!(device => global.device_mark_lost(
+            *device,
+            wgt::DeviceLostReason::Unknown,
+            message
+        ));
     }
     fn device_poll(
         &self,
@@ -1521,6 +1541,28 @@ impl crate::Context for Context {
         buffer: &Self::BufferId,
         _buffer_data: &Self::BufferData,
         sub_range: Range<wgt::BufferAddress>,
+    ) -> Box<dyn crate::context::BufferMappedRange> {
+        let size = sub_range.end - sub_range.start;
+        let global = &self.0;
+        match wgc::gfx_select!(buffer => global.buffer_get_mapped_const_range(
+            *buffer,
+            sub_range.start,
+            Some(size)
+        )) {
+            Ok((ptr, size)) => Box::new(BufferMappedRange {
+                // Only `BufferView`, which never writes, asks for this range.
+                ptr: ptr.cast_mut(),
+                size: size as usize,
+            }),
+            Err(err) => self.handle_error_fatal(err, "Buffer::get_mapped_range"),
+        }
+    }
+
+    fn buffer_get_mapped_range_mut(
+        &self,
+        buffer: &Self::BufferId,
+        _buffer_data: &Self::BufferData,
+        sub_range: Range<wgt::BufferAddress>,
     ) -> Box<dyn crate::context::BufferMappedRange> {
         let size = sub_range.end - sub_range.start;
         let global = &self.0;
@@ -1533,7 +1575,7 @@ impl crate::Context for Context {
                 ptr,
                 size: size as usize,
             }),
-            Err(err) => self.handle_error_fatal(err, "Buffer::get_mapped_range"),
+            Err(err) => self.handle_error_fatal(err, "Buffer::get_mapped_range_mut"),
         }
     }
 
@@ -2292,7 +2334,7 @@ impl crate::Context for Context {
     fn queue_submit<I: Iterator<Item = (Self::CommandBufferId, Self::CommandBufferData)>>(
         &self,
         queue: &Self::QueueId,
-        _queue_data: &Self::QueueData,
+        queue_data: &Self::QueueData,
         command_buffers: I,
     ) -> (Self::SubmissionIndex, Self::SubmissionIndexData) {
         let temp_command_buffers = command_buffers
@@ -2303,6 +2345,20 @@ impl crate::Context for Context {
         let index = match wgc::gfx_select!(*queue => global.queue_submit(*queue, &temp_command_buffers))
         {
             Ok(index) => index,
+            // One of the command buffers couldn't be submitted, so nothing
+            // was. Submitting still consumes the command buffers.
+            Err(err @ wgc::device::queue::QueueSubmitError::CommandBuffer { .. }) => {
+                for command_buffer in &temp_command_buffers {
+                    wgc::gfx_select!(*command_buffer => global.command_buffer_drop(*command_buffer));
+                }
+                self.handle_error_nolabel(&queue_data.error_sink, err, "Queue::submit");
+                // There is no submission of its own to wait for, so hand back
+                // the last one instead.
+                match wgc::gfx_select!(*queue => global.queue_get_last_submission_index(*queue)) {
+                    Ok(index) => index,
+                    Err(err) => self.handle_error_fatal(err, "Queue::submit"),
+                }
+            }
             Err(err) => self.handle_error_fatal(err, "Queue::submit"),
         };
 
@@ -2029,6 +2029,16 @@ impl crate::context::Context for Context {
         })
     }
 
+    fn buffer_get_mapped_range_mut(
+        &self,
+        buffer: &Self::BufferId,
+        buffer_data: &Self::BufferData,
+        sub_range: Range<wgt::BufferAddress>,
+    ) -> Box<dyn crate::context::BufferMappedRange> {
+        // The browser hands out a copy of the mapping either way.
+        crate::context::Context::buffer_get_mapped_range(self, buffer, buffer_data, sub_range)
+    }
+
     fn buffer_get_mapped_range_as_array_buffer(
         &self,
         _buffer: &Self::BufferId,
@@ -320,6 +320,12 @@ pub trait Context: Debug + WasmNotSendSync + Sized {
         buffer_data: &Self::BufferData,
         sub_range: Range<BufferAddress>,
     ) -> Box<dyn BufferMappedRange>;
+    fn buffer_get_mapped_range_mut(
+        &self,
+        buffer: &Self::BufferId,
+        buffer_data: &Self::BufferData,
+        sub_range: Range<BufferAddress>,
+    ) -> Box<dyn BufferMappedRange>;
     #[cfg(webgpu)]
     fn buffer_get_mapped_range_as_array_buffer(
         &self,
@@ -1342,6 +1348,12 @@ pub(crate) trait DynContext: Debug + WasmNotSendSync {
         buffer_data: &crate::Data,
         sub_range: Range<BufferAddress>,
     ) -> Box<dyn BufferMappedRange>;
+    fn buffer_get_mapped_range_mut(
+        &self,
+        buffer: &ObjectId,
+        buffer_data: &crate::Data,
+        sub_range: Range<BufferAddress>,
+    ) -> Box<dyn BufferMappedRange>;
     #[cfg(webgpu)]
     fn buffer_get_mapped_range_as_array_buffer(
         &self,
@@ -2461,6 +2473,17 @@ where
         Context::buffer_get_mapped_range(self, &buffer, buffer_data, sub_range)
     }
 
+    fn buffer_get_mapped_range_mut(
+        &self,
+        buffer: &ObjectId,
+        buffer_data: &crate::Data,
+        sub_range: Range<BufferAddress>,
+    ) -> Box<dyn BufferMappedRange> {
+        let buffer = <T::BufferId>::from(*buffer);
+        let buffer_data = downcast_ref(buffer_data);
+        Context::buffer_get_mapped_range_mut(self, &buffer, buffer_data, sub_range)
+    }
+
     #[cfg(webgpu)]
     fn buffer_get_mapped_range_as_array_buffer(
         &self,
@@ -2620,6 +2620,34 @@ impl Device {
         }
     }
 
+    /// Poll this device every `interval` on a background thread.
+    ///
+    /// This fires the callbacks of completed work, such as [`BufferSlice::map_async`],
+    /// without the application calling [`Device::poll`]. Polling it as well
+    /// is fine, each callback is still called once. A thread already polling
+    /// this device is replaced.
+    ///
+    /// The thread runs until [`Device::stop_poll_thread`] is called or the
+    /// device is dropped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_poll_thread(&self, interval: std::time::Duration) {
+        self.context
+            .as_any()
+            .downcast_ref::<crate::backend::Context>()
+            .unwrap()
+            .device_start_poll_thread(self.data.as_ref().downcast_ref().unwrap(), interval)
+    }
+
+    /// Stop the thread started by [`Device::start_poll_thread`], if any.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stop_poll_thread(&self) {
+        self.context
+            .as_any()
+            .downcast_ref::<crate::backend::Context>()
+            .unwrap()
+            .device_stop_poll_thread(self.data.as_ref().downcast_ref().unwrap())
+    }
+
     /// Destroy this device.
     pub fn destroy(&self) {
         DynContext::device_destroy(&*self.context, &self.id, self.data.as_ref())
@@ -3005,7 +3033,7 @@ impl<'a> BufferSlice<'a> {
     /// through [`BufferDescriptor::mapped_at_creation`] or [`BufferSlice::map_async`], will panic.
     pub fn get_mapped_range_mut(&self) -> BufferViewMut<'a> {
         let end = self.buffer.map_context.lock().add(self.offset, self.size);
-        let data = DynContext::buffer_get_mapped_range(
+        let data = DynContext::buffer_get_mapped_range_mut(
             &*self.buffer.context,
             &self.buffer.id,
             self.buffer.data.as_ref(),